@@ -0,0 +1,168 @@
+#![allow(clippy::result_large_err)]
+
+// stand-in for bubblegum and account compression in tests. a tree keeps every
+// node of a small keccak merkle tree, `transfer` takes the same accounts and
+// arguments as bubblegum's and checks the proof against the current root
+
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRUUY");
+
+pub const MAX_DEPTH: usize = 3;
+// nodes[1] is the root, the children of nodes[i] are nodes[2i] and nodes[2i + 1]
+pub const NODE_COUNT: usize = 2 << MAX_DEPTH;
+
+#[program]
+pub mod bubblegum_stand_in {
+    use super::*;
+
+    pub fn create_tree(ctx: Context<CreateTree>) -> Result<()> {
+        let tree = &mut ctx.accounts.merkle_tree;
+        for i in (1..NODE_COUNT / 2).rev() {
+            tree.nodes[i] = hash_pair(&tree.nodes[2 * i], &tree.nodes[2 * i + 1]);
+        }
+        Ok(())
+    }
+
+    // appends a leaf owned and delegated by `owner`, its nonce is its index
+    pub fn mint(
+        ctx: Context<Mint>,
+        owner: Pubkey,
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+    ) -> Result<()> {
+        let tree = &mut ctx.accounts.merkle_tree;
+        let index = tree.num_minted;
+        if index as usize >= NODE_COUNT / 2 {
+            return err!(StandInError::TreeFull);
+        }
+        let leaf = hash_leaf(&owner, &owner, index, &data_hash, &creator_hash);
+        tree.set_leaf(index as u32, leaf);
+        tree.num_minted += 1;
+        Ok(())
+    }
+
+    pub fn transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, Transfer<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        let accounts = &ctx.accounts;
+        if !accounts.leaf_owner.is_signer && !accounts.leaf_delegate.is_signer {
+            return err!(StandInError::LeafAuthorityMustSign);
+        }
+        let owner = accounts.leaf_owner.key();
+        let delegate = accounts.leaf_delegate.key();
+        let new_owner = accounts.new_leaf_owner.key();
+        let tree = &mut ctx.accounts.merkle_tree;
+        if root != tree.nodes[1] || ctx.remaining_accounts.len() != MAX_DEPTH {
+            return err!(StandInError::InvalidProof);
+        }
+        let mut node = hash_leaf(&owner, &delegate, nonce, &data_hash, &creator_hash);
+        let mut i = index as usize;
+        for sibling in ctx.remaining_accounts {
+            let sibling = sibling.key().to_bytes();
+            node = if i & 1 == 0 {
+                hash_pair(&node, &sibling)
+            } else {
+                hash_pair(&sibling, &node)
+            };
+            i /= 2;
+        }
+        if node != root {
+            return err!(StandInError::InvalidProof);
+        }
+
+        let leaf = hash_leaf(&new_owner, &new_owner, nonce, &data_hash, &creator_hash);
+        tree.set_leaf(index, leaf);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Tree {
+    pub num_minted: u64,
+    pub nodes: [[u8; 32]; NODE_COUNT],
+}
+
+impl Tree {
+    pub const LEN: usize = 8 + 8 + 32 * NODE_COUNT;
+
+    fn set_leaf(&mut self, index: u32, leaf: [u8; 32]) {
+        let mut i = NODE_COUNT / 2 + index as usize;
+        self.nodes[i] = leaf;
+        while i > 1 {
+            i /= 2;
+            self.nodes[i] = hash_pair(&self.nodes[2 * i], &self.nodes[2 * i + 1]);
+        }
+    }
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[left, right]).to_bytes()
+}
+
+fn hash_leaf(
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    nonce: u64,
+    data_hash: &[u8; 32],
+    creator_hash: &[u8; 32],
+) -> [u8; 32] {
+    keccak::hashv(&[
+        owner.as_ref(),
+        delegate.as_ref(),
+        &nonce.to_le_bytes(),
+        data_hash,
+        creator_hash,
+    ])
+    .to_bytes()
+}
+
+#[derive(Accounts)]
+pub struct CreateTree<'info> {
+    #[account(init, payer = payer, space = Tree::LEN)]
+    pub merkle_tree: Account<'info, Tree>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Mint<'info> {
+    #[account(mut)]
+    pub merkle_tree: Account<'info, Tree>,
+}
+
+// the accounts of bubblegum's transfer, the proof is the remaining accounts
+#[derive(Accounts)]
+pub struct Transfer<'info> {
+    /// CHECK: bubblegum's tree config, not kept by the stand-in
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: checked against the leaf
+    pub leaf_owner: UncheckedAccount<'info>,
+    /// CHECK: checked against the leaf
+    pub leaf_delegate: UncheckedAccount<'info>,
+    /// CHECK: the owner of the new leaf
+    pub new_leaf_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub merkle_tree: Account<'info, Tree>,
+    /// CHECK: not called by the stand-in
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: not called by the stand-in
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[error_code]
+pub enum StandInError {
+    #[msg("TreeFull")]
+    TreeFull,
+    #[msg("LeafAuthorityMustSign")]
+    LeafAuthorityMustSign,
+    #[msg("InvalidProof")]
+    InvalidProof,
+}
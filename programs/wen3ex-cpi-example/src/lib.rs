@@ -0,0 +1,232 @@
+#![allow(clippy::result_large_err)]
+
+// example consumer of the wen3ex cpi entrypoints, a desk is a pda wallet of
+// `owner` that lists and fills wen3ex markets on its own, signing with its seeds
+
+use anchor_lang::prelude::*;
+use wen3ex::cpi::accounts::{MarketStExchange, MarketTsCreate, MarketTsExchange};
+use wen3ex::program::Wen3ex;
+
+declare_id!("CsqkXLZ5yg4SGAh8UexZrFV7VwDnBiBX1doUoouGvwjv");
+
+pub const DESK_SEED: &[u8] = b"desk-seed";
+
+#[program]
+pub mod wen3ex_cpi_example {
+    use super::*;
+
+    // the desk sells token_amount of its tokens for sol_amount
+    pub fn list<'info>(
+        ctx: Context<'_, '_, '_, 'info, List<'info>>,
+        token_amount: u64,
+        sol_amount: u64,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let seeds = &[
+            DESK_SEED,
+            owner.as_ref(),
+            &[*ctx.bumps.get("desk").unwrap()],
+        ];
+        let accounts = &ctx.accounts.market;
+        let cpi_accounts = MarketTsCreate {
+            market_account: accounts.market_account.to_account_info(),
+            vault_token_account: accounts.vault_token_account.to_account_info(),
+            creator_token_account: accounts.desk_token_account.to_account_info(),
+            mint: accounts.mint.to_account_info(),
+            creator: ctx.accounts.desk.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            config: accounts.config.to_account_info(),
+            mint_entry: accounts.mint_entry.to_account_info(),
+            mint_index: accounts.mint_index.to_account_info(),
+            creator_index: accounts.creator_index.to_account_info(),
+        };
+        wen3ex::cpi::market_ts_create(
+            CpiContext::new_with_signer(
+                ctx.accounts.wen3ex_program.to_account_info(),
+                cpi_accounts,
+                &[&seeds[..]],
+            ),
+            token_amount,
+            sol_amount,
+            0,
+            0,
+            0,
+            0,
+            0,
+        )
+    }
+
+    // the desk buys a token 2 sol listing, referrer accounts are passed on
+    pub fn buy<'info>(ctx: Context<'_, '_, '_, 'info, Buy<'info>>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let seeds = &[
+            DESK_SEED,
+            owner.as_ref(),
+            &[*ctx.bumps.get("desk").unwrap()],
+        ];
+        let accounts = &ctx.accounts.market;
+        let cpi_accounts = MarketTsExchange {
+            taker: ctx.accounts.desk.to_account_info(),
+            taker_token_account: accounts.desk_token_account.to_account_info(),
+            creator: accounts.creator.to_account_info(),
+            market_account: accounts.market_account.to_account_info(),
+            vault_token_account: accounts.vault_token_account.to_account_info(),
+            mint: accounts.mint.to_account_info(),
+            vault_authority: accounts.vault_authority.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            config: accounts.config.to_account_info(),
+            treasury: accounts.treasury.to_account_info(),
+            sale_phase: ctx.accounts.sale_phase.to_account_info(),
+            mint_index: accounts.mint_index.to_account_info(),
+            creator_index: accounts.creator_index.to_account_info(),
+        };
+        wen3ex::cpi::market_ts_exchange(
+            CpiContext::new_with_signer(
+                ctx.accounts.wen3ex_program.to_account_info(),
+                cpi_accounts,
+                &[&seeds[..]],
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        )
+    }
+
+    // the desk sells its tokens into a sol 2 token listing
+    pub fn sell<'info>(ctx: Context<'_, '_, '_, 'info, Sell<'info>>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let seeds = &[
+            DESK_SEED,
+            owner.as_ref(),
+            &[*ctx.bumps.get("desk").unwrap()],
+        ];
+        let accounts = &ctx.accounts.market;
+        let cpi_accounts = MarketStExchange {
+            taker: ctx.accounts.desk.to_account_info(),
+            taker_token_account: accounts.desk_token_account.to_account_info(),
+            creator: accounts.creator.to_account_info(),
+            creator_token_account: ctx.accounts.creator_token_account.to_account_info(),
+            market_account: accounts.market_account.to_account_info(),
+            vault_token_account: accounts.vault_token_account.to_account_info(),
+            mint: accounts.mint.to_account_info(),
+            vault_authority: accounts.vault_authority.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            config: accounts.config.to_account_info(),
+            treasury: accounts.treasury.to_account_info(),
+            mint_index: accounts.mint_index.to_account_info(),
+            creator_index: accounts.creator_index.to_account_info(),
+        };
+        wen3ex::cpi::market_st_exchange(
+            CpiContext::new_with_signer(
+                ctx.accounts.wen3ex_program.to_account_info(),
+                cpi_accounts,
+                &[&seeds[..]],
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        )
+    }
+}
+
+// wen3ex checks every market account itself, they are passed through unchecked
+#[derive(Accounts)]
+pub struct List<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [DESK_SEED, owner.key().as_ref()], bump)]
+    /// CHECK: system account of the desk, signs for wen3ex
+    pub desk: AccountInfo<'info>,
+    pub market: DeskCreate<'info>,
+    pub wen3ex_program: Program<'info, Wen3ex>,
+}
+
+#[derive(Accounts)]
+pub struct DeskCreate<'info> {
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub market_account: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub vault_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub desk_token_account: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub mint: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub system_program: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub rent: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub config: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub creator_index: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [DESK_SEED, owner.key().as_ref()], bump)]
+    /// CHECK: system account of the desk, signs for wen3ex
+    pub desk: AccountInfo<'info>,
+    pub market: DeskExchange<'info>,
+    /// CHECK: checked by wen3ex
+    pub sale_phase: AccountInfo<'info>,
+    pub wen3ex_program: Program<'info, Wen3ex>,
+}
+
+#[derive(Accounts)]
+pub struct Sell<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut, seeds = [DESK_SEED, owner.key().as_ref()], bump)]
+    /// CHECK: system account of the desk, signs for wen3ex
+    pub desk: AccountInfo<'info>,
+    pub market: DeskExchange<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub creator_token_account: AccountInfo<'info>,
+    pub wen3ex_program: Program<'info, Wen3ex>,
+}
+
+#[derive(Accounts)]
+pub struct DeskExchange<'info> {
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub desk_token_account: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub creator: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub market_account: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub vault_token_account: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub mint: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub system_program: AccountInfo<'info>,
+    /// CHECK: checked by wen3ex
+    pub config: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub treasury: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked by wen3ex
+    pub creator_index: AccountInfo<'info>,
+}
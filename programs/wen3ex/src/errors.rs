@@ -26,4 +26,144 @@ pub enum Wen3ExError {
     IncorrectVaultAuthorityBump,
     #[msg("IncorrectVaultAuthorityAccount")]
     IncorrectVaultAuthorityAccount,
+    #[msg("MarketVersionMismatch, run migrate_market first")]
+    MarketVersionMismatch,
+    #[msg("MarketAlreadyMigrated")]
+    MarketAlreadyMigrated,
+    #[msg("UnknownMarketAccount")]
+    UnknownMarketAccount,
+    #[msg("MarketPaused")]
+    MarketPaused,
+    #[msg("MintNotAllowed, the mint is not listable on this market")]
+    MintNotAllowed,
+    #[msg("InvalidMintStatus")]
+    InvalidMintStatus,
+    #[msg("InvalidOrderSide")]
+    InvalidOrderSide,
+    #[msg("InvalidOrderType")]
+    InvalidOrderType,
+    #[msg("OrderWouldCross, post only order matches a resting order")]
+    OrderWouldCross,
+    #[msg("NoMakerBalanceAccount")]
+    NoMakerBalanceAccount,
+    #[msg("IncorrectMakerBalanceAccount")]
+    IncorrectMakerBalanceAccount,
+    #[msg("OrderBookFull")]
+    OrderBookFull,
+    #[msg("OrderNotFound")]
+    OrderNotFound,
+    #[msg("InvalidSweepAccounts")]
+    InvalidSweepAccounts,
+    #[msg("OfferListingMismatch")]
+    OfferListingMismatch,
+    #[msg("InvalidFee")]
+    InvalidFee,
+    #[msg("IncorrectTreasuryAccount")]
+    IncorrectTreasuryAccount,
+    #[msg("IncorrectReferrerAccount")]
+    IncorrectReferrerAccount,
+    #[msg("InvalidExpireTime, must be 0 or in the future")]
+    InvalidExpireTime,
+    #[msg("ListingExpired")]
+    ListingExpired,
+    #[msg("ListingNotExpired")]
+    ListingNotExpired,
+    #[msg("NotYetOpen, the listing is not fillable before its start time")]
+    NotYetOpen,
+    #[msg("InvalidSaleWindow, start time must be before end time")]
+    InvalidSaleWindow,
+    #[msg("SaleClosed")]
+    SaleClosed,
+    #[msg("SaleNotClosed")]
+    SaleNotClosed,
+    #[msg("SaleCapExceeded")]
+    SaleCapExceeded,
+    #[msg("WalletCapExceeded")]
+    WalletCapExceeded,
+    #[msg("AllowlistRequired, the listing is in its presale")]
+    AllowlistRequired,
+    #[msg("NotAllowlisted")]
+    NotAllowlisted,
+    #[msg("AllocationExceeded")]
+    AllocationExceeded,
+    #[msg("InvalidVestingSchedule, cliff must be between 0 and the duration")]
+    InvalidVestingSchedule,
+    #[msg("VestingRequired, take the listing with market_ts_exchange_vested")]
+    VestingRequired,
+    #[msg("NotVesting")]
+    NotVesting,
+    #[msg("NothingToClaim")]
+    NothingToClaim,
+    #[msg("InvalidDeadline, must be in the future")]
+    InvalidDeadline,
+    #[msg("IncorrectRecipientTokenAccount")]
+    IncorrectRecipientTokenAccount,
+    #[msg("InvalidOracle")]
+    InvalidOracle,
+    #[msg("StaleOraclePrice")]
+    StaleOraclePrice,
+    #[msg("OracleConfidenceTooWide")]
+    OracleConfidenceTooWide,
+    #[msg("OraclePriceRequired, take the listing with market_ts_exchange_pegged")]
+    OraclePriceRequired,
+    #[msg("PriceAboveMax")]
+    PriceAboveMax,
+    #[msg("InvalidPoolMints, mint_a must sort before mint_b")]
+    InvalidPoolMints,
+    #[msg("InsufficientLiquidity")]
+    InsufficientLiquidity,
+    #[msg("SlippageExceeded")]
+    SlippageExceeded,
+    #[msg("InvalidRouteAccounts")]
+    InvalidRouteAccounts,
+    #[msg("RouteUnfilled, no listing could be taken")]
+    RouteUnfilled,
+    #[msg("PriceBelowMin")]
+    PriceBelowMin,
+    #[msg("IncorrectSourceTokenAccount")]
+    IncorrectSourceTokenAccount,
+    #[msg("DelegationRevoked, the source token account no longer covers the listing")]
+    DelegationRevoked,
+    #[msg("ListingNotStale")]
+    ListingNotStale,
+    #[msg("InvalidMetadata")]
+    InvalidMetadata,
+    #[msg("IncorrectVaultTokenAccount")]
+    IncorrectVaultTokenAccount,
+    #[msg("InvalidSwapRequest, ask for a mint or a collection, sol one way")]
+    InvalidSwapRequest,
+    #[msg("SwapFull")]
+    SwapFull,
+    #[msg("InvalidSwapAccounts")]
+    InvalidSwapAccounts,
+    #[msg("NftNotWanted")]
+    NftNotWanted,
+    #[msg("VaultConsistent, the vault holds the deposit, cancel the listing instead")]
+    VaultConsistent,
+    #[msg("ReclaimTooEarly")]
+    ReclaimTooEarly,
+    #[msg("InvalidListingIndex")]
+    InvalidListingIndex,
+    #[msg("MintHasFreezeAuthority")]
+    MintHasFreezeAuthority,
+    #[msg("VaultFrozen, the mint's freeze authority froze the vault")]
+    VaultFrozen,
+    #[msg("DecimalsNotRecorded, the listing predates recorded decimals")]
+    DecimalsNotRecorded,
+    #[msg("OrderTooSmall, below the order book's min_lots")]
+    OrderTooSmall,
+    #[msg("SelfReferral, the taker can't refer their own trade")]
+    SelfReferral,
+    #[msg("VestingNotPeggable, a vesting listing has a fixed price")]
+    VestingNotPeggable,
+    #[msg("EscrowMintMismatch, the escrow holds another mint")]
+    EscrowMintMismatch,
+    #[msg("InvalidOracleLimits, max age must be positive and max confidence at most 10%")]
+    InvalidOracleLimits,
+    #[msg("NftVaultMismatch, the listing keeps its nft in the other kind of vault")]
+    NftVaultMismatch,
+    #[msg("NotAnNft, the mint has decimals or a supply other than 1")]
+    NotAnNft,
+    #[msg("PoolNotEmpty, the vaults of a pool without lp hold tokens")]
+    PoolNotEmpty,
 }
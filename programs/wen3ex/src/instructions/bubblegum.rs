@@ -0,0 +1,97 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+// the parts of metaplex bubblegum we call, its `transfer` instruction moves a
+// compressed nft leaf to a new owner given the current root and a merkle proof
+pub mod bubblegum_program {
+    anchor_lang::declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRUUY");
+}
+
+pub mod spl_account_compression {
+    anchor_lang::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+
+pub mod spl_noop {
+    anchor_lang::declare_id!("noop8ytexvkpCuqbf6FB89BSuNemHtPRqaNC31GWivW");
+}
+
+// sha256("global:transfer")[..8]
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+// a leaf as bubblegum hashes it, root is the tree's current root
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeafArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+pub struct CnftTransfer<'a, 'info> {
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub tree_authority: &'a AccountInfo<'info>,
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub leaf_delegate: &'a AccountInfo<'info>,
+    pub new_leaf_owner: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CnftTransfer<'a, 'info> {
+    // `proof` is the path from the leaf up, less the nodes in the tree's canopy
+    pub fn invoke(
+        &self,
+        leaf: &LeafArgs,
+        proof: &[AccountInfo<'info>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.tree_authority.key(), false),
+            // the owner signs, a wallet in the transaction or a PDA through signer_seeds
+            AccountMeta::new_readonly(self.leaf_owner.key(), true),
+            AccountMeta::new_readonly(self.leaf_delegate.key(), false),
+            AccountMeta::new_readonly(self.new_leaf_owner.key(), false),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        accounts.extend(
+            proof
+                .iter()
+                .map(|node| AccountMeta::new_readonly(node.key(), false)),
+        );
+        let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+        leaf.serialize(&mut data)?;
+
+        let mut account_infos = vec![
+            self.tree_authority.clone(),
+            self.leaf_owner.clone(),
+            self.leaf_delegate.clone(),
+            self.new_leaf_owner.clone(),
+            self.merkle_tree.clone(),
+            self.log_wrapper.clone(),
+            self.compression_program.clone(),
+            self.system_program.clone(),
+        ];
+        account_infos.extend_from_slice(proof);
+        invoke_signed(
+            &Instruction {
+                program_id: bubblegum_program::ID,
+                accounts,
+                data,
+            },
+            &account_infos,
+            signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{BPS_DENOMINATOR, MAX_FEE_BPS},
+    state_config::{ConfigAcceptAdmin, ConfigAccount, ConfigUpdate, Initialize},
+};
+
+pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    ctx.accounts.check_upgrade_authority()?;
+
+    let config = &mut ctx.accounts.config;
+
+    config.version = ConfigAccount::VERSION;
+    config.admin = ctx.accounts.admin.key();
+    config.pending_admin = Pubkey::default();
+    config.paused_markets = 0;
+    config.treasury = ctx.accounts.admin.key();
+    config.bump = *ctx.bumps.get("config").unwrap();
+
+    Ok(())
+}
+
+pub fn set_paused(ctx: Context<ConfigUpdate>, markets: u32, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if paused {
+        config.paused_markets |= markets;
+    } else {
+        config.paused_markets &= !markets;
+    }
+    msg!("paused markets {:#b}", config.paused_markets);
+
+    Ok(())
+}
+
+pub fn set_fees(
+    ctx: Context<ConfigUpdate>,
+    fee_bps: u32,
+    referral_share_bps: u32,
+    treasury: Pubkey,
+) -> Result<()> {
+    if fee_bps > MAX_FEE_BPS || referral_share_bps > BPS_DENOMINATOR as u32 {
+        return err!(Wen3ExError::InvalidFee);
+    }
+    let config = &mut ctx.accounts.config;
+
+    config.fee_bps = fee_bps;
+    config.referral_share_bps = referral_share_bps;
+    config.treasury = treasury;
+
+    Ok(())
+}
+
+// step one of the admin handover, the new admin has to accept
+pub fn propose_admin(ctx: Context<ConfigUpdate>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.pending_admin = new_admin;
+    Ok(())
+}
+
+pub fn accept_admin(ctx: Context<ConfigAcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+
+    Ok(())
+}
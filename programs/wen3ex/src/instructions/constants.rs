@@ -5,4 +5,90 @@ pub const VAULT_TOKEN_2_SOL_SEED: &[u8] = b"vault-token-2-sol-seed";
 pub const VAULT_SOL_2_TOKEN_SEED: &[u8] = b"vault-sol-2-token-seed";
 
 pub const VAULT_NFT_2_SOL_SEED: &[u8] = b"vault-nft-2-sol-seed";
-pub const VAULT_NFT_2_TOKEN_SEED: &[u8] = b"vault-nft-2-token-seed";
+// where a nft 2 sol listing keeps its nft: the VAULT_NFT_2_SOL_SEED PDA moved
+// by spl token, or the vault authority's ATA moved by token metadata (pnfts)
+pub const NFT_VAULT_TOKEN: u8 = 0;
+pub const NFT_VAULT_PROGRAMMABLE: u8 = 1;
+
+pub const CONFIG_SEED: &[u8] = b"config-seed";
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_FEE_BPS: u32 = 1_000;
+pub const MAX_ORACLE_CONF_BPS: u32 = 1_000;
+
+// market type bits, used by ConfigAccount.paused_markets
+pub const MARKET_TT: u32 = 1;
+pub const MARKET_TS: u32 = 1 << 1;
+pub const MARKET_ST: u32 = 1 << 2;
+pub const MARKET_NS: u32 = 1 << 3;
+pub const MARKET_ORDER_BOOK: u32 = 1 << 4;
+pub const MARKET_OFFER: u32 = 1 << 5;
+pub const MARKET_LAUNCHPAD: u32 = 1 << 6;
+pub const MARKET_ESCROW: u32 = 1 << 7;
+pub const MARKET_POOL: u32 = 1 << 8;
+pub const MARKET_DELEGATED: u32 = 1 << 9;
+pub const MARKET_CNFT: u32 = 1 << 10;
+pub const MARKET_SWAP: u32 = 1 << 11;
+
+pub const MINT_ENTRY_SEED: &[u8] = b"mint-entry-seed";
+
+// MintEntryAccount.status
+pub const MINT_STATUS_ALLOWED: u8 = 1;
+pub const MINT_STATUS_DENIED: u8 = 2;
+
+pub const ORDER_BOOK_SEED: &[u8] = b"order-book-seed";
+pub const VAULT_ORDER_BOOK_BASE_SEED: &[u8] = b"vault-order-book-base-seed";
+pub const VAULT_ORDER_BOOK_QUOTE_SEED: &[u8] = b"vault-order-book-quote-seed";
+pub const ORDER_BOOK_BALANCE_SEED: &[u8] = b"order-book-balance-seed";
+
+// order book sides and order types
+pub const SIDE_BID: u8 = 0;
+pub const SIDE_ASK: u8 = 1;
+pub const ORDER_TYPE_LIMIT: u8 = 0;
+pub const ORDER_TYPE_IOC: u8 = 1;
+pub const ORDER_TYPE_POST_ONLY: u8 = 2;
+
+pub const OFFER_SEED: &[u8] = b"offer-seed";
+
+pub const REFERRER_SEED: &[u8] = b"referrer-seed";
+
+pub const LAUNCHPAD_SEED: &[u8] = b"launchpad-seed";
+pub const VAULT_LAUNCHPAD_SEED: &[u8] = b"vault-launchpad-seed";
+pub const PURCHASE_SEED: &[u8] = b"purchase-seed";
+
+pub const SALE_PHASE_SEED: &[u8] = b"sale-phase-seed";
+pub const ALLOWLIST_CLAIM_SEED: &[u8] = b"allowlist-claim-seed";
+
+pub const VESTING_SEED: &[u8] = b"vesting-seed";
+pub const VAULT_VESTING_SEED: &[u8] = b"vault-vesting-seed";
+
+pub const ESCROW_SEED: &[u8] = b"escrow-seed";
+pub const VAULT_ESCROW_SEED: &[u8] = b"vault-escrow-seed";
+
+pub const POOL_SEED: &[u8] = b"pool-seed";
+pub const POOL_LP_MINT_SEED: &[u8] = b"pool-lp-mint-seed";
+pub const VAULT_POOL_SEED: &[u8] = b"vault-pool-seed";
+pub const POOL_LP_LOCK_SEED: &[u8] = b"pool-lp-lock-seed";
+pub const POOL_LP_DECIMALS: u8 = 9;
+// lp minted to the pool's lock account on the first deposit and never burned,
+// so the lp supply never drops back to zero
+pub const POOL_MINIMUM_LIQUIDITY: u64 = 1_000;
+
+pub const MINT_INDEX_SEED: &[u8] = b"mint-index-seed";
+pub const CREATOR_INDEX_SEED: &[u8] = b"creator-index-seed";
+pub const LISTING_INDEX_PAGE: usize = 64;
+
+// approved by creators of delegated listings on their token accounts
+pub const DELEGATE_SEED: &[u8] = b"delegate-seed";
+
+pub const CNFT_LISTING_SEED: &[u8] = b"cnft-listing-seed";
+
+pub const VAULT_SWAP_SEED: &[u8] = b"vault-swap-seed";
+
+// after which anyone may reclaim an inconsistent listing for its creator
+pub const RECLAIM_GRACE_PERIOD: i64 = 180 * 24 * 60 * 60;
+
+// sol amounts are lamports
+pub const SOL_DECIMALS: u8 = 9;
+// UnitPrice.price is scaled by 10^PRICE_DECIMALS
+pub const PRICE_DECIMALS: u8 = 12;
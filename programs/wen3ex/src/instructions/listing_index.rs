@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+use super::state_listing_index::{ListingIndexAccount, ListingIndexCreate};
+
+pub fn create(ctx: Context<ListingIndexCreate>, by_creator: bool) -> Result<()> {
+    let index = &mut ctx.accounts.index;
+
+    index.version = ListingIndexAccount::VERSION;
+    index.key = ctx.accounts.key.key();
+    index.by_creator = by_creator;
+    index.bump = *ctx.bumps.get("index").unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::VAULT_AUTHORITY_SEED,
+    state_cnft::{MarketCnftCancel, MarketCnftCreate, MarketCnftExchange, MarketCnftToSolAccount},
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    utils::{check_expire_time, send_lamports},
+};
+
+// the proof of the leaf is the remaining accounts, for every instruction below
+#[allow(clippy::too_many_arguments)]
+pub fn create<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketCnftCreate<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    sol_amount: u64,
+    expire_time: i64,
+    start_time: i64,
+) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    check_expire_time(expire_time, now_ts.max(start_time))?;
+
+    let market_account = &mut ctx.accounts.market_account;
+    market_account.version = MarketCnftToSolAccount::VERSION;
+    market_account.creator = ctx.accounts.creator.key();
+    market_account.merkle_tree = ctx.accounts.cnft.merkle_tree.key();
+    market_account.nonce = nonce;
+    market_account.index = index;
+    market_account.data_hash = data_hash;
+    market_account.creator_hash = creator_hash;
+    market_account.sol_amount = sol_amount;
+    market_account.create_time = now_ts;
+    market_account.bump = *ctx.bumps.get("market_account").unwrap();
+    market_account.expire_time = expire_time;
+    market_account.start_time = start_time;
+
+    let leaf = ctx.accounts.market_account.leaf(root);
+    ctx.accounts
+        .transfer_from_creator_to_vault()
+        .invoke(&leaf, ctx.remaining_accounts, &[])?;
+
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub fn cancel<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketCnftCancel<'info>>,
+    root: [u8; 32],
+) -> Result<()> {
+    let market_account_key = ctx.accounts.market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let leaf = ctx.accounts.market_account.leaf(root);
+    ctx.accounts.transfer_from_vault_to_creator().invoke(
+        &leaf,
+        ctx.remaining_accounts,
+        &[&authority_seeds[..]],
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )
+}
+
+// when `referred`, the [referrer, referrer_account] pair leads the remaining
+// accounts and the proof follows it
+pub fn exchange<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketCnftExchange<'info>>,
+    root: [u8; 32],
+    referred: bool,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !accounts.market_account.is_open(now_ts) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if accounts.market_account.is_expired(now_ts) {
+        return err!(Wen3ExError::ListingExpired);
+    }
+
+    let (mut referrer, proof) = if referred {
+        if ctx.remaining_accounts.len() < 2 {
+            return err!(Wen3ExError::IncorrectReferrerAccount);
+        }
+        let (referrer, proof) = ctx.remaining_accounts.split_at(2);
+        (Referrer::load(referrer, accounts.taker.key)?, proof)
+    } else {
+        (None, ctx.remaining_accounts)
+    };
+    let sol_amount = accounts.market_account.sol_amount;
+    let fees = accounts.config.fee_split(sol_amount, referrer.is_some())?;
+    send_lamports(
+        &accounts.taker,
+        &accounts.creator,
+        Some(&accounts.system_program),
+        sol_amount - fees.total(),
+    )?;
+    pay_fees(
+        ctx.program_id,
+        &accounts.taker,
+        &accounts.treasury,
+        referrer.as_mut(),
+        Some(&accounts.system_program),
+        &fees,
+        sol_amount,
+    )?;
+
+    let market_account_key = accounts.market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    let leaf = accounts.market_account.leaf(root);
+    accounts
+        .transfer_from_vault_to_taker()
+        .invoke(&leaf, proof, &[&authority_seeds[..]])?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        accounts.market_account.key(),
+    )
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{DELEGATE_SEED, MARKET_DELEGATED},
+    state_delegated::{
+        MarketDelegatedAccount, MarketDelegatedCancel, MarketDelegatedCloseStale,
+        MarketDelegatedCreate, MarketDelegatedExchange,
+    },
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    utils::{check_expire_time, send_lamports},
+};
+
+// the creator approves the delegate PDA for at least token_amount beforehand,
+// nothing moves until the exchange
+pub fn create(
+    ctx: Context<MarketDelegatedCreate>,
+    token_amount: u64,
+    sol_amount: u64,
+    expire_time: i64,
+) -> Result<()> {
+    ctx.accounts.config.check_mint_listable(
+        MARKET_DELEGATED,
+        ctx.program_id,
+        &ctx.accounts.mint_entry,
+    )?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_DELEGATED, &ctx.accounts.mint)?;
+    let now_ts = Clock::get()?.unix_timestamp;
+    check_expire_time(expire_time, now_ts)?;
+    if token_amount == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+
+    let source = ctx.accounts.source.to_account_info();
+    let market_account = &mut ctx.accounts.market_account;
+    market_account.version = MarketDelegatedAccount::VERSION;
+    market_account.creator = ctx.accounts.creator.key();
+    market_account.token = ctx.accounts.mint.key();
+    market_account.source = source.key();
+    market_account.token_amount = token_amount;
+    market_account.sol_amount = sol_amount;
+    market_account.create_time = now_ts;
+    market_account.expire_time = expire_time;
+    if !market_account.is_covered(ctx.program_id, &source)? {
+        return err!(Wen3ExError::DelegationRevoked);
+    }
+
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )?;
+
+    Ok(())
+}
+
+pub fn cancel(ctx: Context<MarketDelegatedCancel>) -> Result<()> {
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )
+}
+
+pub fn close_stale(ctx: Context<MarketDelegatedCloseStale>) -> Result<()> {
+    let market_account = &ctx.accounts.market_account;
+    if !market_account.is_expired(Clock::get()?.unix_timestamp)
+        && market_account.is_covered(ctx.program_id, &ctx.accounts.source)?
+    {
+        return err!(Wen3ExError::ListingNotStale);
+    }
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )
+}
+
+pub fn exchange<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketDelegatedExchange<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let market_account = &accounts.market_account;
+    if market_account.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::ListingExpired);
+    }
+    // checked before any sol moves, a revoked listing is left for close_stale
+    if !market_account.is_covered(ctx.program_id, &accounts.source)? {
+        return err!(Wen3ExError::DelegationRevoked);
+    }
+
+    let sol_amount = market_account.sol_amount;
+    let mut referrer = Referrer::load(ctx.remaining_accounts, ctx.accounts.taker.key)?;
+    let fees = accounts.config.fee_split(sol_amount, referrer.is_some())?;
+    send_lamports(
+        &accounts.taker,
+        &accounts.creator,
+        Some(&accounts.system_program),
+        sol_amount - fees.total(),
+    )?;
+    pay_fees(
+        ctx.program_id,
+        &accounts.taker,
+        &accounts.treasury,
+        referrer.as_mut(),
+        Some(&accounts.system_program),
+        &fees,
+        sol_amount,
+    )?;
+
+    let delegate_bump = *ctx.bumps.get("delegate").unwrap();
+    token::transfer(
+        accounts
+            .transfer_from_source_to_taker_context()
+            .with_signer(&[&[DELEGATE_SEED, &[delegate_bump]]]),
+        market_account.token_amount,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        accounts.market_account.key(),
+    )
+}
@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{MARKET_ESCROW, VAULT_AUTHORITY_SEED},
+    state_escrow::{
+        EscrowAccount, EscrowCreateSol, EscrowCreateToken, EscrowMarkDelivered, EscrowSettleSol,
+        EscrowSettleToken,
+    },
+    utils::send_lamports,
+};
+
+pub fn create_sol(
+    ctx: Context<EscrowCreateSol>,
+    id: u64,
+    amount: u64,
+    deadline: i64,
+) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    if deadline <= now_ts {
+        return err!(Wen3ExError::InvalidDeadline);
+    }
+
+    let escrow = &mut ctx.accounts.escrow;
+
+    escrow.version = EscrowAccount::VERSION;
+    escrow.buyer = ctx.accounts.buyer.key();
+    escrow.seller = ctx.accounts.seller.key();
+    escrow.arbiter = ctx.accounts.arbiter.key();
+    escrow.mint = Pubkey::default();
+    escrow.amount = amount;
+    escrow.deadline = deadline;
+    escrow.create_time = now_ts;
+    escrow.id = id;
+    escrow.bump = *ctx.bumps.get("escrow").unwrap();
+
+    // the sol sits on top of the escrow's rent
+    send_lamports(
+        &ctx.accounts.buyer,
+        &ctx.accounts.escrow.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        amount,
+    )
+}
+
+pub fn create_token(
+    ctx: Context<EscrowCreateToken>,
+    id: u64,
+    amount: u64,
+    deadline: i64,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_ESCROW, &ctx.accounts.mint)?;
+    let now_ts = Clock::get()?.unix_timestamp;
+    if deadline <= now_ts {
+        return err!(Wen3ExError::InvalidDeadline);
+    }
+
+    let escrow = &mut ctx.accounts.escrow;
+
+    escrow.version = EscrowAccount::VERSION;
+    escrow.buyer = ctx.accounts.buyer.key();
+    escrow.seller = ctx.accounts.seller.key();
+    escrow.arbiter = ctx.accounts.arbiter.key();
+    escrow.mint = ctx.accounts.mint.key();
+    escrow.amount = amount;
+    escrow.deadline = deadline;
+    escrow.create_time = now_ts;
+    escrow.id = id;
+    escrow.bump = *ctx.bumps.get("escrow").unwrap();
+
+    token::transfer(ctx.accounts.transfer_to_vault_context(), amount)
+}
+
+pub fn mark_delivered(ctx: Context<EscrowMarkDelivered>) -> Result<()> {
+    ctx.accounts.escrow.delivered = true;
+    Ok(())
+}
+
+pub fn release_sol(ctx: Context<EscrowSettleSol>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    if !escrow.can_release(ctx.accounts.authority.key) {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+
+    // the rest goes back to the buyer when the escrow closes
+    send_lamports(
+        &escrow.to_account_info(),
+        &ctx.accounts.seller,
+        None,
+        escrow.amount,
+    )
+}
+
+// closing the escrow hands the sol back to the buyer
+pub fn refund_sol(ctx: Context<EscrowSettleSol>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !ctx
+        .accounts
+        .escrow
+        .can_refund(ctx.accounts.authority.key, now_ts)
+    {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+    Ok(())
+}
+
+pub fn release_token(ctx: Context<EscrowSettleToken>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    if !escrow.can_release(ctx.accounts.authority.key) {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+    if ctx.accounts.recipient_token_account.owner != escrow.seller {
+        return err!(Wen3ExError::IncorrectRecipientTokenAccount);
+    }
+    settle_token(ctx)
+}
+
+pub fn refund_token(ctx: Context<EscrowSettleToken>) -> Result<()> {
+    let escrow = &ctx.accounts.escrow;
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !escrow.can_refund(ctx.accounts.authority.key, now_ts) {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+    if ctx.accounts.recipient_token_account.owner != escrow.buyer {
+        return err!(Wen3ExError::IncorrectRecipientTokenAccount);
+    }
+    settle_token(ctx)
+}
+
+fn settle_token(ctx: Context<EscrowSettleToken>) -> Result<()> {
+    let escrow_key = ctx.accounts.escrow.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        escrow_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_to_recipient_context()
+            .with_signer(&[&authority_seeds[..]]),
+        ctx.accounts.escrow.amount,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .close_vault_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{MARKET_LAUNCHPAD, VAULT_AUTHORITY_SEED},
+    state_launchpad::{
+        LaunchpadAccount, LaunchpadBuy, LaunchpadCreate, LaunchpadPurchased, LaunchpadWithdraw,
+        PurchaseAccount,
+    },
+    state_referral::{pay_fees, Referrer},
+    utils::send_lamports,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn create(
+    ctx: Context<LaunchpadCreate>,
+    unit_size: u64,
+    price: u64,
+    supply: u64,
+    total_cap: u64,
+    wallet_cap: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    ctx.accounts.config.check_mint_listable(
+        MARKET_LAUNCHPAD,
+        ctx.program_id,
+        &ctx.accounts.mint_entry,
+    )?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_LAUNCHPAD, &ctx.accounts.mint)?;
+
+    let launchpad = &mut ctx.accounts.launchpad;
+
+    launchpad.version = LaunchpadAccount::VERSION;
+    launchpad.issuer = ctx.accounts.issuer.key();
+    launchpad.mint = ctx.accounts.mint.key();
+    launchpad.unit_size = unit_size;
+    launchpad.price = price;
+    launchpad.supply = supply;
+    launchpad.total_cap = total_cap;
+    launchpad.wallet_cap = wallet_cap;
+    launchpad.start_time = start_time;
+    launchpad.end_time = end_time;
+    launchpad.bump = *ctx.bumps.get("launchpad").unwrap();
+    let supply_amount = launchpad.token_amount(supply)?;
+
+    token::transfer(ctx.accounts.transfer_to_vault_context(), supply_amount)?;
+
+    Ok(())
+}
+
+pub fn buy<'info>(
+    ctx: Context<'_, '_, '_, 'info, LaunchpadBuy<'info>>,
+    quantity: u64,
+) -> Result<()> {
+    if quantity == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    let now_ts = Clock::get()?.unix_timestamp;
+    let launchpad = &ctx.accounts.launchpad;
+    if now_ts < launchpad.start_time {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if launchpad.is_over(now_ts) {
+        return err!(Wen3ExError::SaleClosed);
+    }
+
+    let sold = launchpad
+        .sold
+        .checked_add(quantity)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+    if sold > launchpad.total_cap {
+        return err!(Wen3ExError::SaleCapExceeded);
+    }
+    let bought = ctx
+        .accounts
+        .purchase
+        .quantity
+        .checked_add(quantity)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+    if launchpad.wallet_cap != 0 && bought > launchpad.wallet_cap {
+        return err!(Wen3ExError::WalletCapExceeded);
+    }
+    let cost = quantity
+        .checked_mul(launchpad.price)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+    let token_amount = launchpad.token_amount(quantity)?;
+
+    // proceeds wait in the launchpad account, fees go out right away
+    let buyer = ctx.accounts.buyer.to_account_info();
+    let mut referrer = Referrer::load(ctx.remaining_accounts, buyer.key)?;
+    let fees = ctx.accounts.config.fee_split(cost, referrer.is_some())?;
+    let proceeds = cost - fees.total();
+    send_lamports(
+        &buyer,
+        &ctx.accounts.launchpad.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        proceeds,
+    )?;
+    pay_fees(
+        ctx.program_id,
+        &buyer,
+        &ctx.accounts.treasury,
+        referrer.as_mut(),
+        Some(&ctx.accounts.system_program),
+        &fees,
+        cost,
+    )?;
+
+    let launchpad_key = ctx.accounts.launchpad.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        launchpad_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    token::transfer(
+        ctx.accounts
+            .transfer_to_buyer_context()
+            .with_signer(&[&authority_seeds[..]]),
+        token_amount,
+    )?;
+
+    let launchpad = &mut ctx.accounts.launchpad;
+    launchpad.sold = sold;
+    launchpad.proceeds = launchpad
+        .proceeds
+        .checked_add(proceeds)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+
+    let purchase = &mut ctx.accounts.purchase;
+    if purchase.version == 0 {
+        purchase.version = PurchaseAccount::VERSION;
+        purchase.launchpad = launchpad_key;
+        purchase.buyer = buyer.key();
+        purchase.bump = *ctx.bumps.get("purchase").unwrap();
+    }
+    purchase.quantity = bought;
+    purchase.paid = purchase
+        .paid
+        .checked_add(cost)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+
+    emit!(LaunchpadPurchased {
+        launchpad: launchpad_key,
+        buyer: buyer.key(),
+        quantity,
+        cost,
+        sold,
+    });
+
+    Ok(())
+}
+
+pub fn withdraw(ctx: Context<LaunchpadWithdraw>) -> Result<()> {
+    if !ctx.accounts.launchpad.is_over(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::SaleNotClosed);
+    }
+
+    let launchpad_key = ctx.accounts.launchpad.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        launchpad_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    // unsold tokens back, the proceeds follow when the launchpad account closes
+    token::transfer(
+        ctx.accounts
+            .transfer_to_issuer_context()
+            .with_signer(&[&authority_seeds[..]]),
+        ctx.accounts.vault_token_account.amount,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .close_vault_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+
+    Ok(())
+}
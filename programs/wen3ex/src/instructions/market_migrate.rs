@@ -0,0 +1,93 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+    Discriminator,
+};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    state_cnft::MarketCnftToSolAccount, state_migrate::MarketMigrate,
+    state_ns::MarketNftToSolAccount, state_st::MarketStAccount, state_ts::MarketTsAccount,
+    state_tt::MarketTtAccount,
+};
+
+// every market account starts with: discriminator, version: u32, creator: Pubkey
+const VERSION_OFFSET: usize = 8;
+const CREATOR_OFFSET: usize = VERSION_OFFSET + 4;
+const HEADER_LEN: usize = CREATOR_OFFSET + 32;
+
+pub fn migrate(ctx: Context<MarketMigrate>) -> Result<()> {
+    let market_account = ctx.accounts.market_account.to_account_info();
+    let creator_account = ctx.accounts.creator.to_account_info();
+
+    if market_account.owner != ctx.program_id {
+        return err!(Wen3ExError::UnknownMarketAccount);
+    }
+
+    let (version, current_version, current_len) = {
+        let data = market_account.try_borrow_data()?;
+        if data.len() < HEADER_LEN {
+            return err!(Wen3ExError::UnknownMarketAccount);
+        }
+
+        let (current_version, current_len) = current_layout(&data[..8])?;
+        let version = u32::from_le_bytes(
+            data[VERSION_OFFSET..CREATOR_OFFSET]
+                .try_into()
+                .map_err(|_| Wen3ExError::UnknownMarketAccount)?,
+        );
+        let creator = Pubkey::new(&data[CREATOR_OFFSET..HEADER_LEN]);
+        if creator != *creator_account.key {
+            return err!(Wen3ExError::InvalidAuthority);
+        }
+        (version, current_version, current_len)
+    };
+
+    if version >= current_version {
+        return err!(Wen3ExError::MarketAlreadyMigrated);
+    }
+
+    if market_account.data_len() < current_len {
+        // creator pays the rent for the grown account
+        let lamports_required = Rent::get()?.minimum_balance(current_len);
+        let lamports_missing = lamports_required.saturating_sub(market_account.lamports());
+        if lamports_missing > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    creator_account.key,
+                    market_account.key,
+                    lamports_missing,
+                ),
+                &[
+                    creator_account,
+                    market_account.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        market_account.realloc(current_len, true)?;
+    }
+
+    let mut data = market_account.try_borrow_mut_data()?;
+    data[VERSION_OFFSET..CREATOR_OFFSET].copy_from_slice(&current_version.to_le_bytes());
+
+    msg!("market migrated from v{} to v{}", version, current_version);
+    Ok(())
+}
+
+fn current_layout(discriminator: &[u8]) -> Result<(u32, usize)> {
+    if discriminator == MarketTtAccount::discriminator() {
+        Ok((MarketTtAccount::VERSION, MarketTtAccount::LEN))
+    } else if discriminator == MarketTsAccount::discriminator() {
+        Ok((MarketTsAccount::VERSION, MarketTsAccount::LEN))
+    } else if discriminator == MarketStAccount::discriminator() {
+        Ok((MarketStAccount::VERSION, MarketStAccount::LEN))
+    } else if discriminator == MarketNftToSolAccount::discriminator() {
+        Ok((MarketNftToSolAccount::VERSION, MarketNftToSolAccount::LEN))
+    } else if discriminator == MarketCnftToSolAccount::discriminator() {
+        Ok((MarketCnftToSolAccount::VERSION, MarketCnftToSolAccount::LEN))
+    } else {
+        err!(Wen3ExError::UnknownMarketAccount)
+    }
+}
@@ -2,19 +2,41 @@ use anchor_lang::{prelude::*, solana_program};
 use anchor_spl::token;
 use spl_token::instruction::AuthorityType;
 
+use crate::errors::Wen3ExError;
+
 use super::{
-    constants::VAULT_AUTHORITY_SEED,
-    state_ns::{MarketNftToSolCancel, MarketNftToSolCreate, MarketNftToSolExchange},
+    constants::{MARKET_NS, NFT_VAULT_TOKEN, VAULT_AUTHORITY_SEED},
+    state_listing_index::ListingIndexAccount,
+    state_ns::{
+        MarketNftToSolAccount, MarketNftToSolCancel, MarketNftToSolCreate, MarketNftToSolExchange,
+        MarketNftToSolSetReferral,
+    },
+    state_referral::{pay_fees, Referrer},
 };
 
-pub fn create(ctx: Context<MarketNftToSolCreate>, nft_amount: u64, sol_amount: u64) -> Result<()> {
+pub fn create(
+    ctx: Context<MarketNftToSolCreate>,
+    nft_amount: u64,
+    sol_amount: u64,
+    start_time: i64,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .check_mint_listable(MARKET_NS, ctx.program_id, &ctx.accounts.mint_entry)?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_NS, &ctx.accounts.mint)?;
+
     let market_account = &mut ctx.accounts.market_account;
 
+    market_account.version = MarketNftToSolAccount::VERSION;
     market_account.creator = *ctx.accounts.creator.key;
     market_account.nft_token = ctx.accounts.mint.key();
     market_account.nft_amount = nft_amount;
     market_account.sol_amount = sol_amount;
     market_account.create_time = Clock::get()?.unix_timestamp;
+    market_account.start_time = start_time;
+    market_account.vault_kind = NFT_VAULT_TOKEN;
 
     let (vault_authority, _vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account.key().as_ref()],
@@ -32,6 +54,20 @@ pub fn create(ctx: Context<MarketNftToSolCreate>, nft_amount: u64, sol_amount: u
         AuthorityType::AccountOwner,
         Some(vault_authority),
     )?;
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )?;
+
+    Ok(())
+}
+
+pub fn set_referral(ctx: Context<MarketNftToSolSetReferral>, referral_bps: u32) -> Result<()> {
+    ctx.accounts.market_account.referral_bps = referral_bps;
     Ok(())
 }
 
@@ -74,6 +110,13 @@ pub fn cancel(ctx: Context<MarketNftToSolCancel>) -> Result<()> {
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
 
@@ -88,6 +131,9 @@ pub fn exchange<'info>(
 
     let market_account = ctx.accounts.market_account.clone();
     let market_account_key = market_account.key();
+    if !market_account.is_open(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
 
     let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account_key.as_ref()],
@@ -101,18 +147,33 @@ pub fn exchange<'info>(
     ];
 
     // for taker, taker is buy token
-    // transfer sol from taker to creator
+    // transfer sol from taker to creator, less the fees
+    let mut referrer = Referrer::load(ctx.remaining_accounts, ctx.accounts.taker.key)?;
+    let fees = ctx.accounts.config.maker_fee_split(
+        market_account.sol_amount,
+        referrer.is_some(),
+        market_account.referral_bps,
+    )?;
     solana_program::program::invoke(
         &solana_program::system_instruction::transfer(
             taker_account.key,
             creator_account.key,
-            market_account.sol_amount,
+            market_account.sol_amount - fees.total(),
         ),
         &[
             ctx.accounts.taker.to_account_info(),
             ctx.accounts.creator.to_account_info(),
         ],
     )?;
+    pay_fees(
+        ctx.program_id,
+        &taker_account,
+        &ctx.accounts.treasury,
+        referrer.as_mut(),
+        Some(&ctx.accounts.system_program),
+        &fees,
+        market_account.sol_amount,
+    )?;
     // transfer token from vault to taker
     token::transfer(
         ctx.accounts
@@ -126,5 +187,12 @@ pub fn exchange<'info>(
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,148 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+};
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::VAULT_AUTHORITY_SEED,
+    state_config::ConfigAccount,
+    state_listing_index::ListingIndexAccount,
+    state_offer::{
+        OfferAcceptListing, OfferAcceptWallet, OfferAccount, OfferCreate, OfferWithdraw,
+    },
+    state_referral::{pay_fees, Referrer},
+};
+
+pub fn create(
+    ctx: Context<OfferCreate>,
+    nft_amount: u64,
+    sol_amount: u64,
+    listing: Pubkey,
+) -> Result<()> {
+    let bidder_account = ctx.accounts.bidder.to_account_info();
+    let offer_account = ctx.accounts.offer.to_account_info();
+    let offer = &mut ctx.accounts.offer;
+
+    offer.version = OfferAccount::VERSION;
+    offer.bidder = bidder_account.key();
+    offer.nft_token = ctx.accounts.mint.key();
+    offer.nft_amount = nft_amount;
+    offer.sol_amount = sol_amount;
+    offer.listing = listing;
+    offer.create_time = Clock::get()?.unix_timestamp;
+    offer.bump = *ctx.bumps.get("offer").unwrap();
+
+    // escrow the offered sol on top of the offer's rent
+    invoke(
+        &system_instruction::transfer(bidder_account.key, offer_account.key, sol_amount),
+        &[
+            bidder_account,
+            offer_account,
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+// closing the offer gives the escrowed sol and the rent back
+pub fn withdraw(_ctx: Context<OfferWithdraw>) -> Result<()> {
+    Ok(())
+}
+
+pub fn accept_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, OfferAcceptListing<'info>>,
+) -> Result<()> {
+    let market_account_key = ctx.accounts.market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_from_vault_to_bidder_context()
+            .with_signer(&[&authority_seeds[..]]),
+        ctx.accounts.offer.nft_amount,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .close_vault_to_creator_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        market_account_key,
+    )?;
+
+    // the offer is closed to the bidder afterwards, only its rent is left by then
+    let accounts = &ctx.accounts;
+    pay_from_offer(
+        ctx.program_id,
+        &accounts.offer,
+        &accounts.creator,
+        &accounts.treasury,
+        &accounts.config,
+        accounts.market_account.referral_bps,
+        ctx.remaining_accounts,
+    )
+}
+
+pub fn accept_wallet<'info>(
+    ctx: Context<'_, '_, '_, 'info, OfferAcceptWallet<'info>>,
+) -> Result<()> {
+    token::transfer(
+        ctx.accounts.transfer_from_seller_to_bidder_context(),
+        ctx.accounts.offer.nft_amount,
+    )?;
+
+    let accounts = &ctx.accounts;
+    pay_from_offer(
+        ctx.program_id,
+        &accounts.offer,
+        &accounts.seller,
+        &accounts.treasury,
+        &accounts.config,
+        0,
+        ctx.remaining_accounts,
+    )
+}
+
+// remaining_accounts may hold the [referrer, referrer_account] pair, a listing's
+// creator pays it `referral_bps` on top. the seller signs and picks the
+// referrer, so neither party may be it
+fn pay_from_offer<'info>(
+    program_id: &Pubkey,
+    offer: &Account<'info, OfferAccount>,
+    seller: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    config: &ConfigAccount,
+    referral_bps: u32,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let offer_account = offer.to_account_info();
+    let mut referrer = Referrer::load(remaining_accounts, &offer.bidder)?;
+    if matches!(&referrer, Some(referrer) if referrer.wallet.key == seller.key) {
+        return err!(Wen3ExError::SelfReferral);
+    }
+    let fees = config.maker_fee_split(offer.sol_amount, referrer.is_some(), referral_bps)?;
+
+    **offer_account.try_borrow_mut_lamports()? -= offer.sol_amount - fees.total();
+    **seller.try_borrow_mut_lamports()? += offer.sol_amount - fees.total();
+    pay_fees(
+        program_id,
+        &offer_account,
+        treasury,
+        referrer.as_mut(),
+        None,
+        &fees,
+        offer.sol_amount,
+    )
+}
@@ -0,0 +1,392 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        MARKET_ORDER_BOOK, ORDER_TYPE_IOC, ORDER_TYPE_LIMIT, ORDER_TYPE_POST_ONLY, SIDE_ASK,
+        SIDE_BID, VAULT_AUTHORITY_SEED,
+    },
+    state_order_book::{
+        Order, OrderBookAccount, OrderBookBalanceAccount, OrderBookCancel, OrderBookCreate,
+        OrderBookPlace, OrderBookSettle, OrderEvicted, OrderPlaced,
+    },
+};
+
+pub fn create(ctx: Context<OrderBookCreate>, base_lot_size: u64, min_lots: u64) -> Result<()> {
+    let config = &ctx.accounts.config;
+    config.check_freeze_authority(MARKET_ORDER_BOOK, &ctx.accounts.base_mint)?;
+    config.check_freeze_authority(MARKET_ORDER_BOOK, &ctx.accounts.quote_mint)?;
+
+    let order_book = &mut ctx.accounts.order_book;
+
+    order_book.version = OrderBookAccount::VERSION;
+    order_book.base_mint = ctx.accounts.base_mint.key();
+    order_book.quote_mint = ctx.accounts.quote_mint.key();
+    order_book.base_lot_size = base_lot_size;
+    order_book.next_order_id = 1;
+    order_book.bump = *ctx.bumps.get("order_book").unwrap();
+    order_book.min_lots = min_lots;
+
+    Ok(())
+}
+
+pub fn place_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, OrderBookPlace<'info>>,
+    side: u8,
+    price: u64,
+    quantity: u64,
+    order_type: u8,
+) -> Result<()> {
+    if price == 0 || quantity == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    if quantity < ctx.accounts.order_book.min_lots {
+        return err!(Wen3ExError::OrderTooSmall);
+    }
+    if side != SIDE_BID && side != SIDE_ASK {
+        return err!(Wen3ExError::InvalidOrderSide);
+    }
+    if order_type != ORDER_TYPE_LIMIT
+        && order_type != ORDER_TYPE_IOC
+        && order_type != ORDER_TYPE_POST_ONLY
+    {
+        return err!(Wen3ExError::InvalidOrderType);
+    }
+
+    let order_book_key = ctx.accounts.order_book.key();
+    let owner = ctx.accounts.owner.key();
+    let owner_balance_key = ctx.accounts.owner_balance.key();
+    if ctx.accounts.owner_balance.owner == Pubkey::default() {
+        let owner_balance = &mut ctx.accounts.owner_balance;
+        owner_balance.order_book = order_book_key;
+        owner_balance.owner = owner;
+        owner_balance.bump = *ctx.bumps.get("owner_balance").unwrap();
+    }
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        order_book_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    // match against the opposite side first, every fill is at the maker price
+    let mut balances = ctx.remaining_accounts.iter();
+    let mut credits: Vec<(&AccountInfo<'info>, u64, u64)> = Vec::new();
+    let mut remaining = quantity;
+    let mut base_total: u64 = 0;
+    let mut quote_total: u64 = 0;
+    {
+        let order_book = &mut ctx.accounts.order_book;
+        let base_lot_size = order_book.base_lot_size;
+        let opposite = if side == SIDE_BID {
+            &mut order_book.asks
+        } else {
+            &mut order_book.bids
+        };
+
+        while remaining > 0 {
+            let best = match opposite.first_mut() {
+                Some(best) => best,
+                None => break,
+            };
+            let crosses = if side == SIDE_BID {
+                best.price <= price
+            } else {
+                best.price >= price
+            };
+            if !crosses {
+                break;
+            }
+            if order_type == ORDER_TYPE_POST_ONLY {
+                return err!(Wen3ExError::OrderWouldCross);
+            }
+
+            let maker_balance = match balances.next() {
+                Some(maker_balance) => maker_balance,
+                None => return err!(Wen3ExError::NoMakerBalanceAccount),
+            };
+            if maker_balance.key() != best.balance {
+                return err!(Wen3ExError::IncorrectMakerBalanceAccount);
+            }
+
+            let fill = remaining.min(best.quantity);
+            let base_amount = fill
+                .checked_mul(base_lot_size)
+                .ok_or(Wen3ExError::NumericalOverflowError)?;
+            let quote_amount = fill
+                .checked_mul(best.price)
+                .ok_or(Wen3ExError::NumericalOverflowError)?;
+            // an ask maker is owed quote, a bid maker base
+            if side == SIDE_BID {
+                credits.push((maker_balance, 0, quote_amount));
+            } else {
+                credits.push((maker_balance, base_amount, 0));
+            }
+            base_total = base_total
+                .checked_add(base_amount)
+                .ok_or(Wen3ExError::NumericalOverflowError)?;
+            quote_total = quote_total
+                .checked_add(quote_amount)
+                .ok_or(Wen3ExError::NumericalOverflowError)?;
+            best.quantity -= fill;
+            remaining -= fill;
+            if best.quantity == 0 {
+                opposite.remove(0);
+            }
+        }
+    }
+
+    // rest whatever is left, IOC drops it. a full side evicts its worst
+    // priced order for a better one
+    let mut order_id = 0;
+    let mut escrow: u64 = 0;
+    if remaining > 0 && order_type != ORDER_TYPE_IOC {
+        escrow = if side == SIDE_BID {
+            remaining
+                .checked_mul(price)
+                .ok_or(Wen3ExError::NumericalOverflowError)?
+        } else {
+            ctx.accounts.order_book.base_amount(remaining)?
+        };
+
+        let order_book = &mut ctx.accounts.order_book;
+        let base_lot_size = order_book.base_lot_size;
+        order_id = order_book.next_order_id;
+        order_book.next_order_id += 1;
+        let orders = if side == SIDE_BID {
+            &mut order_book.bids
+        } else {
+            &mut order_book.asks
+        };
+        if orders.len() >= OrderBookAccount::MAX_ORDERS {
+            let evicted = orders.pop().unwrap();
+            let improves = if side == SIDE_BID {
+                price > evicted.price
+            } else {
+                price < evicted.price
+            };
+            if !improves {
+                return err!(Wen3ExError::OrderBookFull);
+            }
+            let evicted_balance = match balances.next() {
+                Some(evicted_balance) => evicted_balance,
+                None => return err!(Wen3ExError::NoMakerBalanceAccount),
+            };
+            if evicted_balance.key() != evicted.balance {
+                return err!(Wen3ExError::IncorrectMakerBalanceAccount);
+            }
+            // its escrow goes back through the balance
+            if side == SIDE_BID {
+                let quote_amount = evicted
+                    .quantity
+                    .checked_mul(evicted.price)
+                    .ok_or(Wen3ExError::NumericalOverflowError)?;
+                credits.push((evicted_balance, 0, quote_amount));
+            } else {
+                let base_amount = evicted
+                    .quantity
+                    .checked_mul(base_lot_size)
+                    .ok_or(Wen3ExError::NumericalOverflowError)?;
+                credits.push((evicted_balance, base_amount, 0));
+            }
+            emit!(OrderEvicted {
+                order_book: order_book_key,
+                owner: evicted.owner,
+                order_id: evicted.order_id,
+            });
+        }
+
+        // behind every order with the same or a better price
+        let position = orders
+            .iter()
+            .position(|resting| {
+                if side == SIDE_BID {
+                    resting.price < price
+                } else {
+                    resting.price > price
+                }
+            })
+            .unwrap_or(orders.len());
+        orders.insert(
+            position,
+            Order {
+                order_id,
+                owner,
+                balance: owner_balance_key,
+                price,
+                quantity: remaining,
+            },
+        );
+    }
+
+    for (balance, base_amount, quote_amount) in credits {
+        if balance.key() == owner_balance_key {
+            ctx.accounts
+                .owner_balance
+                .credit(base_amount, quote_amount)?;
+        } else {
+            let mut maker_balance = Account::<OrderBookBalanceAccount>::try_from(balance)?;
+            maker_balance.credit(base_amount, quote_amount)?;
+            maker_balance.exit(ctx.program_id)?;
+        }
+    }
+
+    // the taker pays into the vault what the makers were credited, plus the
+    // escrow of what rests, and takes its side out of the vault
+    let accounts = &ctx.accounts;
+    let (pay_from, pay_to, pay_amount, take_from, take_to, take_amount) = if side == SIDE_BID {
+        (
+            &accounts.owner_quote_account,
+            &accounts.quote_vault,
+            quote_total + escrow,
+            &accounts.base_vault,
+            &accounts.owner_base_account,
+            base_total,
+        )
+    } else {
+        (
+            &accounts.owner_base_account,
+            &accounts.base_vault,
+            base_total + escrow,
+            &accounts.quote_vault,
+            &accounts.owner_quote_account,
+            quote_total,
+        )
+    };
+    if pay_amount > 0 {
+        token::transfer(
+            accounts.transfer_context(
+                pay_from.to_account_info(),
+                pay_to.to_account_info(),
+                accounts.owner.clone(),
+            ),
+            pay_amount,
+        )?;
+    }
+    if take_amount > 0 {
+        token::transfer(
+            accounts
+                .transfer_context(
+                    take_from.to_account_info(),
+                    take_to.to_account_info(),
+                    accounts.vault_authority.clone(),
+                )
+                .with_signer(&[&authority_seeds[..]]),
+            take_amount,
+        )?;
+    }
+
+    emit!(OrderPlaced {
+        order_book: order_book_key,
+        owner,
+        side,
+        price,
+        quantity,
+        filled: quantity - remaining,
+        order_id,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_order(ctx: Context<OrderBookCancel>, order_id: u64) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let order_book_key = ctx.accounts.order_book.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        order_book_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let order_book = &mut ctx.accounts.order_book;
+    let (side, order) = if let Some(i) = order_book
+        .bids
+        .iter()
+        .position(|order| order.order_id == order_id)
+    {
+        (SIDE_BID, order_book.bids.remove(i))
+    } else if let Some(i) = order_book
+        .asks
+        .iter()
+        .position(|order| order.order_id == order_id)
+    {
+        (SIDE_ASK, order_book.asks.remove(i))
+    } else {
+        return err!(Wen3ExError::OrderNotFound);
+    };
+    if order.owner != owner {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+
+    // hand the escrow back
+    let accounts = &ctx.accounts;
+    let (from, to, amount) = if side == SIDE_BID {
+        (
+            accounts.quote_vault.to_account_info(),
+            accounts.owner_quote_account.to_account_info(),
+            order
+                .quantity
+                .checked_mul(order.price)
+                .ok_or(Wen3ExError::NumericalOverflowError)?,
+        )
+    } else {
+        (
+            accounts.base_vault.to_account_info(),
+            accounts.owner_base_account.to_account_info(),
+            accounts.order_book.base_amount(order.quantity)?,
+        )
+    };
+    token::transfer(
+        accounts
+            .transfer_context(from, to, accounts.vault_authority.clone())
+            .with_signer(&[&authority_seeds[..]]),
+        amount,
+    )?;
+
+    Ok(())
+}
+
+pub fn settle(ctx: Context<OrderBookSettle>) -> Result<()> {
+    let order_book_key = ctx.accounts.order_book.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        order_book_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let base_free = ctx.accounts.owner_balance.base_free;
+    let quote_free = ctx.accounts.owner_balance.quote_free;
+    let owner_balance = &mut ctx.accounts.owner_balance;
+    owner_balance.base_free = 0;
+    owner_balance.quote_free = 0;
+
+    let accounts = &ctx.accounts;
+    if base_free > 0 {
+        token::transfer(
+            accounts
+                .transfer_context(
+                    accounts.base_vault.to_account_info(),
+                    accounts.owner_base_account.to_account_info(),
+                )
+                .with_signer(&[&authority_seeds[..]]),
+            base_free,
+        )?;
+    }
+    if quote_free > 0 {
+        token::transfer(
+            accounts
+                .transfer_context(
+                    accounts.quote_vault.to_account_info(),
+                    accounts.owner_quote_account.to_account_info(),
+                )
+                .with_signer(&[&authority_seeds[..]]),
+            quote_free,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{MARKET_NS, NFT_VAULT_PROGRAMMABLE, VAULT_AUTHORITY_SEED},
+    state_listing_index::ListingIndexAccount,
+    state_ns::MarketNftToSolAccount,
+    state_pnft::{
+        vault_is_closable, MarketPnftToSolCancel, MarketPnftToSolCreate, MarketPnftToSolExchange,
+    },
+    state_referral::{pay_fees, Referrer},
+    utils::send_lamports,
+};
+
+pub fn create(
+    ctx: Context<MarketPnftToSolCreate>,
+    nft_amount: u64,
+    sol_amount: u64,
+    start_time: i64,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .check_mint_listable(MARKET_NS, ctx.program_id, &ctx.accounts.mint_entry)?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_NS, &ctx.accounts.mint)?;
+
+    let market_account = &mut ctx.accounts.market_account;
+    market_account.version = MarketNftToSolAccount::VERSION;
+    market_account.creator = *ctx.accounts.creator.key;
+    market_account.nft_token = ctx.accounts.mint.key();
+    market_account.nft_amount = nft_amount;
+    market_account.sol_amount = sol_amount;
+    market_account.create_time = Clock::get()?.unix_timestamp;
+    market_account.start_time = start_time;
+    market_account.vault_kind = NFT_VAULT_PROGRAMMABLE;
+
+    ctx.accounts
+        .transfer_from_creator_to_vault()?
+        .invoke(nft_amount, &[])?;
+
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.pnft.system_program,
+    )
+}
+
+pub fn cancel(ctx: Context<MarketPnftToSolCancel>) -> Result<()> {
+    let market_account_key = ctx.accounts.market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    ctx.accounts.transfer_from_vault_to_creator()?.invoke(
+        ctx.accounts.market_account.nft_amount,
+        &[&authority_seeds[..]],
+    )?;
+    if vault_is_closable(&ctx.accounts.vault_token_account)? {
+        token::close_account(
+            ctx.accounts
+                .close_vault_context()
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+    }
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        market_account_key,
+    )
+}
+
+pub fn exchange<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketPnftToSolExchange<'info>>,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let market_account = &accounts.market_account;
+    if !market_account.is_open(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+
+    // transfer sol from taker to creator, less the fees
+    let mut referrer = Referrer::load(ctx.remaining_accounts, ctx.accounts.taker.key)?;
+    let sol_amount = market_account.sol_amount;
+    let fees = accounts.config.maker_fee_split(
+        sol_amount,
+        referrer.is_some(),
+        market_account.referral_bps,
+    )?;
+    send_lamports(
+        &accounts.taker,
+        &accounts.creator,
+        Some(&accounts.pnft.system_program),
+        sol_amount - fees.total(),
+    )?;
+    pay_fees(
+        ctx.program_id,
+        &accounts.taker,
+        &accounts.treasury,
+        referrer.as_mut(),
+        Some(&accounts.pnft.system_program),
+        &fees,
+        sol_amount,
+    )?;
+
+    let market_account_key = market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    accounts
+        .transfer_from_vault_to_taker()?
+        .invoke(market_account.nft_amount, &[&authority_seeds[..]])?;
+    if vault_is_closable(&accounts.vault_token_account)? {
+        token::close_account(
+            accounts
+                .close_vault_to_creator_context()
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+    }
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        market_account_key,
+    )
+}
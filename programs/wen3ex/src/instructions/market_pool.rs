@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{MARKET_POOL, POOL_MINIMUM_LIQUIDITY, VAULT_AUTHORITY_SEED},
+    state_pool::{PoolAccount, PoolCreate, PoolLiquidity, PoolSwap, PoolSwapped},
+};
+
+pub fn create(ctx: Context<PoolCreate>, fee_bps: u32) -> Result<()> {
+    let config = &ctx.accounts.config;
+    config.check_mint_listable(MARKET_POOL, ctx.program_id, &ctx.accounts.mint_a_entry)?;
+    config.check_mint_listable(MARKET_POOL, ctx.program_id, &ctx.accounts.mint_b_entry)?;
+    config.check_freeze_authority(MARKET_POOL, &ctx.accounts.mint_a)?;
+    config.check_freeze_authority(MARKET_POOL, &ctx.accounts.mint_b)?;
+
+    let pool = &mut ctx.accounts.pool;
+
+    pool.version = PoolAccount::VERSION;
+    pool.creator = ctx.accounts.creator.key();
+    pool.mint_a = ctx.accounts.mint_a.key();
+    pool.mint_b = ctx.accounts.mint_b.key();
+    pool.lp_mint = ctx.accounts.lp_mint.key();
+    pool.fee_bps = fee_bps;
+    pool.bump = *ctx.bumps.get("pool").unwrap();
+
+    Ok(())
+}
+
+// takes at most amount_a / amount_b at the pool ratio
+pub fn deposit(
+    ctx: Context<PoolLiquidity>,
+    amount_a: u64,
+    amount_b: u64,
+    min_lp: u64,
+) -> Result<()> {
+    if ctx.accounts.config.is_paused(MARKET_POOL) {
+        return err!(Wen3ExError::MarketPaused);
+    }
+    let reserve_a = ctx.accounts.vault_a.amount;
+    let reserve_b = ctx.accounts.vault_b.amount;
+    let lp_supply = ctx.accounts.lp_mint.supply;
+
+    let lp = PoolAccount::lp_for_deposit(amount_a, amount_b, reserve_a, reserve_b, lp_supply)?;
+    if lp == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    if lp < min_lp {
+        return err!(Wen3ExError::SlippageExceeded);
+    }
+    let (amount_a, amount_b) = if lp_supply == 0 {
+        (amount_a, amount_b)
+    } else {
+        (
+            PoolAccount::share(lp, reserve_a, lp_supply, true)?,
+            PoolAccount::share(lp, reserve_b, lp_supply, true)?,
+        )
+    };
+
+    let accounts = &ctx.accounts;
+    token::transfer(
+        accounts.transfer_to_vault_context(&accounts.owner_token_a, &accounts.vault_a),
+        amount_a,
+    )?;
+    token::transfer(
+        accounts.transfer_to_vault_context(&accounts.owner_token_b, &accounts.vault_b),
+        amount_b,
+    )?;
+
+    let pool_key = accounts.pool.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        pool_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    if lp_supply == 0 {
+        token::mint_to(
+            accounts
+                .mint_lp_context(&accounts.lp_lock)
+                .with_signer(&[&authority_seeds[..]]),
+            POOL_MINIMUM_LIQUIDITY,
+        )?;
+    }
+    token::mint_to(
+        accounts
+            .mint_lp_context(&accounts.owner_lp_token)
+            .with_signer(&[&authority_seeds[..]]),
+        lp,
+    )?;
+
+    Ok(())
+}
+
+pub fn withdraw(
+    ctx: Context<PoolLiquidity>,
+    lp_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> Result<()> {
+    let lp_supply = ctx.accounts.lp_mint.supply;
+    if lp_amount == 0 || lp_amount > lp_supply {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    let amount_a = PoolAccount::share(lp_amount, ctx.accounts.vault_a.amount, lp_supply, false)?;
+    let amount_b = PoolAccount::share(lp_amount, ctx.accounts.vault_b.amount, lp_supply, false)?;
+    if amount_a < min_amount_a || amount_b < min_amount_b {
+        return err!(Wen3ExError::SlippageExceeded);
+    }
+
+    let accounts = &ctx.accounts;
+    token::burn(accounts.burn_lp_context(), lp_amount)?;
+
+    let pool_key = accounts.pool.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        pool_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    token::transfer(
+        accounts
+            .transfer_from_vault_context(&accounts.vault_a, &accounts.owner_token_a)
+            .with_signer(&[&authority_seeds[..]]),
+        amount_a,
+    )?;
+    token::transfer(
+        accounts
+            .transfer_from_vault_context(&accounts.vault_b, &accounts.owner_token_b)
+            .with_signer(&[&authority_seeds[..]]),
+        amount_b,
+    )?;
+
+    Ok(())
+}
+
+pub fn swap(
+    ctx: Context<PoolSwap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    a_to_b: bool,
+) -> Result<()> {
+    if amount_in == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    let accounts = &ctx.accounts;
+    let (trader_in, vault_in, vault_out, trader_out) = if a_to_b {
+        (
+            &accounts.trader_token_a,
+            &accounts.vault_a,
+            &accounts.vault_b,
+            &accounts.trader_token_b,
+        )
+    } else {
+        (
+            &accounts.trader_token_b,
+            &accounts.vault_b,
+            &accounts.vault_a,
+            &accounts.trader_token_a,
+        )
+    };
+
+    let amount_out = accounts
+        .pool
+        .swap_out(amount_in, vault_in.amount, vault_out.amount)?;
+    if amount_out == 0 || amount_out < min_amount_out {
+        return err!(Wen3ExError::SlippageExceeded);
+    }
+
+    token::transfer(
+        accounts.transfer_to_vault_context(trader_in, vault_in),
+        amount_in,
+    )?;
+
+    let pool_key = accounts.pool.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        pool_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    token::transfer(
+        accounts
+            .transfer_from_vault_context(vault_out, trader_out)
+            .with_signer(&[&authority_seeds[..]]),
+        amount_out,
+    )?;
+
+    emit!(PoolSwapped {
+        pool: pool_key,
+        trader: accounts.trader.key(),
+        a_to_b,
+        amount_in,
+        amount_out,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::SOL_DECIMALS,
+    state_price::{MarketUnitPrice, UnitPrice},
+    state_st::MarketStAccount,
+    state_ts::MarketTsAccount,
+    state_tt::MarketTtAccount,
+};
+
+// tt quotes the deposit in the receive token, ts and st quote the token in sol
+pub fn unit_price(ctx: Context<MarketUnitPrice>) -> Result<UnitPrice> {
+    let market_info = &ctx.accounts.market_account;
+    if let Ok(market) = Account::<MarketTtAccount>::try_from(market_info) {
+        if market.version != MarketTtAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        return UnitPrice::new(
+            market.deposit_token,
+            market.deposit_amount,
+            market.deposit_decimals,
+            market.receive_token,
+            market.receive_amount,
+            market.receive_decimals,
+        );
+    }
+    if let Ok(market) = Account::<MarketTsAccount>::try_from(market_info) {
+        if market.version != MarketTsAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        // a pegged listing has no fixed price
+        return UnitPrice::new(
+            market.token,
+            market.token_amount,
+            market.token_decimals,
+            Pubkey::default(),
+            market.fixed_sol_amount()?,
+            Some(SOL_DECIMALS),
+        );
+    }
+    if let Ok(market) = Account::<MarketStAccount>::try_from(market_info) {
+        if market.version != MarketStAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        return UnitPrice::new(
+            market.token,
+            market.token_amount,
+            market.token_decimals,
+            Pubkey::default(),
+            market.sol_amount,
+            Some(SOL_DECIMALS),
+        );
+    }
+    err!(Wen3ExError::UnknownMarketAccount)
+}
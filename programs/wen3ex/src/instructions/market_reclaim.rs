@@ -0,0 +1,183 @@
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, TokenAccount},
+};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CREATOR_INDEX_SEED, MINT_INDEX_SEED, NFT_VAULT_PROGRAMMABLE, NFT_VAULT_TOKEN,
+        RECLAIM_GRACE_PERIOD, VAULT_AUTHORITY_SEED, VAULT_NFT_2_SOL_SEED, VAULT_TOKEN_2_SOL_SEED,
+        VAULT_TOKEN_2_TOKEN_SEED,
+    },
+    state_listing_index::ListingIndexAccount,
+    state_ns::MarketNftToSolAccount,
+    state_reclaim::MarketReclaim,
+    state_ts::MarketTsAccount,
+    state_tt::MarketTtAccount,
+};
+
+// what reclaim needs from either listing type
+struct Deposit {
+    creator: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    create_time: i64,
+    vault: Pubkey,
+    frozen_by_design: bool, // see market_pnft
+}
+
+pub fn reclaim(ctx: Context<MarketReclaim>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let market_info = &accounts.market_account;
+    let market_key = market_info.key();
+    let deposit = load_deposit(ctx.program_id, market_info)?;
+    if deposit.creator != accounts.creator.key() {
+        return err!(Wen3ExError::InvalidAuthority);
+    }
+    if accounts.vault_token_account.key() != deposit.vault {
+        return err!(Wen3ExError::IncorrectVaultTokenAccount);
+    }
+    if accounts.caller.key() != deposit.creator
+        && Clock::get()?.unix_timestamp < deposit.create_time + RECLAIM_GRACE_PERIOD
+    {
+        return err!(Wen3ExError::ReclaimTooEarly);
+    }
+
+    // a closed vault reads as empty
+    let vault = if accounts.vault_token_account.owner == &token::ID
+        && !accounts.vault_token_account.data_is_empty()
+    {
+        Some(Account::<TokenAccount>::try_from(
+            &accounts.vault_token_account,
+        )?)
+    } else {
+        None
+    };
+    let (amount, frozen) = vault
+        .as_ref()
+        .map_or((0, false), |vault| (vault.amount, vault.is_frozen()));
+    // more than the deposit, say a transfer into the vault, can still be
+    // filled or cancelled
+    let broken =
+        vault.is_none() || amount < deposit.amount || (frozen && !deposit.frozen_by_design);
+    if !broken {
+        return err!(Wen3ExError::VaultConsistent);
+    }
+
+    // a frozen vault can neither be emptied nor closed, it stays behind
+    if vault.is_some() && !frozen {
+        let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+        let authority_seeds = &[
+            VAULT_AUTHORITY_SEED,
+            market_key.as_ref(),
+            &[vault_authority_bump],
+        ];
+        if amount > 0 {
+            let creator_token = Account::<TokenAccount>::try_from(&accounts.creator_token_account)?;
+            if creator_token.mint != deposit.mint || creator_token.owner != deposit.creator {
+                return err!(Wen3ExError::IncorrectCreatorTokenAccount);
+            }
+            token::transfer(
+                accounts
+                    .transfer_to_creator_context()
+                    .with_signer(&[&authority_seeds[..]]),
+                amount,
+            )?;
+        }
+        token::close_account(
+            accounts
+                .close_vault_context()
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+    }
+
+    let (mint_index, _) =
+        Pubkey::find_program_address(&[MINT_INDEX_SEED, deposit.mint.as_ref()], ctx.program_id);
+    let (creator_index, _) = Pubkey::find_program_address(
+        &[CREATOR_INDEX_SEED, deposit.creator.as_ref()],
+        ctx.program_id,
+    );
+    if accounts.mint_index.key() != mint_index || accounts.creator_index.key() != creator_index {
+        return err!(Wen3ExError::InvalidListingIndex);
+    }
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        market_key,
+    )?;
+
+    // the bounty, if any, goes back with the rent
+    close_listing(market_info, &accounts.creator)
+}
+
+fn load_deposit(program_id: &Pubkey, market_info: &AccountInfo) -> Result<Deposit> {
+    let vault =
+        |seed: &[u8]| Pubkey::find_program_address(&[seed, market_info.key.as_ref()], program_id).0;
+    if let Ok(market) = Account::<MarketTtAccount>::try_from(market_info) {
+        if market.version != MarketTtAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        return Ok(Deposit {
+            creator: market.creator,
+            mint: market.deposit_token,
+            amount: market.deposit_amount,
+            create_time: market.create_time,
+            vault: vault(VAULT_TOKEN_2_TOKEN_SEED),
+            frozen_by_design: false,
+        });
+    }
+    if let Ok(market) = Account::<MarketTsAccount>::try_from(market_info) {
+        if market.version != MarketTsAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        return Ok(Deposit {
+            creator: market.creator,
+            mint: market.token,
+            amount: market.token_amount,
+            create_time: market.create_time,
+            vault: vault(VAULT_TOKEN_2_SOL_SEED),
+            frozen_by_design: false,
+        });
+    }
+    if let Ok(market) = Account::<MarketNftToSolAccount>::try_from(market_info) {
+        if market.version != MarketNftToSolAccount::VERSION {
+            return err!(Wen3ExError::MarketVersionMismatch);
+        }
+        // the vault of market_pnft_to_sol_create, where programmable nfts sit
+        // frozen, or of market_nft_to_sol_create
+        let programmable = market.vault_kind == NFT_VAULT_PROGRAMMABLE;
+        let nft_vault = if programmable {
+            get_associated_token_address(&vault(VAULT_AUTHORITY_SEED), &market.nft_token)
+        } else if market.vault_kind == NFT_VAULT_TOKEN {
+            vault(VAULT_NFT_2_SOL_SEED)
+        } else {
+            return err!(Wen3ExError::NftVaultMismatch);
+        };
+        return Ok(Deposit {
+            creator: market.creator,
+            mint: market.nft_token,
+            amount: market.nft_amount,
+            create_time: market.create_time,
+            vault: nft_vault,
+            frozen_by_design: programmable,
+        });
+    }
+    err!(Wen3ExError::UnknownMarketAccount)
+}
+
+fn close_listing<'info>(
+    market_info: &AccountInfo<'info>,
+    creator: &AccountInfo<'info>,
+) -> Result<()> {
+    if let Ok(market) = Account::<MarketTtAccount>::try_from(market_info) {
+        return market.close(creator.clone());
+    }
+    if let Ok(market) = Account::<MarketTsAccount>::try_from(market_info) {
+        return market.close(creator.clone());
+    }
+    Account::<MarketNftToSolAccount>::try_from(market_info)?.close(creator.clone())
+}
@@ -0,0 +1,459 @@
+use std::cmp::Ordering;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+    AccountsClose,
+};
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        MARKET_ST, MARKET_TS, SALE_PHASE_SEED, VAULT_AUTHORITY_SEED, VAULT_SOL_2_TOKEN_SEED,
+        VAULT_TOKEN_2_SOL_SEED,
+    },
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    state_route::{Route, RouteFilled, ROUTE_ACCOUNTS_PER_LISTING},
+    state_sale_phase::SalePhaseAccount,
+    state_st::MarketStAccount,
+    state_ts::MarketTsAccount,
+    utils::send_lamports,
+};
+
+// a live listing of the mint and its remaining_accounts group
+struct Candidate<'a, 'info> {
+    accounts: &'a [AccountInfo<'info>],
+    token_amount: u64,
+    sol_amount: u64,
+}
+
+impl Candidate<'_, '_> {
+    // by sol per token, cheapest first
+    fn cmp_price(&self, other: &Self) -> Ordering {
+        (self.sol_amount as u128 * other.token_amount as u128)
+            .cmp(&(other.sol_amount as u128 * self.token_amount as u128))
+    }
+}
+
+// buys up to `quantity` from token 2 sol listings, cheapest first. the last
+// listing may be taken in part, what is left stays listed at the same price
+pub fn route_buy<'info>(
+    ctx: Context<'_, '_, '_, 'info, Route<'info>>,
+    quantity: u64,
+    max_avg_price: u64,
+) -> Result<()> {
+    if quantity == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    if ctx.accounts.config.is_paused(MARKET_TS) {
+        return err!(Wen3ExError::MarketPaused);
+    }
+    let groups = ctx
+        .remaining_accounts
+        .chunks_exact(ROUTE_ACCOUNTS_PER_LISTING);
+    let mut referrer = match groups.remainder().len() {
+        0 => None,
+        2 => Referrer::load(groups.remainder(), ctx.accounts.taker.key)?,
+        _ => return err!(Wen3ExError::InvalidRouteAccounts),
+    };
+
+    let mint = ctx.accounts.mint.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    let mut skipped: u32 = 0;
+    let mut candidates = Vec::new();
+    for accounts in groups {
+        // taken, cancelled, not open yet, expired, or not at a fixed price
+        let market = match Account::<MarketTsAccount>::try_from(&accounts[0]) {
+            Ok(market)
+                if market.version == MarketTsAccount::VERSION
+                    && market.is_open(now_ts)
+                    && !market.is_expired(now_ts)
+                    && !market.is_vesting()
+                    && !market.is_pegged() =>
+            {
+                market
+            }
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        if market.token != mint {
+            return err!(Wen3ExError::InvalidRouteAccounts);
+        }
+        // presale listings need a proof, see market_ts_exchange_allowlisted
+        let (sale_phase_key, _sale_phase_bump) = Pubkey::find_program_address(
+            &[SALE_PHASE_SEED, market.creator.as_ref(), mint.as_ref()],
+            ctx.program_id,
+        );
+        if sale_phase_key != accounts[4].key() {
+            return err!(Wen3ExError::InvalidRouteAccounts);
+        }
+        if let Some(sale_phase) = SalePhaseAccount::load(ctx.program_id, &accounts[4])? {
+            if sale_phase.is_presale(now_ts) {
+                skipped += 1;
+                continue;
+            }
+        }
+        candidates.push(Candidate {
+            accounts,
+            token_amount: market.token_amount,
+            sol_amount: market.sol_amount,
+        });
+    }
+    candidates.sort_by(|a, b| a.cmp_price(b));
+
+    let mut filled: u64 = 0;
+    let mut sol_spent: u64 = 0;
+    let mut listings: u32 = 0;
+    for candidate in candidates {
+        if filled == quantity {
+            break;
+        }
+        let take = candidate.token_amount.min(quantity - filled);
+        let cost = if take == candidate.token_amount {
+            candidate.sol_amount
+        } else {
+            // rounded up, in the creator's favour
+            let numerator = candidate.sol_amount as u128 * take as u128;
+            let mut cost = numerator / candidate.token_amount as u128;
+            if cost * (candidate.token_amount as u128) < numerator {
+                cost += 1;
+            }
+            cost as u64
+        };
+        // a part that would leave tokens listed for nothing
+        if take < candidate.token_amount && cost >= candidate.sol_amount {
+            skipped += 1;
+            continue;
+        }
+
+        buy_from_listing(&ctx, candidate.accounts, take, cost, referrer.as_mut())?;
+
+        filled += take;
+        sol_spent = sol_spent
+            .checked_add(cost)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        listings += 1;
+    }
+
+    let avg_price = avg_price(sol_spent, filled, ctx.accounts.mint.decimals)?;
+    if avg_price > max_avg_price as u128 {
+        return err!(Wen3ExError::PriceAboveMax);
+    }
+
+    emit!(RouteFilled {
+        taker: ctx.accounts.taker.key(),
+        mint,
+        buy: true,
+        listings,
+        skipped,
+        quantity: filled,
+        sol_amount: sol_spent,
+        avg_price: avg_price as u64,
+    });
+
+    Ok(())
+}
+
+// sells up to `quantity` into sol 2 token listings, best paying first. these
+// are only taken whole, a listing for more than what is left is skipped
+pub fn route_sell<'info>(
+    ctx: Context<'_, '_, '_, 'info, Route<'info>>,
+    quantity: u64,
+    min_avg_price: u64,
+) -> Result<()> {
+    if quantity == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+    if ctx.accounts.config.is_paused(MARKET_ST) {
+        return err!(Wen3ExError::MarketPaused);
+    }
+    let groups = ctx
+        .remaining_accounts
+        .chunks_exact(ROUTE_ACCOUNTS_PER_LISTING);
+    let mut referrer = match groups.remainder().len() {
+        0 => None,
+        2 => Referrer::load(groups.remainder(), ctx.accounts.taker.key)?,
+        _ => return err!(Wen3ExError::InvalidRouteAccounts),
+    };
+
+    let mint = ctx.accounts.mint.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    let mut skipped: u32 = 0;
+    let mut candidates = Vec::new();
+    for accounts in groups {
+        let market = match Account::<MarketStAccount>::try_from(&accounts[0]) {
+            Ok(market)
+                if market.version == MarketStAccount::VERSION
+                    && market.is_open(now_ts)
+                    && !market.is_expired(now_ts) =>
+            {
+                market
+            }
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        if market.token != mint {
+            return err!(Wen3ExError::InvalidRouteAccounts);
+        }
+        candidates.push(Candidate {
+            accounts,
+            token_amount: market.token_amount,
+            sol_amount: market.sol_amount,
+        });
+    }
+    candidates.sort_by(|a, b| b.cmp_price(a));
+
+    let mut filled: u64 = 0;
+    let mut sol_received: u64 = 0;
+    let mut listings: u32 = 0;
+    for candidate in candidates {
+        if candidate.token_amount > quantity - filled {
+            skipped += 1;
+            continue;
+        }
+
+        sell_to_listing(&ctx, candidate.accounts, referrer.as_mut())?;
+
+        filled += candidate.token_amount;
+        sol_received = sol_received
+            .checked_add(candidate.sol_amount)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        listings += 1;
+    }
+
+    let avg_price = avg_price(sol_received, filled, ctx.accounts.mint.decimals)?;
+    if avg_price < min_avg_price as u128 {
+        return err!(Wen3ExError::PriceBelowMin);
+    }
+
+    emit!(RouteFilled {
+        taker: ctx.accounts.taker.key(),
+        mint,
+        buy: false,
+        listings,
+        skipped,
+        quantity: filled,
+        sol_amount: sol_received,
+        avg_price: avg_price as u64,
+    });
+
+    Ok(())
+}
+
+// lamports per whole token
+fn avg_price(sol_amount: u64, quantity: u64, decimals: u8) -> Result<u128> {
+    if quantity == 0 {
+        return err!(Wen3ExError::RouteUnfilled);
+    }
+    let scale = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+    Ok(sol_amount as u128 * scale / quantity as u128)
+}
+
+// checks the group against the listing and returns the vault authority bump
+fn check_listing_accounts(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vault_seed: &[u8],
+    mint: &Pubkey,
+    creator: &Pubkey,
+) -> Result<u8> {
+    let market_key = accounts[0].key();
+    let (vault_key, _vault_bump) =
+        Pubkey::find_program_address(&[vault_seed, market_key.as_ref()], program_id);
+    let (vault_authority_key, vault_authority_bump) =
+        Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, market_key.as_ref()], program_id);
+    if vault_key != accounts[1].key() || vault_authority_key != accounts[2].key() {
+        return err!(Wen3ExError::IncorrectVaultAuthorityAccount);
+    }
+    if Account::<TokenAccount>::try_from(&accounts[1])?.is_frozen() {
+        return err!(Wen3ExError::VaultFrozen);
+    }
+    if *creator != accounts[3].key() {
+        return err!(Wen3ExError::IncorrectCreatorTokenAccount);
+    }
+    if ListingIndexAccount::address(program_id, false, mint) != accounts[5].key()
+        || ListingIndexAccount::address(program_id, true, creator) != accounts[6].key()
+    {
+        return err!(Wen3ExError::InvalidRouteAccounts);
+    }
+    Ok(vault_authority_bump)
+}
+
+fn buy_from_listing<'info>(
+    ctx: &Context<'_, '_, '_, 'info, Route<'info>>,
+    accounts: &[AccountInfo<'info>],
+    take: u64,
+    cost: u64,
+    referrer: Option<&mut Referrer<'info>>,
+) -> Result<()> {
+    let (market_info, vault_info, vault_authority, creator) =
+        (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+    let mut market = Account::<MarketTsAccount>::try_from(market_info)?;
+    let vault_authority_bump = check_listing_accounts(
+        ctx.program_id,
+        accounts,
+        VAULT_TOKEN_2_SOL_SEED,
+        &market.token,
+        &market.creator,
+    )?;
+    let market_key = market_info.key();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let taker = &ctx.accounts.taker;
+    let fees =
+        ctx.accounts
+            .config
+            .maker_fee_split(cost, referrer.is_some(), market.referral_bps)?;
+    invoke(
+        &system_instruction::transfer(taker.key, creator.key, cost - fees.total()),
+        &[
+            taker.clone(),
+            creator.clone(),
+            ctx.accounts.system_program.clone(),
+        ],
+    )?;
+    pay_fees(
+        ctx.program_id,
+        taker,
+        &ctx.accounts.treasury,
+        referrer,
+        Some(&ctx.accounts.system_program),
+        &fees,
+        cost,
+    )?;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.clone(),
+            Transfer {
+                from: vault_info.clone(),
+                to: ctx.accounts.taker_token_account.to_account_info(),
+                authority: vault_authority.clone(),
+            },
+        )
+        .with_signer(&[&authority_seeds[..]]),
+        take,
+    )?;
+
+    if take < market.token_amount {
+        market.token_amount -= take;
+        market.sol_amount -= cost;
+        return market.exit(ctx.program_id);
+    }
+
+    // taken whole, closed as by market_ts_exchange
+    token::close_account(
+        CpiContext::new(
+            ctx.accounts.token_program.clone(),
+            CloseAccount {
+                account: vault_info.clone(),
+                destination: creator.clone(),
+                authority: vault_authority.clone(),
+            },
+        )
+        .with_signer(&[&authority_seeds[..]]),
+    )?;
+    send_lamports(market_info, taker, None, market.bounty)?;
+    ListingIndexAccount::remove(ctx.program_id, &accounts[5], &accounts[6], market_key)?;
+    market.close(creator.clone())
+}
+
+fn sell_to_listing<'info>(
+    ctx: &Context<'_, '_, '_, 'info, Route<'info>>,
+    accounts: &[AccountInfo<'info>],
+    referrer: Option<&mut Referrer<'info>>,
+) -> Result<()> {
+    let (market_info, vault_info, vault_authority, creator, creator_token_account) = (
+        &accounts[0],
+        &accounts[1],
+        &accounts[2],
+        &accounts[3],
+        &accounts[4],
+    );
+    let market = Account::<MarketStAccount>::try_from(market_info)?;
+    let vault_authority_bump = check_listing_accounts(
+        ctx.program_id,
+        accounts,
+        VAULT_SOL_2_TOKEN_SEED,
+        &market.token,
+        &market.creator,
+    )?;
+    let creator_token = Account::<TokenAccount>::try_from(creator_token_account)?;
+    if creator_token.mint != market.token || creator_token.owner != market.creator {
+        return err!(Wen3ExError::IncorrectCreatorTokenAccount);
+    }
+    let market_key = market_info.key();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let taker = &ctx.accounts.taker;
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.clone(),
+            Transfer {
+                from: ctx.accounts.taker_token_account.to_account_info(),
+                to: creator_token_account.clone(),
+                authority: taker.clone(),
+            },
+        ),
+        market.token_amount,
+    )?;
+
+    // as market_st_exchange: the taker refunds the vault's rent to the
+    // creator and gets all of the vault's lamports when it closes
+    invoke(
+        &system_instruction::transfer(
+            taker.key,
+            creator.key,
+            vault_info.lamports() - market.sol_amount,
+        ),
+        &[
+            taker.clone(),
+            creator.clone(),
+            ctx.accounts.system_program.clone(),
+        ],
+    )?;
+    token::close_account(
+        CpiContext::new(
+            ctx.accounts.token_program.clone(),
+            CloseAccount {
+                account: vault_info.clone(),
+                destination: taker.clone(),
+                authority: vault_authority.clone(),
+            },
+        )
+        .with_signer(&[&authority_seeds[..]]),
+    )?;
+
+    let fees = ctx
+        .accounts
+        .config
+        .fee_split(market.sol_amount, referrer.is_some())?;
+    pay_fees(
+        ctx.program_id,
+        taker,
+        &ctx.accounts.treasury,
+        referrer,
+        Some(&ctx.accounts.system_program),
+        &fees,
+        market.sol_amount,
+    )?;
+    send_lamports(market_info, taker, None, market.bounty)?;
+    ListingIndexAccount::remove(ctx.program_id, &accounts[5], &accounts[6], market_key)?;
+    market.close(creator.clone())
+}
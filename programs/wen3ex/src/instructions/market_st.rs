@@ -8,20 +8,44 @@ use spl_token::instruction::AuthorityType;
 use crate::errors::Wen3ExError;
 
 use super::{
-    constants::VAULT_AUTHORITY_SEED,
-    state_st::{MarketStCancel, MarketStCreate, MarketStExchange},
+    constants::{MARKET_ST, VAULT_AUTHORITY_SEED},
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    state_st::{MarketStAccount, MarketStCancel, MarketStCrank, MarketStCreate, MarketStExchange},
+    utils::{check_expire_time, send_lamports},
 };
 
-pub fn create(ctx: Context<MarketStCreate>, token_amount: u64, sol_amount: u64) -> Result<()> {
+pub fn create(
+    ctx: Context<MarketStCreate>,
+    token_amount: u64,
+    sol_amount: u64,
+    bounty: u64,
+    expire_time: i64,
+    start_time: i64,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .check_mint_listable(MARKET_ST, ctx.program_id, &ctx.accounts.mint_entry)?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_ST, &ctx.accounts.mint)?;
+    let now_ts = Clock::get()?.unix_timestamp;
+    check_expire_time(expire_time, now_ts.max(start_time))?;
+
     let creator_account = ctx.accounts.creator.to_account_info().clone();
     let vault_token_account = ctx.accounts.vault_token_account.to_account_info().clone();
     let market_account = &mut ctx.accounts.market_account;
 
+    market_account.version = MarketStAccount::VERSION;
     market_account.creator = *ctx.accounts.creator.key;
     market_account.token = ctx.accounts.mint.key();
     market_account.token_amount = token_amount;
     market_account.sol_amount = sol_amount;
-    market_account.create_time = Clock::get()?.unix_timestamp;
+    market_account.create_time = now_ts;
+    market_account.bounty = bounty;
+    market_account.expire_time = expire_time;
+    market_account.start_time = start_time;
+    market_account.token_decimals = Some(ctx.accounts.mint.decimals);
 
     let (vault_authority, _vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account.key().as_ref()],
@@ -40,6 +64,13 @@ pub fn create(ctx: Context<MarketStCreate>, token_amount: u64, sol_amount: u64)
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
+    // the bounty waits in the market account, cancel hands it back on close
+    send_lamports(
+        &ctx.accounts.creator,
+        &ctx.accounts.market_account.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        bounty,
+    )?;
 
     // change vault_token_account authority from creator to program
     token::set_authority(
@@ -47,6 +78,15 @@ pub fn create(ctx: Context<MarketStCreate>, token_amount: u64, sol_amount: u64)
         AuthorityType::AccountOwner,
         Some(vault_authority),
     )?;
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )?;
+
     Ok(())
 }
 
@@ -72,6 +112,13 @@ pub fn cancel(ctx: Context<MarketStCancel>) -> Result<()> {
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
 
@@ -82,6 +129,13 @@ pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketStExchange<'info>>)
 
     let market_account = ctx.accounts.market_account.clone();
     let market_account_key = market_account.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !market_account.is_open(now_ts) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if market_account.is_expired(now_ts) {
+        return err!(Wen3ExError::ListingExpired);
+    }
 
     let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account_key.as_ref()],
@@ -95,15 +149,9 @@ pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketStExchange<'info>>)
     ];
 
     // for taker, taker is sell token
-    if ctx.remaining_accounts.is_empty() {
-        return err!(Wen3ExError::NoCreatorTokenAccount);
-    }
-    let creator_token_account = ctx.remaining_accounts[0].clone();
-
     // transfer token from taker to creator
     token::transfer(
-        ctx.accounts
-            .transfer_from_taker_to_creator_context(creator_token_account),
+        ctx.accounts.transfer_from_taker_to_creator_context(),
         ctx.accounts.market_account.token_amount,
     )?;
 
@@ -132,5 +180,71 @@ pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketStExchange<'info>>)
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    // the taker got the sol, the fees come out of it
+    let mut referrer = Referrer::load(ctx.remaining_accounts, ctx.accounts.taker.key)?;
+    let fees = ctx
+        .accounts
+        .config
+        .fee_split(market_account.sol_amount, referrer.is_some())?;
+    pay_fees(
+        ctx.program_id,
+        &taker_account,
+        &ctx.accounts.treasury,
+        referrer.as_mut(),
+        Some(&ctx.accounts.system_program),
+        &fees,
+        market_account.sol_amount,
+    )?;
+    send_lamports(
+        &ctx.accounts.market_account.to_account_info(),
+        &taker_account,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
+    Ok(())
+}
+
+pub fn crank(ctx: Context<MarketStCrank>) -> Result<()> {
+    let market_account = ctx.accounts.market_account.clone();
+    let market_account_key = market_account.key();
+    if !market_account.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::ListingNotExpired);
+    }
+
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    // same as cancel, only the bounty goes to whoever closed it
+    token::close_account(
+        ctx.accounts
+            .close_vault_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+    send_lamports(
+        &ctx.accounts.market_account.to_account_info(),
+        &ctx.accounts.cranker,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
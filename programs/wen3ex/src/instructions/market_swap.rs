@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{MARKET_SWAP, VAULT_AUTHORITY_SEED},
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    state_swap::{
+        MarketSwapAccount, MarketSwapCancel, MarketSwapCreate, MarketSwapDeposit,
+        MarketSwapExchange, SwapVault, SWAP_ACCOUNTS_PER_NFT,
+    },
+    token_metadata::verified_collection,
+    utils::send_lamports,
+};
+
+// one nft goes in with create, more with deposit
+pub fn create(
+    ctx: Context<MarketSwapCreate>,
+    want_mint: Pubkey,
+    want_collection: Pubkey,
+    taker_sol: u64,
+    creator_sol: u64,
+) -> Result<()> {
+    // exactly one of a mint and a collection, sol at most one way
+    if (want_mint == Pubkey::default()) == (want_collection == Pubkey::default()) {
+        return err!(Wen3ExError::InvalidSwapRequest);
+    }
+    if taker_sol > 0 && creator_sol > 0 {
+        return err!(Wen3ExError::InvalidSwapRequest);
+    }
+    ctx.accounts.config.check_mint_listable(
+        MARKET_SWAP,
+        ctx.program_id,
+        &ctx.accounts.mint_entry,
+    )?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_SWAP, &ctx.accounts.mint)?;
+
+    let market_account = &mut ctx.accounts.market_account;
+    market_account.version = MarketSwapAccount::VERSION;
+    market_account.creator = *ctx.accounts.creator.key;
+    market_account.offered = vec![ctx.accounts.mint.key()];
+    market_account.want_mint = want_mint;
+    market_account.want_collection = want_collection;
+    market_account.taker_sol = taker_sol;
+    market_account.creator_sol = creator_sol;
+    market_account.create_time = Clock::get()?.unix_timestamp;
+
+    token::transfer(ctx.accounts.transfer_from_creator_to_vault_context(), 1)?;
+    // the sol for the taker waits in the listing
+    send_lamports(
+        &ctx.accounts.creator,
+        &ctx.accounts.market_account.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        creator_sol,
+    )?;
+
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )
+}
+
+pub fn deposit(ctx: Context<MarketSwapDeposit>) -> Result<()> {
+    ctx.accounts.config.check_mint_listable(
+        MARKET_SWAP,
+        ctx.program_id,
+        &ctx.accounts.mint_entry,
+    )?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_SWAP, &ctx.accounts.mint)?;
+    let market_account = &mut ctx.accounts.market_account;
+    if market_account.offered.len() >= MarketSwapAccount::MAX_OFFERED {
+        return err!(Wen3ExError::SwapFull);
+    }
+    market_account.offered.push(ctx.accounts.mint.key());
+
+    token::transfer(ctx.accounts.transfer_from_creator_to_vault_context(), 1)
+}
+
+// the vaults of the offered nfts and the creator's token accounts are the
+// remaining accounts, see SWAP_ACCOUNTS_PER_NFT
+pub fn cancel<'info>(ctx: Context<'_, '_, '_, 'info, MarketSwapCancel<'info>>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let market_account_key = accounts.market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    let vaults = SwapVault::load_all(
+        ctx.program_id,
+        &accounts.market_account,
+        accounts.creator.key,
+        ctx.remaining_accounts,
+    )?;
+    for vault in vaults {
+        token::transfer(
+            vault
+                .transfer_context(&accounts.token_program, &accounts.vault_authority)
+                .with_signer(&[&authority_seeds[..]]),
+            1,
+        )?;
+        token::close_account(
+            vault
+                .close_context(
+                    &accounts.token_program,
+                    &accounts.vault_authority,
+                    &accounts.creator,
+                )
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+    }
+
+    // creator_sol goes back with the listing's rent
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        market_account_key,
+    )
+}
+
+// the vaults of the offered nfts and the taker's token accounts are the
+// remaining accounts, see SWAP_ACCOUNTS_PER_NFT, then optionally the
+// [referrer, referrer_account] pair
+pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketSwapExchange<'info>>) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let market_account = &accounts.market_account;
+    let taker_mint = accounts.taker_mint.key();
+    let wanted = if market_account.want_mint != Pubkey::default() {
+        taker_mint == market_account.want_mint
+    } else {
+        verified_collection(&accounts.taker_metadata, &taker_mint)?
+            == Some(market_account.want_collection)
+    };
+    if !wanted {
+        return err!(Wen3ExError::NftNotWanted);
+    }
+
+    let vault_accounts_len = market_account.offered.len() * SWAP_ACCOUNTS_PER_NFT;
+    let (vault_accounts, referrer_accounts) = ctx
+        .remaining_accounts
+        .split_at(vault_accounts_len.min(ctx.remaining_accounts.len()));
+    let mut referrer = match referrer_accounts.len() {
+        0 => None,
+        2 => Referrer::load(referrer_accounts, accounts.taker.key)?,
+        _ => return err!(Wen3ExError::InvalidSwapAccounts),
+    };
+
+    // the protocol fee is on the sol paid by the taker
+    let taker_sol = market_account.taker_sol;
+    let fees = accounts.config.fee_split(taker_sol, referrer.is_some())?;
+    send_lamports(
+        &accounts.taker,
+        &accounts.creator,
+        Some(&accounts.system_program),
+        taker_sol - fees.total(),
+    )?;
+    pay_fees(
+        ctx.program_id,
+        &accounts.taker,
+        &accounts.treasury,
+        referrer.as_mut(),
+        Some(&accounts.system_program),
+        &fees,
+        taker_sol,
+    )?;
+    send_lamports(
+        &market_account.to_account_info(),
+        &accounts.taker,
+        None,
+        market_account.creator_sol,
+    )?;
+    token::transfer(accounts.transfer_from_taker_to_creator_context(), 1)?;
+
+    let market_account_key = market_account.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+    let vaults = SwapVault::load_all(
+        ctx.program_id,
+        market_account,
+        accounts.taker.key,
+        vault_accounts,
+    )?;
+    for vault in vaults {
+        token::transfer(
+            vault
+                .transfer_context(&accounts.token_program, &accounts.vault_authority)
+                .with_signer(&[&authority_seeds[..]]),
+            1,
+        )?;
+        token::close_account(
+            vault
+                .close_context(
+                    &accounts.token_program,
+                    &accounts.vault_authority,
+                    &accounts.creator,
+                )
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+    }
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        market_account_key,
+    )
+}
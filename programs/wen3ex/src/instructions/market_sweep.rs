@@ -0,0 +1,267 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+    AccountsClose,
+};
+use anchor_spl::token::{self, CloseAccount, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        MARKET_NS, MARKET_TS, NFT_VAULT_TOKEN, SALE_PHASE_SEED, VAULT_AUTHORITY_SEED,
+        VAULT_NFT_2_SOL_SEED, VAULT_TOKEN_2_SOL_SEED,
+    },
+    state_listing_index::ListingIndexAccount,
+    state_ns::MarketNftToSolAccount,
+    state_referral::{pay_fees, Referrer},
+    state_sale_phase::SalePhaseAccount,
+    state_sweep::{Sweep, SweepFilled, SWEEP_ACCOUNTS_PER_LISTING},
+    state_ts::MarketTsAccount,
+    utils::send_lamports,
+};
+
+// what sweep needs from either listing type
+struct Listing {
+    creator: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    sol_amount: u64,
+    bounty: u64,
+    referral_bps: u32,
+    vault_seed: &'static [u8],
+}
+
+pub fn sweep<'info>(
+    ctx: Context<'_, '_, '_, 'info, Sweep<'info>>,
+    max_sol: u64,
+    max_quantity: u64,
+) -> Result<()> {
+    let groups = ctx
+        .remaining_accounts
+        .chunks_exact(SWEEP_ACCOUNTS_PER_LISTING);
+    let mut referrer = match groups.remainder().len() {
+        0 => None,
+        2 => Referrer::load(groups.remainder(), ctx.accounts.taker.key)?,
+        _ => return err!(Wen3ExError::InvalidSweepAccounts),
+    };
+
+    let taker = ctx.accounts.taker.to_account_info();
+    let config = &ctx.accounts.config;
+    let mut sol_spent: u64 = 0;
+    let mut quantity: u64 = 0;
+    let mut listings: u32 = 0;
+    let mut skipped: u32 = 0;
+    let now_ts = Clock::get()?.unix_timestamp;
+
+    for accounts in groups {
+        let (market_info, vault_info, vault_authority, creator, taker_token_account, sale_phase) = (
+            &accounts[0],
+            &accounts[1],
+            &accounts[2],
+            &accounts[3],
+            &accounts[4],
+            &accounts[5],
+        );
+        let (mint_index, creator_index) = (&accounts[6], &accounts[7]);
+
+        // taken, cancelled, not open yet or expired meanwhile, or paused
+        let listing = match load_listing(market_info, now_ts) {
+            Some(listing) => listing,
+            None => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let market = if listing.vault_seed == VAULT_TOKEN_2_SOL_SEED {
+            MARKET_TS
+        } else {
+            MARKET_NS
+        };
+        if config.is_paused(market) {
+            skipped += 1;
+            continue;
+        }
+        // presale listings need a proof, see market_ts_exchange_allowlisted
+        if market == MARKET_TS {
+            let (sale_phase_key, _sale_phase_bump) = Pubkey::find_program_address(
+                &[
+                    SALE_PHASE_SEED,
+                    listing.creator.as_ref(),
+                    listing.mint.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            if sale_phase_key != sale_phase.key() {
+                return err!(Wen3ExError::InvalidSweepAccounts);
+            }
+            if let Some(sale_phase) = SalePhaseAccount::load(ctx.program_id, sale_phase)? {
+                if sale_phase.is_presale(now_ts) {
+                    skipped += 1;
+                    continue;
+                }
+            }
+        }
+
+        let next_sol_spent = sol_spent
+            .checked_add(listing.sol_amount)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        let next_quantity = quantity
+            .checked_add(listing.amount)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        if next_sol_spent > max_sol || next_quantity > max_quantity {
+            skipped += 1;
+            continue;
+        }
+
+        // the listing is live, from here on a mismatch is the caller's mistake
+        let market_key = market_info.key();
+        let (vault_key, _vault_bump) = Pubkey::find_program_address(
+            &[listing.vault_seed, market_key.as_ref()],
+            ctx.program_id,
+        );
+        let (vault_authority_key, vault_authority_bump) = Pubkey::find_program_address(
+            &[VAULT_AUTHORITY_SEED, market_key.as_ref()],
+            ctx.program_id,
+        );
+        if vault_key != vault_info.key() || vault_authority_key != vault_authority.key() {
+            return err!(Wen3ExError::IncorrectVaultAuthorityAccount);
+        }
+        if Account::<TokenAccount>::try_from(vault_info)?.is_frozen() {
+            return err!(Wen3ExError::VaultFrozen);
+        }
+        if listing.creator != creator.key() {
+            return err!(Wen3ExError::IncorrectCreatorTokenAccount);
+        }
+        if ListingIndexAccount::address(ctx.program_id, false, &listing.mint) != mint_index.key()
+            || ListingIndexAccount::address(ctx.program_id, true, &listing.creator)
+                != creator_index.key()
+        {
+            return err!(Wen3ExError::InvalidSweepAccounts);
+        }
+        let taker_token = Account::<TokenAccount>::try_from(taker_token_account)?;
+        if taker_token.mint != listing.mint {
+            return err!(Wen3ExError::IncorrectTakerTokenAccount);
+        }
+
+        let authority_seeds = &[
+            VAULT_AUTHORITY_SEED,
+            market_key.as_ref(),
+            &[vault_authority_bump],
+        ];
+
+        let fees =
+            config.maker_fee_split(listing.sol_amount, referrer.is_some(), listing.referral_bps)?;
+        invoke(
+            &system_instruction::transfer(
+                taker.key,
+                creator.key,
+                listing.sol_amount - fees.total(),
+            ),
+            &[
+                taker.clone(),
+                creator.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        pay_fees(
+            ctx.program_id,
+            &taker,
+            &ctx.accounts.treasury,
+            referrer.as_mut(),
+            Some(&ctx.accounts.system_program),
+            &fees,
+            listing.sol_amount,
+        )?;
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.clone(),
+                Transfer {
+                    from: vault_info.clone(),
+                    to: taker_token_account.clone(),
+                    authority: vault_authority.clone(),
+                },
+            )
+            .with_signer(&[&authority_seeds[..]]),
+            listing.amount,
+        )?;
+        token::close_account(
+            CpiContext::new(
+                ctx.accounts.token_program.clone(),
+                CloseAccount {
+                    account: vault_info.clone(),
+                    destination: creator.clone(),
+                    authority: vault_authority.clone(),
+                },
+            )
+            .with_signer(&[&authority_seeds[..]]),
+        )?;
+        send_lamports(market_info, &taker, None, listing.bounty)?;
+        close_listing(market_info, creator)?;
+        ListingIndexAccount::remove(ctx.program_id, mint_index, creator_index, market_key)?;
+
+        sol_spent = next_sol_spent;
+        quantity = next_quantity;
+        listings += 1;
+    }
+
+    emit!(SweepFilled {
+        taker: taker.key(),
+        listings,
+        skipped,
+        sol_spent,
+        quantity,
+    });
+
+    Ok(())
+}
+
+fn load_listing(market_info: &AccountInfo, now_ts: i64) -> Option<Listing> {
+    if let Ok(market) = Account::<MarketTsAccount>::try_from(market_info) {
+        if market.version != MarketTsAccount::VERSION
+            || !market.is_open(now_ts)
+            || market.is_expired(now_ts)
+            || market.is_vesting()
+            || market.is_pegged()
+        {
+            return None;
+        }
+        return Some(Listing {
+            creator: market.creator,
+            mint: market.token,
+            amount: market.token_amount,
+            sol_amount: market.sol_amount,
+            bounty: market.bounty,
+            referral_bps: market.referral_bps,
+            vault_seed: VAULT_TOKEN_2_SOL_SEED,
+        });
+    }
+    if let Ok(market) = Account::<MarketNftToSolAccount>::try_from(market_info) {
+        if market.version != MarketNftToSolAccount::VERSION
+            || market.vault_kind != NFT_VAULT_TOKEN
+            || !market.is_open(now_ts)
+        {
+            return None;
+        }
+        return Some(Listing {
+            creator: market.creator,
+            mint: market.nft_token,
+            amount: market.nft_amount,
+            sol_amount: market.sol_amount,
+            bounty: 0,
+            referral_bps: market.referral_bps,
+            vault_seed: VAULT_NFT_2_SOL_SEED,
+        });
+    }
+    None
+}
+
+fn close_listing<'info>(
+    market_info: &AccountInfo<'info>,
+    creator: &AccountInfo<'info>,
+) -> Result<()> {
+    if let Ok(market) = Account::<MarketTsAccount>::try_from(market_info) {
+        return market.close(creator.clone());
+    }
+    Account::<MarketNftToSolAccount>::try_from(market_info)?.close(creator.clone())
+}
@@ -2,19 +2,64 @@ use anchor_lang::{prelude::*, solana_program};
 use anchor_spl::token;
 use spl_token::instruction::AuthorityType;
 
+use crate::errors::Wen3ExError;
+
 use super::{
-    constants::VAULT_AUTHORITY_SEED,
-    state_ts::{MarketTsCancel, MarketTsCreate, MarketTsExchange},
+    constants::{MARKET_TS, MAX_ORACLE_CONF_BPS, VAULT_AUTHORITY_SEED},
+    oracle::OraclePrice,
+    state_listing_index::ListingIndexAccount,
+    state_referral::{pay_fees, Referrer},
+    state_sale_phase::{AllowlistClaimAccount, SalePhaseAccount},
+    state_ts::{
+        MarketTsAccount, MarketTsCancel, MarketTsCrank, MarketTsCreate, MarketTsExchange,
+        MarketTsExchangeAllowlisted, MarketTsExchangePegged, MarketTsExchangeVested,
+        MarketTsSetPeg, MarketTsSetReferral,
+    },
+    state_vesting::VestingAccount,
+    utils::{check_expire_time, send_lamports},
 };
 
-pub fn create(ctx: Context<MarketTsCreate>, token_amount: u64, sol_amount: u64) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn create(
+    ctx: Context<MarketTsCreate>,
+    token_amount: u64,
+    sol_amount: u64,
+    bounty: u64,
+    expire_time: i64,
+    start_time: i64,
+    vesting_cliff: i64,
+    vesting_duration: i64,
+) -> Result<()> {
+    ctx.accounts
+        .config
+        .check_mint_listable(MARKET_TS, ctx.program_id, &ctx.accounts.mint_entry)?;
+    ctx.accounts
+        .config
+        .check_freeze_authority(MARKET_TS, &ctx.accounts.mint)?;
+    let now_ts = Clock::get()?.unix_timestamp;
+    check_expire_time(expire_time, now_ts.max(start_time))?;
+    if vesting_duration < 0 || vesting_cliff < 0 || vesting_cliff > vesting_duration {
+        return err!(Wen3ExError::InvalidVestingSchedule);
+    }
+    // nothing would ever unlock, and the vesting account never close
+    if vesting_duration > 0 && token_amount == 0 {
+        return err!(Wen3ExError::InvalidAmount);
+    }
+
     let market_account = &mut ctx.accounts.market_account;
 
+    market_account.version = MarketTsAccount::VERSION;
     market_account.creator = *ctx.accounts.creator.key;
     market_account.token = ctx.accounts.mint.key();
     market_account.token_amount = token_amount;
     market_account.sol_amount = sol_amount;
-    market_account.create_time = Clock::get()?.unix_timestamp;
+    market_account.create_time = now_ts;
+    market_account.bounty = bounty;
+    market_account.expire_time = expire_time;
+    market_account.start_time = start_time;
+    market_account.token_decimals = Some(ctx.accounts.mint.decimals);
+    market_account.vesting_cliff = vesting_cliff;
+    market_account.vesting_duration = vesting_duration;
 
     let (vault_authority, _vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account.key().as_ref()],
@@ -26,12 +71,29 @@ pub fn create(ctx: Context<MarketTsCreate>, token_amount: u64, sol_amount: u64)
         ctx.accounts.market_account.token_amount,
     )?;
 
+    // the bounty waits in the market account, cancel hands it back on close
+    send_lamports(
+        &ctx.accounts.creator,
+        &ctx.accounts.market_account.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        bounty,
+    )?;
+
     // change vault_token_account authority from creator to program
     token::set_authority(
         ctx.accounts.set_vault_authority_context(),
         AuthorityType::AccountOwner,
         Some(vault_authority),
     )?;
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )?;
+
     Ok(())
 }
 
@@ -74,22 +136,224 @@ pub fn cancel(ctx: Context<MarketTsCancel>) -> Result<()> {
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
 
 pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketTsExchange<'info>>) -> Result<()> {
-    let creator_account = ctx.accounts.creator.to_account_info();
-    let taker_account = ctx.accounts.taker.to_account_info();
+    if let Some(sale_phase) = SalePhaseAccount::load(ctx.program_id, &ctx.accounts.sale_phase)? {
+        if sale_phase.is_presale(Clock::get()?.unix_timestamp) {
+            return err!(Wen3ExError::AllowlistRequired);
+        }
+    }
+    if ctx.accounts.market_account.is_vesting() {
+        return err!(Wen3ExError::VestingRequired);
+    }
+
+    let sol_amount = ctx.accounts.market_account.fixed_sol_amount()?;
     let taker_token_account = ctx.accounts.taker_token_account.to_account_info();
-    // let taker_token_account_info = ctx.accounts.taker_token_account.clone();
-    // let vault_token_account = ctx.accounts.vault_token_account.to_account_info();
+    fill(
+        ctx.accounts,
+        ctx.program_id,
+        ctx.remaining_accounts,
+        taker_token_account,
+        sol_amount,
+    )
+}
 
-    let market_account = ctx.accounts.market_account.clone();
+pub fn exchange_allowlisted<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketTsExchangeAllowlisted<'info>>,
+    allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let exchange = &ctx.accounts.exchange;
+    let sale_phase = match SalePhaseAccount::load(ctx.program_id, &exchange.sale_phase)? {
+        Some(sale_phase) => sale_phase,
+        None => return err!(Wen3ExError::NotAllowlisted),
+    };
+    if Clock::get()?.unix_timestamp < sale_phase.start_time {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if !sale_phase.verify(exchange.taker.key, allocation, &proof) {
+        return err!(Wen3ExError::NotAllowlisted);
+    }
+
+    let taken = ctx
+        .accounts
+        .allowlist_claim
+        .taken
+        .checked_add(exchange.market_account.token_amount)
+        .ok_or(Wen3ExError::NumericalOverflowError)?;
+    if taken > allocation {
+        return err!(Wen3ExError::AllocationExceeded);
+    }
+    let sale_phase_key = exchange.sale_phase.key();
+    let taker_key = exchange.taker.key();
+    let allowlist_claim = &mut ctx.accounts.allowlist_claim;
+    if allowlist_claim.version == 0 {
+        allowlist_claim.version = AllowlistClaimAccount::VERSION;
+        allowlist_claim.sale_phase = sale_phase_key;
+        allowlist_claim.wallet = taker_key;
+        allowlist_claim.bump = *ctx.bumps.get("allowlist_claim").unwrap();
+    }
+    allowlist_claim.taken = taken;
+
+    let exchange = &ctx.accounts.exchange;
+    if exchange.market_account.is_vesting() {
+        return err!(Wen3ExError::VestingRequired);
+    }
+    let sol_amount = exchange.market_account.fixed_sol_amount()?;
+    let taker_token_account = exchange.taker_token_account.to_account_info();
+    fill(
+        exchange,
+        ctx.program_id,
+        ctx.remaining_accounts,
+        taker_token_account,
+        sol_amount,
+    )
+}
+
+pub fn exchange_vested<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketTsExchangeVested<'info>>,
+) -> Result<()> {
+    let exchange = &ctx.accounts.exchange;
+    let now_ts = Clock::get()?.unix_timestamp;
+    if let Some(sale_phase) = SalePhaseAccount::load(ctx.program_id, &exchange.sale_phase)? {
+        if sale_phase.is_presale(now_ts) {
+            return err!(Wen3ExError::AllowlistRequired);
+        }
+    }
+    let market_account = &exchange.market_account;
+    if !market_account.is_vesting() {
+        return err!(Wen3ExError::NotVesting);
+    }
+    let sol_amount = market_account.fixed_sol_amount()?;
+
+    let vesting = &mut ctx.accounts.vesting;
+    vesting.version = VestingAccount::VERSION;
+    vesting.beneficiary = exchange.taker.key();
+    vesting.mint = market_account.token;
+    vesting.total = market_account.token_amount;
+    vesting.start_time = now_ts;
+    vesting.cliff_time = now_ts + market_account.vesting_cliff;
+    vesting.end_time = now_ts + market_account.vesting_duration;
+    vesting.bump = *ctx.bumps.get("vesting").unwrap();
+
+    let vesting_vault = ctx.accounts.vesting_vault.to_account_info();
+    fill(
+        &ctx.accounts.exchange,
+        ctx.program_id,
+        ctx.remaining_accounts,
+        vesting_vault,
+        sol_amount,
+    )
+}
+
+pub fn exchange_pegged<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarketTsExchangePegged<'info>>,
+    max_sol_amount: u64,
+) -> Result<()> {
+    let exchange = &ctx.accounts.exchange;
+    let now_ts = Clock::get()?.unix_timestamp;
+    if let Some(sale_phase) = SalePhaseAccount::load(ctx.program_id, &exchange.sale_phase)? {
+        if sale_phase.is_presale(now_ts) {
+            return err!(Wen3ExError::AllowlistRequired);
+        }
+    }
+    let market_account = &exchange.market_account;
+    if market_account.is_vesting() {
+        return err!(Wen3ExError::VestingRequired);
+    }
+
+    // priced now, the taker bounds it as the oracle may move
+    let sol_amount = OraclePrice::load(&ctx.accounts.oracle)?
+        .checked(
+            now_ts,
+            market_account.oracle_max_age,
+            market_account.oracle_max_conf_bps,
+        )?
+        .sol_amount(
+            market_account.token_amount,
+            exchange.mint.decimals,
+            market_account.spread_bps,
+        )?;
+    if sol_amount > max_sol_amount {
+        return err!(Wen3ExError::PriceAboveMax);
+    }
+
+    let taker_token_account = exchange.taker_token_account.to_account_info();
+    fill(
+        exchange,
+        ctx.program_id,
+        ctx.remaining_accounts,
+        taker_token_account,
+        sol_amount,
+    )
+}
+
+pub fn set_peg(
+    ctx: Context<MarketTsSetPeg>,
+    spread_bps: i32,
+    oracle_max_age: i64,
+    oracle_max_conf_bps: u32,
+) -> Result<()> {
+    let oracle = &ctx.accounts.oracle;
+    if oracle.key() != Pubkey::default() {
+        if oracle_max_age <= 0
+            || oracle_max_conf_bps == 0
+            || oracle_max_conf_bps > MAX_ORACLE_CONF_BPS
+        {
+            return err!(Wen3ExError::InvalidOracleLimits);
+        }
+        OraclePrice::load(oracle)?;
+    }
+
+    let market_account = &mut ctx.accounts.market_account;
+    market_account.oracle = oracle.key();
+    market_account.spread_bps = spread_bps;
+    market_account.oracle_max_age = oracle_max_age;
+    market_account.oracle_max_conf_bps = oracle_max_conf_bps;
+
+    Ok(())
+}
+
+pub fn set_referral(ctx: Context<MarketTsSetReferral>, referral_bps: u32) -> Result<()> {
+    ctx.accounts.market_account.referral_bps = referral_bps;
+    Ok(())
+}
+
+// takes `sol_amount` from the taker and delivers the tokens to `recipient`
+fn fill<'info>(
+    accounts: &MarketTsExchange<'info>,
+    program_id: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    recipient: AccountInfo<'info>,
+    sol_amount: u64,
+) -> Result<()> {
+    let creator_account = accounts.creator.to_account_info();
+    let taker_account = accounts.taker.to_account_info();
+    // let taker_token_account_info = accounts.taker_token_account.clone();
+    // let vault_token_account = accounts.vault_token_account.to_account_info();
+
+    let market_account = accounts.market_account.clone();
     let market_account_key = market_account.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !market_account.is_open(now_ts) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if market_account.is_expired(now_ts) {
+        return err!(Wen3ExError::ListingExpired);
+    }
 
     let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account_key.as_ref()],
-        ctx.program_id,
+        program_id,
     );
 
     let authority_seeds = &[
@@ -99,30 +363,101 @@ pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketTsExchange<'info>>)
     ];
 
     // for taker, taker is buy token
-    // transfer sol from taker to creator
+    // transfer sol from taker to creator, less the fees
+    let mut referrer = Referrer::load(remaining_accounts, accounts.taker.key)?;
+    let fees = accounts.config.maker_fee_split(
+        sol_amount,
+        referrer.is_some(),
+        market_account.referral_bps,
+    )?;
     solana_program::program::invoke(
         &solana_program::system_instruction::transfer(
             taker_account.key,
             creator_account.key,
-            market_account.sol_amount,
+            sol_amount - fees.total(),
         ),
         &[
-            ctx.accounts.taker.to_account_info(),
-            ctx.accounts.creator.to_account_info(),
+            accounts.taker.to_account_info(),
+            accounts.creator.to_account_info(),
         ],
     )?;
+    pay_fees(
+        program_id,
+        &taker_account,
+        &accounts.treasury,
+        referrer.as_mut(),
+        Some(&accounts.system_program),
+        &fees,
+        sol_amount,
+    )?;
     // transfer token from vault to taker
+    token::transfer(
+        accounts
+            .transfer_from_vault_to_taker_context(recipient)
+            .with_signer(&[&authority_seeds[..]]),
+        accounts.market_account.token_amount,
+    )?;
+    token::close_account(
+        accounts
+            .close_vault_to_creator_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+    send_lamports(
+        &accounts.market_account.to_account_info(),
+        &taker_account,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        program_id,
+        &accounts.mint_index,
+        &accounts.creator_index,
+        accounts.market_account.key(),
+    )?;
+
+    Ok(())
+}
+
+pub fn crank(ctx: Context<MarketTsCrank>) -> Result<()> {
+    let market_account = ctx.accounts.market_account.clone();
+    let market_account_key = market_account.key();
+    if !market_account.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::ListingNotExpired);
+    }
+
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    // same as cancel, only the bounty goes to whoever closed it
     token::transfer(
         ctx.accounts
-            .transfer_from_vault_to_taker_context(taker_token_account)
+            .transfer_from_vault_to_creator_context()
             .with_signer(&[&authority_seeds[..]]),
-        ctx.accounts.market_account.token_amount,
+        market_account.token_amount,
     )?;
     token::close_account(
         ctx.accounts
-            .close_vault_to_creator_context()
+            .close_vault_context()
             .with_signer(&[&authority_seeds[..]]),
     )?;
+    send_lamports(
+        &ctx.accounts.market_account.to_account_info(),
+        &ctx.accounts.cranker,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
 
     Ok(())
 }
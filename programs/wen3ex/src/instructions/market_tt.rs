@@ -5,28 +5,47 @@ use anchor_spl::token;
 use spl_token::instruction::AuthorityType;
 
 use super::{
-    constants::VAULT_AUTHORITY_SEED,
-    state_tt::{MarketTtCancel, MarketTtCreate, MarketTtExchange},
+    constants::{MARKET_TT, VAULT_AUTHORITY_SEED},
+    state_listing_index::ListingIndexAccount,
+    state_tt::{MarketTtAccount, MarketTtCancel, MarketTtCrank, MarketTtCreate, MarketTtExchange},
+    utils::{check_expire_time, send_lamports},
 };
 use crate::errors::Wen3ExError;
 
+#[allow(clippy::too_many_arguments)]
 pub fn create(
     ctx: Context<MarketTtCreate>,
     deposit_amount: u64,
     receive_amount: u64,
     deposit_token: Pubkey,
     receive_token: Pubkey,
+    bounty: u64,
+    expire_time: i64,
+    start_time: i64,
 ) -> Result<()> {
+    let config = &ctx.accounts.config;
+    config.check_mint_listable(MARKET_TT, ctx.program_id, &ctx.accounts.deposit_mint_entry)?;
+    config.check_mint_listable(MARKET_TT, ctx.program_id, &ctx.accounts.receive_mint_entry)?;
+    config.check_freeze_authority(MARKET_TT, &ctx.accounts.mint)?;
+    config.check_freeze_authority(MARKET_TT, &ctx.accounts.receive_mint)?;
+
     let now_ts = Clock::get()?.unix_timestamp;
+    check_expire_time(expire_time, now_ts.max(start_time))?;
 
     let market_account = &mut ctx.accounts.market_account;
 
+    market_account.version = MarketTtAccount::VERSION;
     market_account.creator = *ctx.accounts.creator.key;
     market_account.deposit_amount = deposit_amount;
     market_account.receive_amount = receive_amount;
     market_account.deposit_token = deposit_token;
     market_account.receive_token = receive_token;
     market_account.create_time = now_ts;
+    market_account.bounty = bounty;
+    market_account.expire_time = expire_time;
+    market_account.start_time = start_time;
+    market_account.deposit_decimals = Some(ctx.accounts.mint.decimals);
+    market_account.receive_decimals = Some(ctx.accounts.receive_mint.decimals);
 
     let (vault_authority, _vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account.key().as_ref()],
@@ -44,6 +63,23 @@ pub fn create(
         ctx.accounts.market_account.deposit_amount,
     )?;
 
+    // the bounty waits in the market account, cancel hands it back on close
+    send_lamports(
+        &ctx.accounts.creator,
+        &ctx.accounts.market_account.to_account_info(),
+        Some(&ctx.accounts.system_program),
+        bounty,
+    )?;
+
+    ListingIndexAccount::insert(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+        &ctx.accounts.creator,
+        &ctx.accounts.system_program,
+    )?;
+
     Ok(())
 }
 
@@ -79,12 +115,26 @@ pub fn cancel(ctx: Context<MarketTtCancel>) -> Result<()> {
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
 
-pub fn exchange(ctx: Context<MarketTtExchange>) -> Result<()> {
+pub fn exchange<'info>(ctx: Context<'_, '_, '_, 'info, MarketTtExchange<'info>>) -> Result<()> {
     let market_account = ctx.accounts.market_account.clone();
     let market_account_key = market_account.key();
+    let now_ts = Clock::get()?.unix_timestamp;
+    if !market_account.is_open(now_ts) {
+        return err!(Wen3ExError::NotYetOpen);
+    }
+    if market_account.is_expired(now_ts) {
+        return err!(Wen3ExError::ListingExpired);
+    }
 
     let (_vault_authority, vault_authority_bump) = Pubkey::find_program_address(
         &[VAULT_AUTHORITY_SEED, market_account_key.as_ref()],
@@ -115,5 +165,62 @@ pub fn exchange(ctx: Context<MarketTtExchange>) -> Result<()> {
             .with_signer(&[&authority_seeds[..]]),
     )?;
 
+    send_lamports(
+        &ctx.accounts.market_account.to_account_info(),
+        &ctx.accounts.taker,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
+    Ok(())
+}
+
+pub fn crank(ctx: Context<MarketTtCrank>) -> Result<()> {
+    let market_account = ctx.accounts.market_account.clone();
+    let market_account_key = market_account.key();
+    if !market_account.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(Wen3ExError::ListingNotExpired);
+    }
+
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        market_account_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    // same as cancel, only the bounty goes to whoever closed it
+    token::transfer(
+        ctx.accounts
+            .transfer_to_creator_context()
+            .with_signer(&[&authority_seeds[..]]),
+        market_account.deposit_amount,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .close_context()
+            .with_signer(&[&authority_seeds[..]]),
+    )?;
+    send_lamports(
+        &ctx.accounts.market_account.to_account_info(),
+        &ctx.accounts.cranker,
+        None,
+        market_account.bounty,
+    )?;
+
+    ListingIndexAccount::remove(
+        ctx.program_id,
+        &ctx.accounts.mint_index,
+        &ctx.accounts.creator_index,
+        ctx.accounts.market_account.key(),
+    )?;
+
     Ok(())
 }
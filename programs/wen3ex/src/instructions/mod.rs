@@ -1,21 +1,101 @@
+mod bubblegum;
 mod constants;
+mod oracle;
+mod token_metadata;
+mod utils;
+
+pub mod config;
+pub mod state_config;
+pub use state_config::*;
+
+pub mod registry;
+pub mod state_registry;
+pub use state_registry::*;
+
+pub mod referral;
+pub mod state_referral;
+pub use state_referral::*;
+
+pub mod listing_index;
+pub mod state_listing_index;
+pub use state_listing_index::*;
+
+pub mod sale_phase;
+pub mod state_sale_phase;
+pub use state_sale_phase::*;
+
+pub mod state_vesting;
+pub mod vesting;
+pub use state_vesting::*;
 
 pub mod market_tt;
-pub use market_tt::*;
 pub mod state_tt;
 pub use state_tt::*;
 
 pub mod market_ts;
-pub use market_ts::*;
 pub mod state_ts;
 pub use state_ts::*;
 
 pub mod market_st;
-pub use market_st::*;
 pub mod state_st;
 pub use state_st::*;
 
 pub mod market_ns;
-pub use market_ns::*;
 pub mod state_ns;
 pub use state_ns::*;
+
+pub mod market_pnft;
+pub mod state_pnft;
+pub use state_pnft::*;
+
+pub mod market_migrate;
+pub mod state_migrate;
+pub use state_migrate::*;
+
+pub mod market_reclaim;
+pub mod state_reclaim;
+pub use state_reclaim::*;
+
+pub mod market_price;
+pub mod state_price;
+pub use state_price::*;
+
+pub mod market_order_book;
+pub mod state_order_book;
+pub use state_order_book::*;
+
+pub mod market_sweep;
+pub mod state_sweep;
+pub use state_sweep::*;
+
+pub mod market_offer;
+pub mod state_offer;
+pub use state_offer::*;
+
+pub mod market_launchpad;
+pub mod state_launchpad;
+pub use state_launchpad::*;
+
+pub mod market_escrow;
+pub mod state_escrow;
+pub use state_escrow::*;
+
+pub mod market_pool;
+pub mod state_pool;
+pub use state_pool::*;
+
+pub mod market_route;
+pub mod state_route;
+pub use state_route::*;
+
+pub mod market_delegated;
+pub mod state_delegated;
+pub use state_delegated::*;
+
+pub mod market_cnft;
+pub mod state_cnft;
+pub use state_cnft::*;
+
+pub mod market_swap;
+pub mod state_swap;
+pub use state_swap::*;
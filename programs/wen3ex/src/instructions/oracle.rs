@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::constants::BPS_DENOMINATOR;
+
+// price accounts are only read from the pyth oracle program
+pub mod pyth_program {
+    anchor_lang::declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+}
+
+// the parts of a pyth v2 price account we read, offsets into its data
+const MAGIC: u32 = 0xa1b2_c3d4;
+const ATYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+// aggregate price of a pyth style feed, price * 10^expo
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    pub fn load(oracle: &AccountInfo) -> Result<Self> {
+        if *oracle.owner != pyth_program::ID {
+            return err!(Wen3ExError::InvalidOracle);
+        }
+        let data = oracle.try_borrow_data()?;
+        if data.len() < PRICE_ACCOUNT_MIN_LEN
+            || read_u32(&data, 0) != MAGIC
+            || read_u32(&data, 8) != ATYPE_PRICE
+        {
+            return err!(Wen3ExError::InvalidOracle);
+        }
+        if read_u32(&data, AGG_STATUS_OFFSET) != STATUS_TRADING {
+            return err!(Wen3ExError::InvalidOracle);
+        }
+        Ok(Self {
+            price: read_u64(&data, AGG_PRICE_OFFSET) as i64,
+            conf: read_u64(&data, AGG_CONF_OFFSET),
+            expo: read_u32(&data, EXPO_OFFSET) as i32,
+            publish_time: read_u64(&data, TIMESTAMP_OFFSET) as i64,
+        })
+    }
+
+    // the price, provided it is recent and tight enough
+    pub fn checked(self, now_ts: i64, max_age: i64, max_conf_bps: u32) -> Result<Self> {
+        if self.price <= 0 {
+            return err!(Wen3ExError::InvalidOracle);
+        }
+        if now_ts.saturating_sub(self.publish_time) > max_age {
+            return err!(Wen3ExError::StaleOraclePrice);
+        }
+        if self.conf as u128 * BPS_DENOMINATOR as u128 > self.price as u128 * max_conf_bps as u128 {
+            return err!(Wen3ExError::OracleConfidenceTooWide);
+        }
+        Ok(self)
+    }
+
+    // lamports for `amount` atoms of a mint with `decimals`, the feed quoting
+    // sol per whole token, moved by `spread_bps`
+    pub fn sol_amount(&self, amount: u64, decimals: u8, spread_bps: i32) -> Result<u64> {
+        let value = (amount as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        let exp = self.expo + 9 - decimals as i32;
+        let scale = 10u128
+            .checked_pow(exp.unsigned_abs())
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        let value = if exp >= 0 {
+            value
+                .checked_mul(scale)
+                .ok_or(Wen3ExError::NumericalOverflowError)?
+        } else {
+            value / scale
+        };
+        let value = value
+            .checked_mul((BPS_DENOMINATOR as i64 + spread_bps as i64) as u128)
+            .ok_or(Wen3ExError::NumericalOverflowError)?
+            / BPS_DENOMINATOR as u128;
+        u64::try_from(value).map_err(|_| error!(Wen3ExError::NumericalOverflowError))
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+use super::state_referral::{ReferrerAccount, ReferrerRegister};
+
+pub fn register(ctx: Context<ReferrerRegister>) -> Result<()> {
+    let referrer_account = &mut ctx.accounts.referrer_account;
+
+    referrer_account.version = ReferrerAccount::VERSION;
+    referrer_account.referrer = ctx.accounts.referrer.key();
+    referrer_account.bump = *ctx.bumps.get("referrer_account").unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use super::{
+    state_config::ConfigUpdate,
+    state_registry::{MintEntryAccount, MintEntryRemove, MintEntrySet},
+};
+
+pub fn set_mint_status(ctx: Context<MintEntrySet>, status: u8) -> Result<()> {
+    let mint_entry = &mut ctx.accounts.mint_entry;
+
+    mint_entry.version = MintEntryAccount::VERSION;
+    mint_entry.mint = ctx.accounts.mint.key();
+    mint_entry.status = status;
+    mint_entry.bump = *ctx.bumps.get("mint_entry").unwrap();
+
+    Ok(())
+}
+
+pub fn remove_mint_entry(_ctx: Context<MintEntryRemove>) -> Result<()> {
+    Ok(())
+}
+
+pub fn set_mint_policy(
+    ctx: Context<ConfigUpdate>,
+    allowlist_markets: u32,
+    denylist_markets: u32,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.allowlist_markets = allowlist_markets;
+    config.denylist_markets = denylist_markets;
+
+    Ok(())
+}
+
+pub fn set_freeze_authority_policy(
+    ctx: Context<ConfigUpdate>,
+    freeze_authority_markets: u32,
+) -> Result<()> {
+    ctx.accounts.config.freeze_authority_markets = freeze_authority_markets;
+
+    Ok(())
+}
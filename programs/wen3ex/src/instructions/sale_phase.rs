@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use super::state_sale_phase::{SalePhaseAccount, SalePhaseClose, SalePhaseSet};
+
+pub fn set(
+    ctx: Context<SalePhaseSet>,
+    merkle_root: [u8; 32],
+    start_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    let sale_phase = &mut ctx.accounts.sale_phase;
+
+    sale_phase.version = SalePhaseAccount::VERSION;
+    sale_phase.creator = ctx.accounts.creator.key();
+    sale_phase.mint = ctx.accounts.mint.key();
+    sale_phase.merkle_root = merkle_root;
+    sale_phase.start_time = start_time;
+    sale_phase.end_time = end_time;
+    sale_phase.bump = *ctx.bumps.get("sale_phase").unwrap();
+
+    Ok(())
+}
+
+// closing the account is all there is to it
+pub fn close(_ctx: Context<SalePhaseClose>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    bubblegum::{bubblegum_program, spl_account_compression, spl_noop, CnftTransfer, LeafArgs},
+    constants::{
+        CNFT_LISTING_SEED, CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_CNFT, MINT_INDEX_SEED,
+        VAULT_AUTHORITY_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+// sell a compressed nft with sol back. the leaf is owned by the vault
+// authority of the listing while listed, its merkle proof comes in the
+// remaining accounts of every instruction. a leaf has no mint, the listing is
+// indexed under its merkle tree in the mint indexes
+#[account]
+pub struct MarketCnftToSolAccount {
+    pub version: u32,
+    pub creator: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub nonce: u64, // the leaf id within the tree
+    pub index: u32,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub sol_amount: u64,
+    pub create_time: i64,
+    pub bump: u8,
+    pub expire_time: i64, // 0 never expires
+    pub start_time: i64,  // not fillable before, 0 open right away
+}
+
+impl MarketCnftToSolAccount {
+    pub const VERSION: u32 = 2;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 4 + 32 + 32 + 8 + 8 + 1 + 8 + 8;
+
+    pub fn leaf(&self, root: [u8; 32]) -> LeafArgs {
+        LeafArgs {
+            root,
+            data_hash: self.data_hash,
+            creator_hash: self.creator_hash,
+            nonce: self.nonce,
+            index: self.index,
+        }
+    }
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        self.expire_time != 0 && now_ts >= self.expire_time
+    }
+
+    pub fn is_open(&self, now_ts: i64) -> bool {
+        now_ts >= self.start_time
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], data_hash: [u8; 32], creator_hash: [u8; 32], nonce: u64)]
+pub struct MarketCnftCreate<'info> {
+    #[account(
+        init,
+        seeds = [CNFT_LISTING_SEED, cnft.merkle_tree.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+        payer = creator,
+        space = MarketCnftToSolAccount::LEN,
+    )]
+    pub market_account: Box<Account<'info, MarketCnftToSolAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    /// CHECK: the leaf delegate, the creator when there is none
+    pub leaf_delegate: AccountInfo<'info>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_CNFT) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, cnft.merkle_tree.key().as_ref()], bump)]
+    /// CHECK: listing index of the merkle tree, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub cnft: CnftAccounts<'info>,
+}
+
+impl<'info> MarketCnftCreate<'info> {
+    pub fn transfer_from_creator_to_vault(&self) -> CnftTransfer<'_, 'info> {
+        self.cnft.transfer(
+            &self.creator,
+            &self.leaf_delegate,
+            &self.vault_authority,
+            &self.system_program,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketCnftCancel<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [CNFT_LISTING_SEED, market_account.merkle_tree.as_ref(), &market_account.nonce.to_le_bytes()],
+        bump = market_account.bump,
+        constraint = market_account.version == MarketCnftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.merkle_tree == cnft.merkle_tree.key(),
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketCnftToSolAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, cnft.merkle_tree.key().as_ref()], bump)]
+    /// CHECK: listing index of the merkle tree, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub cnft: CnftAccounts<'info>,
+}
+
+impl<'info> MarketCnftCancel<'info> {
+    pub fn transfer_from_vault_to_creator(&self) -> CnftTransfer<'_, 'info> {
+        self.cnft.transfer(
+            &self.vault_authority,
+            &self.vault_authority,
+            &self.creator,
+            &self.system_program,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketCnftExchange<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [CNFT_LISTING_SEED, market_account.merkle_tree.as_ref(), &market_account.nonce.to_le_bytes()],
+        bump = market_account.bump,
+        constraint = market_account.version == MarketCnftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.merkle_tree == cnft.merkle_tree.key(),
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketCnftToSolAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_CNFT) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, cnft.merkle_tree.key().as_ref()], bump)]
+    /// CHECK: listing index of the merkle tree, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub cnft: CnftAccounts<'info>,
+}
+
+impl<'info> MarketCnftExchange<'info> {
+    pub fn transfer_from_vault_to_taker(&self) -> CnftTransfer<'_, 'info> {
+        self.cnft.transfer(
+            &self.vault_authority,
+            &self.vault_authority,
+            &self.taker,
+            &self.system_program,
+        )
+    }
+}
+
+// what bubblegum's transfer needs besides the owners, checked by bubblegum
+#[derive(Accounts)]
+pub struct CnftAccounts<'info> {
+    /// CHECK: the tree config PDA of bubblegum
+    pub tree_authority: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: the concurrent merkle tree of the leaf
+    pub merkle_tree: AccountInfo<'info>,
+    #[account(address = spl_noop::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub log_wrapper: AccountInfo<'info>,
+    #[account(address = spl_account_compression::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub compression_program: AccountInfo<'info>,
+    #[account(address = bubblegum_program::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub bubblegum_program: AccountInfo<'info>,
+}
+
+impl<'info> CnftAccounts<'info> {
+    fn transfer<'a>(
+        &'a self,
+        leaf_owner: &'a AccountInfo<'info>,
+        leaf_delegate: &'a AccountInfo<'info>,
+        new_leaf_owner: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+    ) -> CnftTransfer<'a, 'info> {
+        CnftTransfer {
+            bubblegum_program: &self.bubblegum_program,
+            tree_authority: &self.tree_authority,
+            leaf_owner,
+            leaf_delegate,
+            new_leaf_owner,
+            merkle_tree: &self.merkle_tree,
+            log_wrapper: &self.log_wrapper,
+            compression_program: &self.compression_program,
+            system_program,
+        }
+    }
+}
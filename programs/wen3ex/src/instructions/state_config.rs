@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::Wen3ExError, program::Wen3ex};
+
+use super::constants::CONFIG_SEED;
+
+#[account] // program wide settings, one per program
+pub struct ConfigAccount {
+    pub version: u32,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey, // set by propose_admin, Pubkey::default() when none
+    pub paused_markets: u32,   // MARKET_* bits, create and exchange are rejected when set
+    pub bump: u8,
+    pub allowlist_markets: u32, // MARKET_* bits, only mints registered as allowed can be listed
+    pub denylist_markets: u32,  // MARKET_* bits, mints registered as denied can't be listed
+    pub treasury: Pubkey,       // receives the protocol fee
+    pub fee_bps: u32,           // protocol fee on the sol side of an exchange
+    pub referral_share_bps: u32, // part of the protocol fee paid to the referrer
+    pub freeze_authority_markets: u32, // MARKET_* bits, mints with a freeze authority can't be listed
+    pub reserved: [u8; 12],            // room for new settings without a realloc
+}
+
+impl ConfigAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 4 + 1 + 4 + 4 + 32 + 4 + 4 + 4 + 12;
+
+    pub fn is_paused(&self, market: u32) -> bool {
+        self.paused_markets & market != 0
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        seeds = [CONFIG_SEED],
+        bump,
+        payer = admin,
+        space = ConfigAccount::LEN,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+
+    // only the upgrade authority may claim the admin role, see check_upgrade_authority
+    pub program: Program<'info, Wen3ex>,
+    /// CHECK: the program's ProgramData, validated in check_upgrade_authority
+    pub program_data: AccountInfo<'info>,
+
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub admin: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> Initialize<'info> {
+    // built with the localnet feature, a program loaded without an upgrade
+    // authority, as `anchor test` does, lets the first caller be the admin
+    pub fn check_upgrade_authority(&self) -> Result<()> {
+        let program_data_address = match self.program.programdata_address()? {
+            Some(address) => address,
+            None if cfg!(feature = "localnet") => return Ok(()),
+            None => return err!(Wen3ExError::InvalidAuthority),
+        };
+        if self.program_data.key() != program_data_address {
+            return err!(Wen3ExError::InvalidAuthority);
+        }
+        let program_data = Account::<ProgramData>::try_from(&self.program_data)?;
+        if program_data.upgrade_authority_address != Some(self.admin.key()) {
+            return err!(Wen3ExError::InvalidAuthority);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ConfigUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub admin: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigAcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.pending_admin == pending_admin.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub pending_admin: AccountInfo<'info>,
+}
@@ -0,0 +1,193 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, DELEGATE_SEED, MARKET_DELEGATED, MINT_ENTRY_SEED,
+        MINT_INDEX_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+// sell token with sol back, without custody. the tokens stay in the creator's
+// token account, which approves the delegate PDA, and are pulled on exchange
+#[account]
+pub struct MarketDelegatedAccount {
+    pub version: u32,
+    pub creator: Pubkey,
+    pub token: Pubkey,  // the mint
+    pub source: Pubkey, // the creator's token account
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub create_time: i64,
+    pub expire_time: i64, // 0 never expires
+}
+
+impl MarketDelegatedAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 32 + 8 + 8 + 8 + 8;
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        self.expire_time != 0 && now_ts >= self.expire_time
+    }
+
+    // whether `source` still holds and delegates enough to fill the listing,
+    // a closed or frozen account never does
+    pub fn is_covered(&self, program_id: &Pubkey, source: &AccountInfo) -> Result<bool> {
+        if source.key() != self.source {
+            return err!(Wen3ExError::IncorrectSourceTokenAccount);
+        }
+        if source.owner != &token::ID || source.data_is_empty() {
+            return Ok(false);
+        }
+        let source = match TokenAccount::try_deserialize(&mut &source.data.borrow()[..]) {
+            Ok(source) => source,
+            Err(_) => return Ok(false),
+        };
+        let (delegate, _bump) = Pubkey::find_program_address(&[DELEGATE_SEED], program_id);
+        Ok(source.mint == self.token
+            && source.owner == self.creator
+            && !source.is_frozen()
+            && source.delegate == COption::Some(delegate)
+            && source.delegated_amount >= self.token_amount
+            && source.amount >= self.token_amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketDelegatedCreate<'info> {
+    #[account(zero)]
+    pub market_account: Box<Account<'info, MarketDelegatedAccount>>,
+    #[account(
+        constraint = source.mint == mint.key(),
+        constraint = source.owner == creator.key() @ Wen3ExError::IncorrectSourceTokenAccount,
+    )]
+    pub source: Box<Account<'info, TokenAccount>>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_DELEGATED) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarketDelegatedCancel<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketDelegatedAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketDelegatedAccount>>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+// anyone closes a listing its source no longer covers, or an expired one,
+// the rent goes back to the creator
+#[derive(Accounts)]
+pub struct MarketDelegatedCloseStale<'info> {
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketDelegatedAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketDelegatedAccount>>,
+    /// CHECK: the creator's token account, may be closed, checked by the handler
+    pub source: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarketDelegatedExchange<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = taker_token_account.mint == market_account.token,
+    )]
+    pub taker_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketDelegatedAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketDelegatedAccount>>,
+    #[account(mut)]
+    /// CHECK: the creator's token account, checked by the handler
+    pub source: AccountInfo<'info>,
+    #[account(seeds = [DELEGATE_SEED], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub delegate: AccountInfo<'info>,
+    // the delegate signs for every delegated listing, never hand that to another program
+    pub token_program: Program<'info, Token>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_DELEGATED) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> MarketDelegatedExchange<'info> {
+    pub fn transfer_from_source_to_taker_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.source.clone(),
+            to: self.taker_token_account.to_account_info().clone(),
+            authority: self.delegate.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
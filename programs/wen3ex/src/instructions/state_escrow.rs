@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, Mint, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{CONFIG_SEED, ESCROW_SEED, MARKET_ESCROW, VAULT_AUTHORITY_SEED, VAULT_ESCROW_SEED},
+    state_config::ConfigAccount,
+};
+
+// the buyer's leg of a trade settled off chain. sol stays in this account,
+// tokens in the escrow vault. the buyer or the arbiter releases to the
+// seller, the seller or the arbiter refunds, and past the deadline the
+// buyer may take it back. once the seller marked it delivered, only the
+// arbiter refunds
+#[account]
+pub struct EscrowAccount {
+    pub version: u32,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub arbiter: Pubkey,
+    pub mint: Pubkey, // Pubkey::default() for sol
+    pub amount: u64,
+    pub delivered: bool, // set by the seller, for the arbiter to go by
+    pub deadline: i64,
+    pub create_time: i64,
+    pub id: u64,
+    pub bump: u8,
+}
+
+impl EscrowAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 1;
+
+    pub fn can_release(&self, signer: &Pubkey) -> bool {
+        *signer == self.buyer || *signer == self.arbiter
+    }
+
+    pub fn can_refund(&self, signer: &Pubkey, now_ts: i64) -> bool {
+        if self.delivered {
+            return *signer == self.arbiter;
+        }
+        *signer == self.seller
+            || *signer == self.arbiter
+            || (*signer == self.buyer && now_ts >= self.deadline)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, amount: u64, deadline: i64)]
+pub struct EscrowCreateSol<'info> {
+    #[account(
+        init,
+        seeds = [ESCROW_SEED, buyer.key().as_ref(), &id.to_le_bytes()],
+        bump,
+        payer = buyer,
+        space = EscrowAccount::LEN,
+        constraint = amount > 0 @ Wen3ExError::InvalidAmount,
+    )]
+    pub escrow: Box<Account<'info, EscrowAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub buyer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub seller: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub arbiter: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ESCROW) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, amount: u64, deadline: i64)]
+pub struct EscrowCreateToken<'info> {
+    #[account(
+        init,
+        seeds = [ESCROW_SEED, buyer.key().as_ref(), &id.to_le_bytes()],
+        bump,
+        payer = buyer,
+        space = EscrowAccount::LEN,
+        constraint = amount > 0 @ Wen3ExError::InvalidAmount,
+    )]
+    pub escrow: Box<Account<'info, EscrowAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_ESCROW_SEED, escrow.key().as_ref()],
+        bump,
+        payer = buyer,
+        token::mint = mint,
+        token::authority = vault_authority
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, escrow.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == mint.key(),
+        constraint = buyer_token_account.owner == buyer.key(),
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub buyer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub seller: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub arbiter: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ESCROW) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> EscrowCreateToken<'info> {
+    pub fn transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.buyer_token_account.to_account_info().clone(),
+            to: self.vault_token_account.to_account_info().clone(),
+            authority: self.buyer.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct EscrowMarkDelivered<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub seller: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.buyer.as_ref(), &escrow.id.to_le_bytes()],
+        bump = escrow.bump,
+        constraint = escrow.version == EscrowAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = escrow.seller == seller.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub escrow: Box<Account<'info, EscrowAccount>>,
+}
+
+// release pays the seller, refund the buyer, the rent always goes back to the buyer
+#[derive(Accounts)]
+pub struct EscrowSettleSol<'info> {
+    #[account(signer)]
+    /// CHECK: the buyer, the seller or the arbiter, checked by the handler
+    pub authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.buyer.as_ref(), &escrow.id.to_le_bytes()],
+        bump = escrow.bump,
+        constraint = escrow.version == EscrowAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = escrow.buyer == buyer.key(),
+        constraint = escrow.seller == seller.key(),
+        constraint = escrow.mint == Pubkey::default() @ Wen3ExError::EscrowMintMismatch,
+        close = buyer
+    )]
+    pub escrow: Box<Account<'info, EscrowAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowSettleToken<'info> {
+    #[account(signer)]
+    /// CHECK: the buyer, the seller or the arbiter, checked by the handler
+    pub authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    // the seller's for a release, the buyer's for a refund
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == escrow.mint @ Wen3ExError::EscrowMintMismatch,
+    )]
+    pub recipient_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.buyer.as_ref(), &escrow.id.to_le_bytes()],
+        bump = escrow.bump,
+        constraint = escrow.version == EscrowAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = escrow.buyer == buyer.key(),
+        close = buyer
+    )]
+    pub escrow: Box<Account<'info, EscrowAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ESCROW_SEED, escrow.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, escrow.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> EscrowSettleToken<'info> {
+    pub fn transfer_to_recipient_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.recipient_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.buyer.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
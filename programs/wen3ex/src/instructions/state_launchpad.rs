@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, Mint, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, LAUNCHPAD_SEED, MARKET_LAUNCHPAD, MINT_ENTRY_SEED, PURCHASE_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_LAUNCHPAD_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+// fixed price primary sale, the issuer escrows the supply up front and the
+// proceeds stay in this account until the sale is over
+#[account]
+pub struct LaunchpadAccount {
+    pub version: u32,
+    pub issuer: Pubkey,
+    pub mint: Pubkey,
+    pub unit_size: u64,  // token atoms per unit
+    pub price: u64,      // lamports per unit
+    pub supply: u64,     // units escrowed
+    pub total_cap: u64,  // units for sale, at most the supply
+    pub wallet_cap: u64, // units per buyer, 0 no cap
+    pub sold: u64,       // units
+    pub proceeds: u64,   // lamports, less the fees
+    pub start_time: i64,
+    pub end_time: i64,
+    pub bump: u8,
+}
+
+impl LaunchpadAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn token_amount(&self, units: u64) -> Result<u64> {
+        units
+            .checked_mul(self.unit_size)
+            .ok_or_else(|| error!(Wen3ExError::NumericalOverflowError))
+    }
+
+    pub fn is_over(&self, now_ts: i64) -> bool {
+        now_ts >= self.end_time || self.sold == self.total_cap
+    }
+}
+
+#[account] // what one wallet bought from one launchpad
+pub struct PurchaseAccount {
+    pub version: u32,
+    pub launchpad: Pubkey,
+    pub buyer: Pubkey,
+    pub quantity: u64, // units
+    pub paid: u64,     // lamports, fees included
+    pub bump: u8,
+}
+
+impl PurchaseAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(
+    unit_size: u64,
+    price: u64,
+    supply: u64,
+    total_cap: u64,
+    wallet_cap: u64,
+    start_time: i64,
+    end_time: i64,
+)]
+pub struct LaunchpadCreate<'info> {
+    #[account(
+        init,
+        seeds = [LAUNCHPAD_SEED, mint.key().as_ref(), issuer.key().as_ref()],
+        bump,
+        payer = issuer,
+        space = LaunchpadAccount::LEN,
+        constraint = unit_size > 0 && price > 0 @ Wen3ExError::InvalidAmount,
+        constraint = total_cap > 0 && total_cap <= supply @ Wen3ExError::InvalidAmount,
+        constraint = start_time < end_time @ Wen3ExError::InvalidSaleWindow,
+    )]
+    pub launchpad: Box<Account<'info, LaunchpadAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_LAUNCHPAD_SEED, launchpad.key().as_ref()],
+        bump,
+        payer = issuer,
+        token::mint = mint,
+        token::authority = vault_authority
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, launchpad.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = issuer_token_account.mint == mint.key(),
+        constraint = issuer_token_account.owner == issuer.key(),
+    )]
+    pub issuer_token_account: Box<Account<'info, TokenAccount>>,
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub issuer: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_LAUNCHPAD) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> LaunchpadCreate<'info> {
+    pub fn transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.issuer_token_account.to_account_info().clone(),
+            to: self.vault_token_account.to_account_info().clone(),
+            authority: self.issuer.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// remaining_accounts: optional [referrer, referrer_account]
+#[derive(Accounts)]
+pub struct LaunchpadBuy<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub buyer: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.mint == launchpad.mint,
+        constraint = buyer_token_account.owner == buyer.key(),
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        seeds = [PURCHASE_SEED, launchpad.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = PurchaseAccount::LEN,
+    )]
+    pub purchase: Box<Account<'info, PurchaseAccount>>,
+    #[account(
+        mut,
+        seeds = [LAUNCHPAD_SEED, launchpad.mint.as_ref(), launchpad.issuer.as_ref()],
+        bump = launchpad.bump,
+        constraint = launchpad.version == LaunchpadAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+    )]
+    pub launchpad: Box<Account<'info, LaunchpadAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_LAUNCHPAD_SEED, launchpad.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, launchpad.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_LAUNCHPAD) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> LaunchpadBuy<'info> {
+    pub fn transfer_to_buyer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.buyer_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// after the sale the issuer takes the unsold tokens and, by closing the
+// launchpad account, the proceeds
+#[derive(Accounts)]
+pub struct LaunchpadWithdraw<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub issuer: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = issuer_token_account.mint == launchpad.mint,
+        constraint = issuer_token_account.owner == issuer.key(),
+    )]
+    pub issuer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [LAUNCHPAD_SEED, launchpad.mint.as_ref(), issuer.key().as_ref()],
+        bump = launchpad.bump,
+        constraint = launchpad.version == LaunchpadAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        close = issuer
+    )]
+    pub launchpad: Box<Account<'info, LaunchpadAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_LAUNCHPAD_SEED, launchpad.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, launchpad.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> LaunchpadWithdraw<'info> {
+    pub fn transfer_to_issuer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.issuer_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.issuer.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[event]
+pub struct LaunchpadPurchased {
+    pub launchpad: Pubkey,
+    pub buyer: Pubkey,
+    pub quantity: u64, // units
+    pub cost: u64,     // lamports, fees included
+    pub sold: u64,     // units sold so far
+}
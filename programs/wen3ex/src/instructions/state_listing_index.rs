@@ -0,0 +1,155 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+    Discriminator,
+};
+
+use super::constants::{CREATOR_INDEX_SEED, LISTING_INDEX_PAGE, MINT_INDEX_SEED};
+
+// open listings of one mint or one creator: token 2 sol, delegated, sol 2
+// token, token 2 token, nft and pnft 2 sol, swap and cnft 2 sol listings. the
+// mint is the vaulted one, the wanted one for sol 2 token, the first offered
+// nft for a swap and the merkle tree for a cnft.
+// the index PDAs are passed to every create, cancel and exchange and are
+// kept up to date once created, it's fine for them not to exist.
+// an index has no cap and is edited in place, never deserialized on chain.
+// the create that finds it full grows it by LISTING_INDEX_PAGE listings and
+// its creator pays the extra rent, whoever opened the index. an index never
+// shrinks and that rent is not refunded.
+// every fill of a mint write-locks its index, so fills of one indexed mint
+// (or creator) don't run in parallel
+#[account]
+pub struct ListingIndexAccount {
+    pub version: u32,
+    pub key: Pubkey, // the mint or the creator
+    pub by_creator: bool,
+    pub bump: u8,
+    pub listings: Vec<Pubkey>,
+}
+
+// the listings' length, then the listings, after the fixed fields
+const LENGTH_OFFSET: usize = 8 + 4 + 32 + 1 + 1;
+const LISTINGS_OFFSET: usize = LENGTH_OFFSET + 4;
+
+impl ListingIndexAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = LISTINGS_OFFSET + 32 * LISTING_INDEX_PAGE;
+
+    pub fn seed(by_creator: bool) -> &'static [u8] {
+        if by_creator {
+            CREATOR_INDEX_SEED
+        } else {
+            MINT_INDEX_SEED
+        }
+    }
+
+    pub fn address(program_id: &Pubkey, by_creator: bool, key: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[Self::seed(by_creator), key.as_ref()], program_id).0
+    }
+
+    // adds a new listing to the mint and creator indexes that exist, the
+    // payer tops up the rent of a full index growing by a page
+    pub fn insert<'info>(
+        program_id: &Pubkey,
+        mint_index: &AccountInfo<'info>,
+        creator_index: &AccountInfo<'info>,
+        listing: Pubkey,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        for index in [mint_index, creator_index] {
+            if !Self::exists(program_id, index)? {
+                continue;
+            }
+            let length = Self::length(&index.try_borrow_data()?);
+            if LISTINGS_OFFSET + 32 * (length + 1) > index.data_len() {
+                let new_len = index.data_len() + 32 * LISTING_INDEX_PAGE;
+                let lamports_required = Rent::get()?.minimum_balance(new_len);
+                let lamports_missing = lamports_required.saturating_sub(index.lamports());
+                if lamports_missing > 0 {
+                    invoke(
+                        &system_instruction::transfer(payer.key, index.key, lamports_missing),
+                        &[payer.clone(), index.clone(), system_program.clone()],
+                    )?;
+                }
+                index.realloc(new_len, true)?;
+            }
+            let mut data = index.try_borrow_mut_data()?;
+            let at = LISTINGS_OFFSET + 32 * length;
+            data[at..at + 32].copy_from_slice(listing.as_ref());
+            Self::set_length(&mut data, length + 1);
+        }
+        Ok(())
+    }
+
+    // drops a cancelled, taken or cranked listing from the indexes, the last
+    // listing takes its place
+    pub fn remove<'info>(
+        program_id: &Pubkey,
+        mint_index: &AccountInfo<'info>,
+        creator_index: &AccountInfo<'info>,
+        listing: Pubkey,
+    ) -> Result<()> {
+        for index in [mint_index, creator_index] {
+            if !Self::exists(program_id, index)? {
+                continue;
+            }
+            let mut data = index.try_borrow_mut_data()?;
+            let length = Self::length(&data);
+            let position = data[LISTINGS_OFFSET..LISTINGS_OFFSET + 32 * length]
+                .chunks_exact(32)
+                .position(|key| key == listing.as_ref());
+            if let Some(position) = position {
+                let at = LISTINGS_OFFSET + 32 * position;
+                let last = LISTINGS_OFFSET + 32 * (length - 1);
+                data.copy_within(last..last + 32, at);
+                data[last..last + 32].fill(0);
+                Self::set_length(&mut data, length - 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn exists(program_id: &Pubkey, index: &AccountInfo) -> Result<bool> {
+        if index.owner != program_id || index.data_is_empty() {
+            return Ok(false);
+        }
+        let data = index.try_borrow_data()?;
+        if data.len() < LISTINGS_OFFSET || data[..8] != Self::discriminator() {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        Ok(true)
+    }
+
+    fn length(data: &[u8]) -> usize {
+        let mut length = [0u8; 4];
+        length.copy_from_slice(&data[LENGTH_OFFSET..LISTINGS_OFFSET]);
+        u32::from_le_bytes(length) as usize
+    }
+
+    fn set_length(data: &mut [u8], length: usize) {
+        data[LENGTH_OFFSET..LISTINGS_OFFSET].copy_from_slice(&(length as u32).to_le_bytes());
+    }
+}
+
+// the index of a mint (key is the mint) or of a creator (key is the wallet),
+// tracking the listings created from then on
+#[derive(Accounts)]
+#[instruction(by_creator: bool)]
+pub struct ListingIndexCreate<'info> {
+    #[account(
+        init,
+        seeds = [ListingIndexAccount::seed(by_creator), key.key().as_ref()],
+        bump,
+        payer = payer,
+        space = ListingIndexAccount::LEN,
+    )]
+    pub index: Box<Account<'info, ListingIndexAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub key: AccountInfo<'info>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub payer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+// Market account layouts only ever grow by appending fields, and every new
+// field treats zero as "not set". An account written by an older version is
+// therefore a prefix of the current layout: `migrate_market` reallocs it to
+// the current `LEN`, zero-fills the tail and stamps the current `VERSION`.
+#[derive(Accounts)]
+pub struct MarketMigrate<'info> {
+    /// CHECK: owner, discriminator and creator are validated in the handler,
+    /// the account can't be typed because older layouts don't deserialize
+    #[account(mut)]
+    pub market_account: AccountInfo<'info>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
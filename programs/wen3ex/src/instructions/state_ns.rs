@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{CloseAccount, Mint, SetAuthority, TokenAccount, Transfer};
 
-use super::constants::VAULT_NFT_2_SOL_SEED;
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_NS, MAX_FEE_BPS, MINT_ENTRY_SEED, MINT_INDEX_SEED,
+        NFT_VAULT_TOKEN, VAULT_NFT_2_SOL_SEED,
+    },
+    state_config::ConfigAccount,
+};
 
 // sell nft with sol back
 #[account]
@@ -12,6 +20,18 @@ pub struct MarketNftToSolAccount {
     pub nft_amount: u64,   //
     pub sol_amount: u64,   // 期待 sol 的数量
     pub create_time: i64,
+    pub start_time: i64,   // not fillable before, 0 open right away
+    pub referral_bps: u32, // paid by the creator to a fill's referrer, on top of the fee share
+    pub vault_kind: u8,    // NFT_VAULT_TOKEN or NFT_VAULT_PROGRAMMABLE, only its own paths take it
+}
+
+impl MarketNftToSolAccount {
+    pub const VERSION: u32 = 4;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 4 + 1;
+
+    pub fn is_open(&self, now_ts: i64) -> bool {
+        now_ts >= self.start_time
+    }
 }
 
 #[derive(Accounts)]
@@ -49,6 +69,21 @@ pub struct MarketNftToSolCreate<'info> {
     pub rent: Sysvar<'info, Rent>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_NS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketNftToSolCreate<'info> {
     pub fn transfer_from_creator_to_vault_context(
@@ -73,6 +108,22 @@ impl<'info> MarketNftToSolCreate<'info> {
     }
 }
 
+// the bps of the price the creator pays a fill's referrer, 0 for none
+#[derive(Accounts)]
+#[instruction(referral_bps: u32)]
+pub struct MarketNftToSolSetReferral<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = referral_bps <= MAX_FEE_BPS @ Wen3ExError::InvalidFee,
+    )]
+    pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
+}
+
 #[derive(Accounts)]
 pub struct MarketNftToSolCancel<'info> {
     /// CHECK: This is not dangerous because we don't read or write from this account
@@ -86,13 +137,16 @@ pub struct MarketNftToSolCancel<'info> {
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub vault_authority: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
+        constraint = market_account.vault_kind == NFT_VAULT_TOKEN @ Wen3ExError::NftVaultMismatch,
         close = creator
     )]
     pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
@@ -100,6 +154,12 @@ pub struct MarketNftToSolCancel<'info> {
     pub mint: Account<'info, Mint>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.nft_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketNftToSolCancel<'info> {
     pub fn transfer_from_vault_to_creator_context(
@@ -138,13 +198,16 @@ pub struct MarketNftToSolExchange<'info> {
     pub creator: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
+        constraint = market_account.vault_kind == NFT_VAULT_TOKEN @ Wen3ExError::NftVaultMismatch,
         close = creator
     )]
     pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -155,6 +218,21 @@ pub struct MarketNftToSolExchange<'info> {
     pub token_program: AccountInfo<'info>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub system_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_NS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.nft_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 
 impl<'info> MarketNftToSolExchange<'info> {
@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, Mint, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_OFFER, MINT_INDEX_SEED, NFT_VAULT_TOKEN,
+        OFFER_SEED, VAULT_AUTHORITY_SEED, VAULT_NFT_2_SOL_SEED,
+    },
+    state_config::ConfigAccount,
+    state_ns::MarketNftToSolAccount,
+};
+
+// a bid on an nft, the offered sol is kept in the offer account itself
+#[account]
+pub struct OfferAccount {
+    pub version: u32,
+    pub bidder: Pubkey,
+    pub nft_token: Pubkey, // the mint
+    pub nft_amount: u64,
+    pub sol_amount: u64,
+    pub listing: Pubkey, // MarketNftToSolAccount, Pubkey::default() for an unlisted nft
+    pub create_time: i64,
+    pub bump: u8,
+}
+
+impl OfferAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 32 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(nft_amount: u64, sol_amount: u64)]
+pub struct OfferCreate<'info> {
+    #[account(
+        init,
+        seeds = [OFFER_SEED, mint.key().as_ref(), bidder.key().as_ref()],
+        bump,
+        payer = bidder,
+        space = OfferAccount::LEN,
+        constraint = nft_amount > 0 && sol_amount > 0 @ Wen3ExError::InvalidAmount,
+    )]
+    pub offer: Box<Account<'info, OfferAccount>>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub bidder: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_OFFER) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OfferWithdraw<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub bidder: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, offer.nft_token.as_ref(), bidder.key().as_ref()],
+        bump = offer.bump,
+        close = bidder
+    )]
+    pub offer: Box<Account<'info, OfferAccount>>,
+}
+
+// the seller accepts with the nft sitting in a nft 2 sol listing
+#[derive(Accounts)]
+pub struct OfferAcceptListing<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut, constraint = bidder.key() == offer.bidder)]
+    pub bidder: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = bidder_token_account.mint == offer.nft_token,
+        constraint = bidder_token_account.owner == offer.bidder,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, offer.nft_token.as_ref(), offer.bidder.as_ref()],
+        bump = offer.bump,
+        constraint = offer.listing == Pubkey::default() || offer.listing == market_account.key() @ Wen3ExError::OfferListingMismatch,
+        close = bidder
+    )]
+    pub offer: Box<Account<'info, OfferAccount>>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.nft_token == offer.nft_token,
+        constraint = market_account.nft_amount == offer.nft_amount @ Wen3ExError::InvalidAmount,
+        constraint = market_account.vault_kind == NFT_VAULT_TOKEN @ Wen3ExError::NftVaultMismatch,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_NFT_2_SOL_SEED, market_account.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_OFFER) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.nft_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> OfferAcceptListing<'info> {
+    pub fn transfer_from_vault_to_bidder_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.bidder_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_vault_to_creator_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.creator.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// the seller accepts with the nft in their own wallet
+#[derive(Accounts)]
+pub struct OfferAcceptWallet<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub seller: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == offer.nft_token,
+        constraint = seller_token_account.owner == seller.key(),
+    )]
+    pub seller_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut, constraint = bidder.key() == offer.bidder)]
+    pub bidder: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = bidder_token_account.mint == offer.nft_token,
+        constraint = bidder_token_account.owner == offer.bidder,
+    )]
+    pub bidder_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, offer.nft_token.as_ref(), offer.bidder.as_ref()],
+        bump = offer.bump,
+        constraint = offer.listing == Pubkey::default() @ Wen3ExError::OfferListingMismatch,
+        close = bidder
+    )]
+    pub offer: Box<Account<'info, OfferAccount>>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_OFFER) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+}
+
+impl<'info> OfferAcceptWallet<'info> {
+    pub fn transfer_from_seller_to_bidder_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.seller_token_account.to_account_info().clone(),
+            to: self.bidder_token_account.to_account_info().clone(),
+            authority: self.seller.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
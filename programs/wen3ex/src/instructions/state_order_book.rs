@@ -0,0 +1,357 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, MARKET_ORDER_BOOK, ORDER_BOOK_BALANCE_SEED, ORDER_BOOK_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_ORDER_BOOK_BASE_SEED, VAULT_ORDER_BOOK_QUOTE_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Order {
+    pub order_id: u64,
+    pub owner: Pubkey,
+    pub balance: Pubkey, // the owner's OrderBookBalanceAccount, credited with fills
+    pub price: u64,      // quote atoms per lot
+    pub quantity: u64,   // lots left
+}
+
+impl Order {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+#[account] // central limit order book for one base / quote pair
+pub struct OrderBookAccount {
+    pub version: u32,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_lot_size: u64, // base atoms per lot
+    pub next_order_id: u64,
+    pub bump: u8,
+    pub min_lots: u64,    // smallest order placed, keeps dust from filling the book
+    pub bids: Vec<Order>, // best (highest) price first, then oldest first
+    pub asks: Vec<Order>, // best (lowest) price first, then oldest first
+}
+
+impl OrderBookAccount {
+    pub const VERSION: u32 = 2;
+    pub const MAX_ORDERS: usize = 32; // per side, the worst priced is evicted by a better one
+    pub const LEN: usize =
+        8 + 4 + 32 + 32 + 8 + 8 + 1 + 8 + (4 + Order::LEN * Self::MAX_ORDERS) * 2;
+
+    pub fn base_amount(&self, lots: u64) -> Result<u64> {
+        lots.checked_mul(self.base_lot_size)
+            .ok_or_else(|| error!(Wen3ExError::NumericalOverflowError))
+    }
+}
+
+// what an owner's filled and evicted orders left in the vaults. makers are
+// credited here rather than paid out during the match, so no maker account
+// can stall a taker, see order_book_settle
+#[account]
+pub struct OrderBookBalanceAccount {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub base_free: u64,  // base atoms
+    pub quote_free: u64, // quote atoms
+    pub bump: u8,
+}
+
+impl OrderBookBalanceAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+
+    pub fn credit(&mut self, base_amount: u64, quote_amount: u64) -> Result<()> {
+        self.base_free = self
+            .base_free
+            .checked_add(base_amount)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        self.quote_free = self
+            .quote_free
+            .checked_add(quote_amount)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(base_lot_size: u64, min_lots: u64)]
+pub struct OrderBookCreate<'info> {
+    #[account(
+        init,
+        seeds = [ORDER_BOOK_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = OrderBookAccount::LEN,
+        constraint = base_lot_size > 0 @ Wen3ExError::InvalidAmount,
+        constraint = min_lots > 0 @ Wen3ExError::InvalidAmount,
+        constraint = base_mint.key() != quote_mint.key() @ Wen3ExError::InvalidAmount,
+    )]
+    pub order_book: Box<Account<'info, OrderBookAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_ORDER_BOOK_BASE_SEED, order_book.key().as_ref()],
+        bump,
+        payer = payer,
+        token::mint = base_mint,
+        token::authority = vault_authority
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_ORDER_BOOK_QUOTE_SEED, order_book.key().as_ref()],
+        bump,
+        payer = payer,
+        token::mint = quote_mint,
+        token::authority = vault_authority
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, order_book.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+
+    pub base_mint: Box<Account<'info, Mint>>,
+    pub quote_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ORDER_BOOK) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub payer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+// remaining_accounts: the balance account of every resting order the new
+// order is expected to match, in matching order, then the balance account of
+// the worst priced order when the new order evicts it from a full side
+#[derive(Accounts)]
+pub struct OrderBookPlace<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = owner_base_account.mint == order_book.base_mint,
+        constraint = owner_base_account.owner == owner.key(),
+    )]
+    pub owner_base_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = owner_quote_account.mint == order_book.quote_mint,
+        constraint = owner_quote_account.owner == owner.key(),
+    )]
+    pub owner_quote_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [ORDER_BOOK_SEED, order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+        constraint = order_book.version == OrderBookAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+    )]
+    pub order_book: Box<Account<'info, OrderBookAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_BASE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !base_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_QUOTE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !quote_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, order_book.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [ORDER_BOOK_BALANCE_SEED, order_book.key().as_ref(), owner.key().as_ref()],
+        bump,
+        payer = owner,
+        space = OrderBookBalanceAccount::LEN,
+    )]
+    pub owner_balance: Box<Account<'info, OrderBookBalanceAccount>>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ORDER_BOOK) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> OrderBookPlace<'info> {
+    pub fn transfer_context(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority,
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct OrderBookCancel<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = owner_base_account.mint == order_book.base_mint,
+        constraint = owner_base_account.owner == owner.key(),
+    )]
+    pub owner_base_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = owner_quote_account.mint == order_book.quote_mint,
+        constraint = owner_quote_account.owner == owner.key(),
+    )]
+    pub owner_quote_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [ORDER_BOOK_SEED, order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+        constraint = order_book.version == OrderBookAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+    )]
+    pub order_book: Box<Account<'info, OrderBookAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_BASE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !base_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_QUOTE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !quote_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, order_book.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> OrderBookCancel<'info> {
+    pub fn transfer_context(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority,
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct OrderBookSettle<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = owner_base_account.mint == order_book.base_mint,
+        constraint = owner_base_account.owner == owner.key(),
+    )]
+    pub owner_base_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = owner_quote_account.mint == order_book.quote_mint,
+        constraint = owner_quote_account.owner == owner.key(),
+    )]
+    pub owner_quote_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ORDER_BOOK_SEED, order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBookAccount>>,
+    #[account(
+        mut,
+        seeds = [ORDER_BOOK_BALANCE_SEED, order_book.key().as_ref(), owner.key().as_ref()],
+        bump = owner_balance.bump,
+    )]
+    pub owner_balance: Box<Account<'info, OrderBookBalanceAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_BASE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !base_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_ORDER_BOOK_QUOTE_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = !quote_vault.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, order_book.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> OrderBookSettle<'info> {
+    pub fn transfer_context(
+        &self,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from,
+            to,
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[event]
+pub struct OrderPlaced {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub side: u8,
+    pub price: u64,
+    pub quantity: u64,
+    pub filled: u64,   // lots matched against resting orders
+    pub order_id: u64, // 0 when nothing was left to rest
+}
+
+#[event]
+pub struct OrderEvicted {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64, // its escrow was credited to the owner's balance
+}
@@ -0,0 +1,318 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address},
+    token::{self, CloseAccount, Mint, TokenAccount},
+};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_NS, MINT_ENTRY_SEED, MINT_INDEX_SEED,
+        NFT_VAULT_PROGRAMMABLE, VAULT_AUTHORITY_SEED,
+    },
+    state_config::ConfigAccount,
+    state_ns::MarketNftToSolAccount,
+    token_metadata::{auth_rules_program, is_programmable, token_metadata_program, PnftTransfer},
+};
+
+// sell nft with sol back, moved by token metadata so programmable nfts can be
+// listed too. the listing is a MarketNftToSolAccount, the vault the associated
+// token account of the vault authority, created by token metadata on transfer
+
+#[derive(Accounts)]
+#[instruction(nft_amount: u64)]
+pub struct MarketPnftToSolCreate<'info> {
+    #[account(zero)]
+    pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = creator_token_account.amount >= nft_amount,
+        constraint = creator_token_account.mint == mint.key(),
+        constraint = creator_token_account.owner == creator.key(),
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&vault_authority.key(), &mint.key()) @ Wen3ExError::IncorrectVaultTokenAccount,
+    )]
+    /// CHECK: created by token metadata on transfer
+    pub vault_token_account: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_NS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub pnft: PnftAccounts<'info>,
+}
+
+impl<'info> MarketPnftToSolCreate<'info> {
+    pub fn transfer_from_creator_to_vault(&self) -> Result<PnftTransfer<'_, 'info>> {
+        self.pnft.transfer(
+            &self.mint,
+            self.creator_token_account.as_ref().as_ref(),
+            &self.creator,
+            &self.vault_token_account,
+            &self.vault_authority,
+            &self.creator,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketPnftToSolCancel<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&creator.key(), &mint.key()) @ Wen3ExError::IncorrectCreatorTokenAccount,
+    )]
+    /// CHECK: created by token metadata on transfer when missing
+    pub creator_token_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.vault_kind == NFT_VAULT_PROGRAMMABLE @ Wen3ExError::NftVaultMismatch,
+        constraint = market_account.nft_token == mint.key(),
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&vault_authority.key(), &mint.key()) @ Wen3ExError::IncorrectVaultTokenAccount,
+    )]
+    /// CHECK: the associated token account of the vault authority
+    pub vault_token_account: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.nft_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub pnft: PnftAccounts<'info>,
+}
+
+impl<'info> MarketPnftToSolCancel<'info> {
+    pub fn transfer_from_vault_to_creator(&self) -> Result<PnftTransfer<'_, 'info>> {
+        self.pnft.transfer(
+            &self.mint,
+            &self.vault_token_account,
+            &self.vault_authority,
+            &self.creator_token_account,
+            &self.creator,
+            &self.creator,
+        )
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        self.pnft.close_vault_context(
+            &self.vault_token_account,
+            &self.creator,
+            &self.vault_authority,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketPnftToSolExchange<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&taker.key(), &mint.key()) @ Wen3ExError::IncorrectRecipientTokenAccount,
+    )]
+    /// CHECK: created by token metadata on transfer when missing
+    pub taker_token_account: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketNftToSolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.vault_kind == NFT_VAULT_PROGRAMMABLE @ Wen3ExError::NftVaultMismatch,
+        constraint = market_account.nft_token == mint.key(),
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketNftToSolAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address(&vault_authority.key(), &mint.key()) @ Wen3ExError::IncorrectVaultTokenAccount,
+    )]
+    /// CHECK: the associated token account of the vault authority
+    pub vault_token_account: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_NS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.nft_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+    pub pnft: PnftAccounts<'info>,
+}
+
+impl<'info> MarketPnftToSolExchange<'info> {
+    pub fn transfer_from_vault_to_taker(&self) -> Result<PnftTransfer<'_, 'info>> {
+        self.pnft.transfer(
+            &self.mint,
+            &self.vault_token_account,
+            &self.vault_authority,
+            &self.taker_token_account,
+            &self.taker,
+            &self.taker,
+        )
+    }
+
+    pub fn close_vault_to_creator_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        self.pnft.close_vault_context(
+            &self.vault_token_account,
+            &self.creator,
+            &self.vault_authority,
+        )
+    }
+}
+
+// what token metadata's transfer needs besides the token accounts and their
+// owners. the token records and the rule set are only read for programmable
+// nfts, pass the token metadata program in their place otherwise
+#[derive(Accounts)]
+pub struct PnftAccounts<'info> {
+    #[account(mut)]
+    /// CHECK: checked against the mint by is_programmable, and by token metadata
+    pub metadata: AccountInfo<'info>,
+    /// CHECK: the master edition of the mint, checked by token metadata
+    pub edition: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: token record of the source token account, checked by token metadata
+    pub source_token_record: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: token record of the destination token account, checked by token metadata
+    pub destination_token_record: AccountInfo<'info>,
+    /// CHECK: the rule set of the nft, checked by token metadata
+    pub authorization_rules: AccountInfo<'info>,
+    #[account(
+        constraint = authorization_rules_program.key() == auth_rules_program::ID
+            || authorization_rules_program.key() == token_metadata_program::ID
+    )]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub authorization_rules_program: AccountInfo<'info>,
+    #[account(address = token_metadata_program::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_metadata_program: AccountInfo<'info>,
+    #[account(address = sysvar::instructions::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub sysvar_instructions: AccountInfo<'info>,
+    #[account(address = token::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(address = associated_token::ID)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub associated_token_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> PnftAccounts<'info> {
+    // plain nfts go without token records nor rule set
+    fn transfer<'a>(
+        &'a self,
+        mint: &'a Account<'info, Mint>,
+        token: &'a AccountInfo<'info>,
+        token_owner: &'a AccountInfo<'info>,
+        destination: &'a AccountInfo<'info>,
+        destination_owner: &'a AccountInfo<'info>,
+        payer: &'a AccountInfo<'info>,
+    ) -> Result<PnftTransfer<'a, 'info>> {
+        let programmable = is_programmable(&self.metadata, &mint.key())?;
+        let optional = |account: &'a AccountInfo<'info>| {
+            if programmable {
+                account
+            } else {
+                &self.token_metadata_program
+            }
+        };
+        Ok(PnftTransfer {
+            token_metadata_program: &self.token_metadata_program,
+            token,
+            token_owner,
+            destination,
+            destination_owner,
+            mint: mint.as_ref(),
+            metadata: &self.metadata,
+            edition: &self.edition,
+            owner_token_record: optional(&self.source_token_record),
+            destination_token_record: optional(&self.destination_token_record),
+            authority: token_owner,
+            payer,
+            system_program: &self.system_program,
+            sysvar_instructions: &self.sysvar_instructions,
+            token_program: &self.token_program,
+            associated_token_program: &self.associated_token_program,
+            authorization_rules_program: optional(&self.authorization_rules_program),
+            authorization_rules: optional(&self.authorization_rules),
+        })
+    }
+
+    fn close_vault_context(
+        &self,
+        vault_token_account: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+        vault_authority: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: vault_token_account.clone(),
+            destination: destination.clone(),
+            authority: vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// the vault once emptied: token metadata may have closed it already, and
+// leaves it frozen for programmable nfts. the program can't thaw an empty pnft
+// account, so it stays open and the creator's rent for it is not recovered
+pub fn vault_is_closable(vault_token_account: &AccountInfo) -> Result<bool> {
+    if vault_token_account.data_is_empty() {
+        return Ok(false);
+    }
+    let vault = TokenAccount::try_deserialize(&mut &vault_token_account.try_borrow_data()?[..])?;
+    Ok(vault.amount == 0 && !vault.is_frozen())
+}
@@ -0,0 +1,367 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Burn, Mint, MintTo, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        BPS_DENOMINATOR, CONFIG_SEED, MARKET_POOL, MAX_FEE_BPS, MINT_ENTRY_SEED, POOL_LP_DECIMALS,
+        POOL_LP_LOCK_SEED, POOL_LP_MINT_SEED, POOL_MINIMUM_LIQUIDITY, POOL_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_POOL_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+// constant product pool for a token pair, mint_a sorts before mint_b so there
+// is one pool per pair. the reserves are the vault balances, the swap fee
+// stays in the pool for the liquidity providers
+#[account]
+pub struct PoolAccount {
+    pub version: u32,
+    pub creator: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_bps: u32,
+    pub bump: u8,
+}
+
+impl PoolAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 32 + 32 + 4 + 1;
+
+    // lp tokens for a deposit, the first one sets the price and leaves
+    // POOL_MINIMUM_LIQUIDITY of its lp locked in the pool, as uniswap v2 does,
+    // so donations to the vaults can't inflate the lp price
+    pub fn lp_for_deposit(
+        amount_a: u64,
+        amount_b: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        lp_supply: u64,
+    ) -> Result<u64> {
+        let lp = if lp_supply == 0 {
+            // the lock keeps the supply up, only tokens sent to the vaults
+            // before the first deposit can be there
+            if reserve_a != 0 || reserve_b != 0 {
+                return err!(Wen3ExError::PoolNotEmpty);
+            }
+            sqrt(amount_a as u128 * amount_b as u128)
+                .checked_sub(POOL_MINIMUM_LIQUIDITY as u128)
+                .ok_or(Wen3ExError::InsufficientLiquidity)?
+        } else {
+            if reserve_a == 0 || reserve_b == 0 {
+                return err!(Wen3ExError::InsufficientLiquidity);
+            }
+            let lp_a = amount_a as u128 * lp_supply as u128 / reserve_a as u128;
+            let lp_b = amount_b as u128 * lp_supply as u128 / reserve_b as u128;
+            lp_a.min(lp_b)
+        };
+        u64::try_from(lp).map_err(|_| error!(Wen3ExError::NumericalOverflowError))
+    }
+
+    // share of `reserve` backing `lp` lp tokens, rounded up when depositing
+    // and down when withdrawing
+    pub fn share(lp: u64, reserve: u64, lp_supply: u64, round_up: bool) -> Result<u64> {
+        let numerator = lp as u128 * reserve as u128;
+        let mut share = numerator / lp_supply as u128;
+        if round_up && share * (lp_supply as u128) < numerator {
+            share += 1;
+        }
+        u64::try_from(share).map_err(|_| error!(Wen3ExError::NumericalOverflowError))
+    }
+
+    pub fn swap_out(&self, amount_in: u64, reserve_in: u64, reserve_out: u64) -> Result<u64> {
+        if reserve_in == 0 || reserve_out == 0 {
+            return err!(Wen3ExError::InsufficientLiquidity);
+        }
+        let amount_in = amount_in as u128 * (BPS_DENOMINATOR - self.fee_bps as u64) as u128
+            / BPS_DENOMINATOR as u128;
+        let amount_out = reserve_out as u128 * amount_in / (reserve_in as u128 + amount_in);
+        Ok(amount_out as u64)
+    }
+}
+
+fn sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x / 2 + 1;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+#[derive(Accounts)]
+#[instruction(fee_bps: u32)]
+pub struct PoolCreate<'info> {
+    #[account(
+        init,
+        seeds = [POOL_SEED, mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        payer = creator,
+        space = PoolAccount::LEN,
+        constraint = mint_a.key() < mint_b.key() @ Wen3ExError::InvalidPoolMints,
+        constraint = fee_bps <= MAX_FEE_BPS @ Wen3ExError::InvalidFee,
+    )]
+    pub pool: Box<Account<'info, PoolAccount>>,
+    #[account(
+        init,
+        seeds = [POOL_LP_MINT_SEED, pool.key().as_ref()],
+        bump,
+        payer = creator,
+        mint::decimals = POOL_LP_DECIMALS,
+        mint::authority = vault_authority
+    )]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    // holds the locked lp, the vault authority never moves it
+    #[account(
+        init,
+        seeds = [POOL_LP_LOCK_SEED, pool.key().as_ref()],
+        bump,
+        payer = creator,
+        token::mint = lp_mint,
+        token::authority = vault_authority
+    )]
+    pub lp_lock: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), mint_a.key().as_ref()],
+        bump,
+        payer = creator,
+        token::mint = mint_a,
+        token::authority = vault_authority
+    )]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), mint_b.key().as_ref()],
+        bump,
+        payer = creator,
+        token::mint = mint_b,
+        token::authority = vault_authority
+    )]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, pool.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    pub mint_a: Box<Account<'info, Mint>>,
+    pub mint_b: Box<Account<'info, Mint>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint_a.key().as_ref()], bump)]
+    /// CHECK: registry entry of mint_a, may not exist
+    pub mint_a_entry: AccountInfo<'info>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint_b.key().as_ref()], bump)]
+    /// CHECK: registry entry of mint_b, may not exist
+    pub mint_b_entry: AccountInfo<'info>,
+
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_POOL) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+// deposit and withdraw, withdrawing works while the pool market is paused
+#[derive(Accounts)]
+pub struct PoolLiquidity<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = owner_token_a.mint == pool.mint_a,
+        constraint = owner_token_a.owner == owner.key(),
+    )]
+    pub owner_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = owner_token_b.mint == pool.mint_b,
+        constraint = owner_token_b.owner == owner.key(),
+    )]
+    pub owner_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = owner_lp_token.mint == pool.lp_mint,
+        constraint = owner_lp_token.owner == owner.key(),
+    )]
+    pub owner_lp_token: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        constraint = pool.version == PoolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+    )]
+    pub pool: Box<Account<'info, PoolAccount>>,
+    #[account(mut, seeds = [POOL_LP_MINT_SEED, pool.key().as_ref()], bump)]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    #[account(mut, seeds = [POOL_LP_LOCK_SEED, pool.key().as_ref()], bump)]
+    pub lp_lock: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), pool.mint_a.as_ref()],
+        bump,
+        constraint = !vault_a.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), pool.mint_b.as_ref()],
+        bump,
+        constraint = !vault_b.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, pool.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> PoolLiquidity<'info> {
+    pub fn transfer_to_vault_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.owner.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn transfer_from_vault_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn mint_lp_context(
+        &self,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        let cpi_accounts = MintTo {
+            mint: self.lp_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn burn_lp_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        let cpi_accounts = Burn {
+            mint: self.lp_mint.to_account_info(),
+            from: self.owner_lp_token.to_account_info(),
+            authority: self.owner.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct PoolSwap<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub trader: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = trader_token_a.mint == pool.mint_a,
+        constraint = trader_token_a.owner == trader.key(),
+    )]
+    pub trader_token_a: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = trader_token_b.mint == pool.mint_b,
+        constraint = trader_token_b.owner == trader.key(),
+    )]
+    pub trader_token_b: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [POOL_SEED, pool.mint_a.as_ref(), pool.mint_b.as_ref()],
+        bump = pool.bump,
+        constraint = pool.version == PoolAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+    )]
+    pub pool: Box<Account<'info, PoolAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), pool.mint_a.as_ref()],
+        bump,
+        constraint = !vault_a.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_a: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_POOL_SEED, pool.key().as_ref(), pool.mint_b.as_ref()],
+        bump,
+        constraint = !vault_b.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_b: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, pool.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_POOL) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> PoolSwap<'info> {
+    pub fn transfer_to_vault_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.trader.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn transfer_from_vault_context(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[event]
+pub struct PoolSwapped {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub a_to_b: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::constants::PRICE_DECIMALS;
+
+// read only, simulate market_unit_price to quote a listing without fetching
+// its mints
+#[derive(Accounts)]
+pub struct MarketUnitPrice<'info> {
+    /// CHECK: owner, discriminator and version are validated in the handler,
+    /// it is one of several market account types
+    pub market_account: AccountInfo<'info>,
+}
+
+// what one whole unit of `base_mint` costs in `quote_mint`, Pubkey::default()
+// for sol, scaled by 10^decimals
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct UnitPrice {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub price: u128,
+    pub decimals: u8,
+}
+
+impl UnitPrice {
+    // quote_amount * 10^base_decimals * 10^PRICE_DECIMALS
+    //   / (base_amount * 10^quote_decimals)
+    pub fn new(
+        base_mint: Pubkey,
+        base_amount: u64,
+        base_decimals: Option<u8>,
+        quote_mint: Pubkey,
+        quote_amount: u64,
+        quote_decimals: Option<u8>,
+    ) -> Result<Self> {
+        let (base_decimals, quote_decimals) = match (base_decimals, quote_decimals) {
+            (Some(base), Some(quote)) => (base, quote),
+            _ => return err!(Wen3ExError::DecimalsNotRecorded),
+        };
+        let pow = |decimals: u8| 10u128.checked_pow(decimals as u32);
+        let price = pow(base_decimals)
+            .zip(pow(PRICE_DECIMALS))
+            .and_then(|(base_scale, price_scale)| {
+                (quote_amount as u128)
+                    .checked_mul(base_scale)?
+                    .checked_mul(price_scale)?
+                    .checked_div((base_amount as u128).checked_mul(pow(quote_decimals)?)?)
+            })
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        Ok(UnitPrice {
+            base_mint,
+            quote_mint,
+            price,
+            decimals: PRICE_DECIMALS,
+        })
+    }
+}
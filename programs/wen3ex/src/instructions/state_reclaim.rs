@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, Token, Transfer};
+
+use super::constants::VAULT_AUTHORITY_SEED;
+
+// closes a token deposit listing (tt, ts, ns) whose vault no longer holds the
+// recorded deposit: short, emptied, closed or frozen. what is left in the
+// vault, the vault rent and the listing rent go to the creator
+#[derive(Accounts)]
+pub struct MarketReclaim<'info> {
+    #[account(mut)]
+    /// CHECK: owner, discriminator and creator are validated in the handler,
+    /// it is one of several market account types
+    pub market_account: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: checked against the vault address of the market type in the handler
+    pub vault_token_account: AccountInfo<'info>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// the creator, or anyone once RECLAIM_GRACE_PERIOD has passed since creation
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    /// CHECK: checked against the listing in the handler
+    pub creator: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: receives what is left in the vault, checked in the handler when used
+    pub creator_token_account: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    #[account(mut)]
+    /// CHECK: listing index of the mint, checked in the handler, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: listing index of the creator, checked in the handler, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> MarketReclaim<'info> {
+    pub fn transfer_to_creator_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.clone(),
+            to: self.creator_token_account.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.clone(),
+            destination: self.creator.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
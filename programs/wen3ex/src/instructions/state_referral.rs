@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{BPS_DENOMINATOR, REFERRER_SEED},
+    state_config::ConfigAccount,
+    utils::send_lamports,
+};
+
+#[account] // lifetime stats of a frontend routing volume through the program
+pub struct ReferrerAccount {
+    pub version: u32,
+    pub referrer: Pubkey, // wallet receiving the referral fees
+    pub trades: u64,
+    pub volume: u64,   // lamports
+    pub earnings: u64, // lamports
+    pub bump: u8,
+}
+
+impl ReferrerAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct ReferrerRegister<'info> {
+    #[account(
+        init,
+        seeds = [REFERRER_SEED, referrer.key().as_ref()],
+        bump,
+        payer = referrer,
+        space = ReferrerAccount::LEN,
+    )]
+    pub referrer_account: Box<Account<'info, ReferrerAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub referrer: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+pub struct FeeSplit {
+    pub protocol: u64,
+    pub referrer: u64,
+}
+
+impl FeeSplit {
+    pub fn total(&self) -> u64 {
+        self.protocol + self.referrer
+    }
+}
+
+impl ConfigAccount {
+    pub fn fee_split(&self, amount: u64, referred: bool) -> Result<FeeSplit> {
+        self.maker_fee_split(amount, referred, 0)
+    }
+
+    // `maker_referral_bps` of the amount goes to the referrer on top of its
+    // share of the protocol fee, out of the maker's proceeds: the maker is
+    // paid `amount - total()`
+    pub fn maker_fee_split(
+        &self,
+        amount: u64,
+        referred: bool,
+        maker_referral_bps: u32,
+    ) -> Result<FeeSplit> {
+        let bps_of = |value: u128, bps: u32| {
+            value
+                .checked_mul(bps as u128)
+                .map(|scaled| scaled / BPS_DENOMINATOR as u128)
+                .ok_or(Wen3ExError::NumericalOverflowError)
+        };
+        let fee = bps_of(amount as u128, self.fee_bps)?;
+        let (fee_share, maker_part) = if referred {
+            (
+                bps_of(fee, self.referral_share_bps)?,
+                bps_of(amount as u128, maker_referral_bps)?,
+            )
+        } else {
+            (0, 0)
+        };
+        let protocol = fee
+            .checked_sub(fee_share)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        let referrer = fee_share
+            .checked_add(maker_part)
+            .ok_or(Wen3ExError::NumericalOverflowError)?;
+        if protocol + referrer > amount as u128 {
+            return err!(Wen3ExError::NumericalOverflowError);
+        }
+        Ok(FeeSplit {
+            protocol: protocol as u64,
+            referrer: referrer as u64,
+        })
+    }
+}
+
+// optional [referrer, referrer_account] pair leading an exchange's remaining_accounts
+pub struct Referrer<'info> {
+    pub wallet: AccountInfo<'info>,
+    pub account: Account<'info, ReferrerAccount>,
+}
+
+impl<'info> Referrer<'info> {
+    pub fn load(accounts: &[AccountInfo<'info>], taker: &Pubkey) -> Result<Option<Self>> {
+        let (wallet, account) = match accounts {
+            [wallet, account, ..] => (wallet, account),
+            _ => return Ok(None),
+        };
+        let account = Account::<ReferrerAccount>::try_from(account)?;
+        if account.referrer != wallet.key() {
+            return err!(Wen3ExError::IncorrectReferrerAccount);
+        }
+        if wallet.key == taker {
+            return err!(Wen3ExError::SelfReferral);
+        }
+        Ok(Some(Self {
+            wallet: wallet.clone(),
+            account,
+        }))
+    }
+
+    pub fn record(&mut self, program_id: &Pubkey, volume: u64, earnings: u64) -> Result<()> {
+        let account = &mut self.account;
+        account.trades = account.trades.saturating_add(1);
+        account.volume = account.volume.saturating_add(volume);
+        account.earnings = account.earnings.saturating_add(earnings);
+        account.exit(program_id)
+    }
+}
+
+// pays the fees out of `payer`, a system account signing the exchange when
+// `system_program` is given, an account owned by this program otherwise
+pub fn pay_fees<'info>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    referrer: Option<&mut Referrer<'info>>,
+    system_program: Option<&AccountInfo<'info>>,
+    fees: &FeeSplit,
+    volume: u64,
+) -> Result<()> {
+    send_lamports(payer, treasury, system_program, fees.protocol)?;
+    // only a shared fee counts towards the referrer's stats
+    match referrer {
+        Some(referrer) if fees.referrer > 0 => {
+            send_lamports(payer, &referrer.wallet, system_program, fees.referrer)?;
+            referrer.record(program_id, volume, fees.referrer)
+        }
+        _ => Ok(()),
+    }
+}
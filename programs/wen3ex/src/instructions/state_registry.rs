@@ -0,0 +1,113 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::Mint;
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{CONFIG_SEED, MINT_ENTRY_SEED, MINT_STATUS_ALLOWED, MINT_STATUS_DENIED},
+    state_config::ConfigAccount,
+    token_metadata::edition_address,
+};
+
+#[account] // one per registered mint
+pub struct MintEntryAccount {
+    pub version: u32,
+    pub mint: Pubkey,
+    pub status: u8, // MINT_STATUS_*
+    pub bump: u8,
+}
+
+impl MintEntryAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 1 + 1;
+
+    // the entry PDA is passed to every create, it's fine for it not to exist
+    pub fn status_of(program_id: &Pubkey, mint_entry: &AccountInfo) -> Result<Option<u8>> {
+        if mint_entry.owner != program_id || mint_entry.data_is_empty() {
+            return Ok(None);
+        }
+        let entry = Account::<MintEntryAccount>::try_from(mint_entry)?;
+        Ok(Some(entry.status))
+    }
+}
+
+impl ConfigAccount {
+    pub fn check_mint_listable(
+        &self,
+        market: u32,
+        program_id: &Pubkey,
+        mint_entry: &AccountInfo,
+    ) -> Result<()> {
+        if self.allowlist_markets & market == 0 && self.denylist_markets & market == 0 {
+            return Ok(());
+        }
+        let status = MintEntryAccount::status_of(program_id, mint_entry)?;
+        if self.allowlist_markets & market != 0 && status != Some(MINT_STATUS_ALLOWED) {
+            return err!(Wen3ExError::MintNotAllowed);
+        }
+        if self.denylist_markets & market != 0 && status == Some(MINT_STATUS_DENIED) {
+            return err!(Wen3ExError::MintNotAllowed);
+        }
+        Ok(())
+    }
+
+    // a freeze authority can freeze a vault and brick cancel and exchange.
+    // the master edition of an nft, only used by token metadata, is fine
+    pub fn check_freeze_authority(&self, market: u32, mint: &Account<Mint>) -> Result<()> {
+        if self.freeze_authority_markets & market == 0 {
+            return Ok(());
+        }
+        match mint.freeze_authority {
+            COption::Some(authority) if authority != edition_address(&mint.key()) => {
+                err!(Wen3ExError::MintHasFreezeAuthority)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(status: u8)]
+pub struct MintEntrySet<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [MINT_ENTRY_SEED, mint.key().as_ref()],
+        bump,
+        payer = admin,
+        space = MintEntryAccount::LEN,
+        constraint = status == MINT_STATUS_ALLOWED || status == MINT_STATUS_DENIED @ Wen3ExError::InvalidMintStatus,
+    )]
+    pub mint_entry: Box<Account<'info, MintEntryAccount>>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub admin: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintEntryRemove<'info> {
+    #[account(
+        mut,
+        seeds = [MINT_ENTRY_SEED, mint_entry.mint.as_ref()],
+        bump = mint_entry.bump,
+        close = admin
+    )]
+    pub mint_entry: Box<Account<'info, MintEntryAccount>>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.admin == admin.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub admin: AccountInfo<'info>,
+}
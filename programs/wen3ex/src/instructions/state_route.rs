@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::errors::Wen3ExError;
+
+use super::{constants::CONFIG_SEED, state_config::ConfigAccount};
+
+// remaining_accounts, ROUTE_ACCOUNTS_PER_LISTING per listing of the mint:
+// route_buy  [market_account (MarketTsAccount), vault_token_account, vault_authority, creator, sale_phase,
+//             mint_index, creator_index]
+// route_sell [market_account (MarketStAccount), vault_token_account, vault_authority, creator, creator_token_account,
+//             mint_index, creator_index]
+// then optionally the [referrer, referrer_account] pair
+pub const ROUTE_ACCOUNTS_PER_LISTING: usize = 7;
+
+#[derive(Accounts)]
+pub struct Route<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = taker_token_account.mint == mint.key(),
+        constraint = taker_token_account.owner == taker.key(),
+    )]
+    pub taker_token_account: Box<Account<'info, TokenAccount>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+#[event]
+pub struct RouteFilled {
+    pub taker: Pubkey,
+    pub mint: Pubkey,
+    pub buy: bool,
+    pub listings: u32, // listings taken from, in full or in part
+    pub skipped: u32,  // listings gone, paused, in presale or not fitting
+    pub quantity: u64,
+    pub sol_amount: u64,
+    pub avg_price: u64, // lamports per whole token
+}
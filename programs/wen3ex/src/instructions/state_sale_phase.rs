@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+use crate::errors::Wen3ExError;
+
+use super::constants::SALE_PHASE_SEED;
+
+// presale of one creator's token 2 sol listings of one mint: from start_time
+// to end_time only wallets in the merkle tree may take them, afterwards
+// anyone may
+#[account]
+pub struct SalePhaseAccount {
+    pub version: u32,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub merkle_root: [u8; 32], // leaves are keccak(wallet, allocation as u64 le)
+    pub start_time: i64,
+    pub end_time: i64,
+    pub bump: u8,
+}
+
+impl SalePhaseAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 32 + 8 + 8 + 1;
+
+    pub fn is_presale(&self, now_ts: i64) -> bool {
+        now_ts < self.end_time
+    }
+
+    // sorted pair hashing, the proof runs from the leaf up
+    pub fn verify(&self, wallet: &Pubkey, allocation: u64, proof: &[[u8; 32]]) -> bool {
+        let mut node = keccak::hashv(&[wallet.as_ref(), &allocation.to_le_bytes()]).0;
+        for sibling in proof {
+            node = if node <= *sibling {
+                keccak::hashv(&[&node, sibling]).0
+            } else {
+                keccak::hashv(&[sibling, &node]).0
+            };
+        }
+        node == self.merkle_root
+    }
+
+    // the phase of a listing, None once it is closed
+    pub fn load(program_id: &Pubkey, sale_phase: &AccountInfo) -> Result<Option<Self>> {
+        if sale_phase.owner != program_id || sale_phase.data_is_empty() {
+            return Ok(None);
+        }
+        let data = sale_phase.try_borrow_data()?;
+        Ok(Some(Self::try_deserialize(&mut &data[..])?))
+    }
+}
+
+#[account] // what one wallet has taken during one presale
+pub struct AllowlistClaimAccount {
+    pub version: u32,
+    pub sale_phase: Pubkey,
+    pub wallet: Pubkey,
+    pub taken: u64, // token amount
+    pub bump: u8,
+}
+
+impl AllowlistClaimAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], start_time: i64, end_time: i64)]
+pub struct SalePhaseSet<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [SALE_PHASE_SEED, creator.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = creator,
+        space = SalePhaseAccount::LEN,
+        constraint = start_time < end_time @ Wen3ExError::InvalidSaleWindow,
+    )]
+    pub sale_phase: Box<Account<'info, SalePhaseAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub mint: AccountInfo<'info>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+// closing the phase opens its listings to everyone
+#[derive(Accounts)]
+pub struct SalePhaseClose<'info> {
+    #[account(
+        mut,
+        seeds = [SALE_PHASE_SEED, creator.key().as_ref(), sale_phase.mint.as_ref()],
+        bump = sale_phase.bump,
+        close = creator
+    )]
+    pub sale_phase: Box<Account<'info, SalePhaseAccount>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+}
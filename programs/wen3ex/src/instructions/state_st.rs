@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{CloseAccount, Mint, SetAuthority, TokenAccount, Transfer};
 
-use super::constants::VAULT_SOL_2_TOKEN_SEED;
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_ST, MINT_ENTRY_SEED, MINT_INDEX_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_SOL_2_TOKEN_SEED,
+    },
+    state_config::ConfigAccount,
+};
 
 #[account] // buy token with sol
 pub struct MarketStAccount {
@@ -11,6 +19,23 @@ pub struct MarketStAccount {
     pub token_amount: u64, // 期待的数量
     pub sol_amount: u64,   // 质押 sol
     pub create_time: i64,
+    pub bounty: u64, // lamports kept in the market account, paid to the taker or the cranker
+    pub expire_time: i64, // 0 never expires
+    pub start_time: i64, // not fillable before, 0 open right away
+    pub token_decimals: Option<u8>, // None for listings created before it was recorded
+}
+
+impl MarketStAccount {
+    pub const VERSION: u32 = 4;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 2;
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        self.expire_time != 0 && now_ts >= self.expire_time
+    }
+
+    pub fn is_open(&self, now_ts: i64) -> bool {
+        now_ts >= self.start_time
+    }
 }
 
 #[derive(Accounts)]
@@ -47,6 +72,21 @@ pub struct MarketStCreate<'info> {
     pub rent: Sysvar<'info, Rent>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ST) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketStCreate<'info> {
     pub fn transfer_from_creator_to_vault_context(
@@ -85,12 +125,14 @@ pub struct MarketStCancel<'info> {
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub vault_authority: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketStAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         close = creator
     )]
@@ -99,6 +141,12 @@ pub struct MarketStCancel<'info> {
     pub mint: Account<'info, Mint>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketStCancel<'info> {
     pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
@@ -111,6 +159,53 @@ impl<'info> MarketStCancel<'info> {
     }
 }
 
+// anyone may close an expired listing, the escrowed sol goes back to the
+// creator and the bounty to the cranker
+#[derive(Accounts)]
+pub struct MarketStCrank<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub cranker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [VAULT_SOL_2_TOKEN_SEED, market_account.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketStAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketStAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+impl<'info> MarketStCrank<'info> {
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.creator.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
 #[derive(Accounts)]
 pub struct MarketStExchange<'info> {
     #[account(mut, signer)]
@@ -127,6 +222,13 @@ pub struct MarketStExchange<'info> {
     pub creator: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = creator_token_account.mint == mint.key(),
+        constraint = creator_token_account.owner == creator.key() @ Wen3ExError::IncorrectCreatorTokenAccount,
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketStAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         close = creator
     )]
@@ -134,6 +236,7 @@ pub struct MarketStExchange<'info> {
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -144,16 +247,30 @@ pub struct MarketStExchange<'info> {
     pub token_program: AccountInfo<'info>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub system_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_ST) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 
 impl<'info> MarketStExchange<'info> {
     pub fn transfer_from_taker_to_creator_context(
         &self,
-        creator_token_account: AccountInfo<'info>,
     ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         let cpi_accounts = Transfer {
             from: self.taker_token_account.to_account_info().clone(),
-            to: creator_token_account.clone(),
+            to: self.creator_token_account.to_account_info().clone(),
             authority: self.taker.clone(),
         };
         CpiContext::new(self.token_program.clone(), cpi_accounts)
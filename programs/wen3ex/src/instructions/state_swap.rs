@@ -0,0 +1,340 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, Mint, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_SWAP, MINT_ENTRY_SEED, MINT_INDEX_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_SWAP_SEED,
+    },
+    state_config::ConfigAccount,
+};
+
+// remaining_accounts of cancel and exchange, SWAP_ACCOUNTS_PER_NFT per offered
+// nft, in the order of MarketSwapAccount.offered:
+// [vault_token_account, destination_token_account]
+// the destination is the creator's token account of the mint on cancel, the
+// taker's on exchange. exchange may end with the [referrer, referrer_account]
+// pair
+pub const SWAP_ACCOUNTS_PER_NFT: usize = 2;
+
+// swap nfts for an nft. the creator escrows one or more nfts, each in its own
+// vault, and asks for either a given mint or any nft of a verified collection.
+// sol may go along either way: taker_sol from the taker, or creator_sol, held
+// by this account, to the taker. every nft moves with a plain spl token
+// transfer, so programmable nfts, frozen in their token accounts, can't be
+// swapped
+#[account]
+pub struct MarketSwapAccount {
+    pub version: u32,
+    pub creator: Pubkey,
+    pub offered: Vec<Pubkey>,    // the escrowed mints
+    pub want_mint: Pubkey,       // default when asking for a collection
+    pub want_collection: Pubkey, // default when asking for a mint
+    pub taker_sol: u64,
+    pub creator_sol: u64,
+    pub create_time: i64,
+}
+
+impl MarketSwapAccount {
+    pub const VERSION: u32 = 1;
+    pub const MAX_OFFERED: usize = 4;
+    pub const LEN: usize = 8 + 4 + 32 + (4 + 32 * Self::MAX_OFFERED) + 32 + 32 + 8 + 8 + 8;
+
+    pub fn vault_address(program_id: &Pubkey, market: &Pubkey, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[VAULT_SWAP_SEED, market.as_ref(), mint.as_ref()],
+            program_id,
+        )
+        .0
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketSwapCreate<'info> {
+    #[account(zero)]
+    pub market_account: Box<Account<'info, MarketSwapAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_SWAP_SEED, market_account.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = creator,
+        token::mint = mint,
+        token::authority = vault_authority
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = creator_token_account.amount >= 1,
+        constraint = creator_token_account.mint == mint.key()
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = mint.decimals == 0 && mint.supply == 1 @ Wen3ExError::NotAnNft)]
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_SWAP) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> MarketSwapCreate<'info> {
+    pub fn transfer_from_creator_to_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.creator_token_account.to_account_info().clone(),
+            to: self.vault_token_account.to_account_info().clone(),
+            authority: self.creator.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// adds one more nft to a swap listing
+#[derive(Accounts)]
+pub struct MarketSwapDeposit<'info> {
+    #[account(
+        mut,
+        constraint = market_account.version == MarketSwapAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+    )]
+    pub market_account: Box<Account<'info, MarketSwapAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_SWAP_SEED, market_account.key().as_ref(), mint.key().as_ref()],
+        bump,
+        payer = creator,
+        token::mint = mint,
+        token::authority = vault_authority
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = creator_token_account.amount >= 1,
+        constraint = creator_token_account.mint == mint.key()
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = mint.decimals == 0 && mint.supply == 1 @ Wen3ExError::NotAnNft)]
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_SWAP) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+}
+
+impl<'info> MarketSwapDeposit<'info> {
+    pub fn transfer_from_creator_to_vault_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.creator_token_account.to_account_info().clone(),
+            to: self.vault_token_account.to_account_info().clone(),
+            authority: self.creator.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct MarketSwapCancel<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketSwapAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketSwapAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.offered[0].as_ref()], bump)]
+    /// CHECK: listing index of the first offered mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarketSwapExchange<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = taker_nft_account.mint == taker_mint.key(),
+        constraint = taker_nft_account.owner == taker.key(),
+        constraint = taker_nft_account.amount >= 1,
+    )]
+    pub taker_nft_account: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = taker_mint.decimals == 0 && taker_mint.supply == 1 @ Wen3ExError::NotAnNft)]
+    pub taker_mint: Box<Account<'info, Mint>>,
+    /// CHECK: metadata of taker_mint, read for collection requests only
+    pub taker_metadata: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = creator_nft_account.mint == taker_mint.key(),
+        constraint = creator_nft_account.owner == creator.key() @ Wen3ExError::IncorrectCreatorTokenAccount,
+    )]
+    pub creator_nft_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketSwapAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketSwapAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_SWAP) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.offered[0].as_ref()], bump)]
+    /// CHECK: listing index of the first offered mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> MarketSwapExchange<'info> {
+    pub fn transfer_from_taker_to_creator_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.taker_nft_account.to_account_info().clone(),
+            to: self.creator_nft_account.to_account_info().clone(),
+            authority: self.taker.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
+// the offered nfts of a cancel or exchange, vault and destination per nft
+pub struct SwapVault<'a, 'info> {
+    pub vault_token_account: &'a AccountInfo<'info>,
+    pub destination_token_account: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SwapVault<'a, 'info> {
+    pub fn load_all(
+        program_id: &Pubkey,
+        market_account: &Account<'info, MarketSwapAccount>,
+        destination_owner: &Pubkey,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Vec<Self>> {
+        if remaining_accounts.len() != market_account.offered.len() * SWAP_ACCOUNTS_PER_NFT {
+            return err!(Wen3ExError::InvalidSwapAccounts);
+        }
+        let market_key = market_account.key();
+        market_account
+            .offered
+            .iter()
+            .zip(remaining_accounts.chunks_exact(SWAP_ACCOUNTS_PER_NFT))
+            .map(|(mint, accounts)| {
+                let (vault_token_account, destination_token_account) = (&accounts[0], &accounts[1]);
+                if vault_token_account.key()
+                    != MarketSwapAccount::vault_address(program_id, &market_key, mint)
+                {
+                    return err!(Wen3ExError::InvalidSwapAccounts);
+                }
+                if Account::<TokenAccount>::try_from(vault_token_account)?.is_frozen() {
+                    return err!(Wen3ExError::VaultFrozen);
+                }
+                let destination = Account::<TokenAccount>::try_from(destination_token_account)?;
+                if destination.mint != *mint || destination.owner != *destination_owner {
+                    return err!(Wen3ExError::IncorrectRecipientTokenAccount);
+                }
+                Ok(SwapVault {
+                    vault_token_account,
+                    destination_token_account,
+                })
+            })
+            .collect()
+    }
+
+    pub fn transfer_context(
+        &self,
+        token_program: &AccountInfo<'info>,
+        vault_authority: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.clone(),
+            to: self.destination_token_account.clone(),
+            authority: vault_authority.clone(),
+        };
+        CpiContext::new(token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_context(
+        &self,
+        token_program: &AccountInfo<'info>,
+        vault_authority: &AccountInfo<'info>,
+        creator: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.clone(),
+            destination: creator.clone(),
+            authority: vault_authority.clone(),
+        };
+        CpiContext::new(token_program.clone(), cpi_accounts)
+    }
+}
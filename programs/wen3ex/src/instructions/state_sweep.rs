@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::Wen3ExError;
+
+use super::{constants::CONFIG_SEED, state_config::ConfigAccount};
+
+// remaining_accounts, SWEEP_ACCOUNTS_PER_LISTING per listing:
+// [market_account, vault_token_account, vault_authority, creator, taker_token_account, sale_phase,
+//  mint_index, creator_index]
+// market_account is a MarketTsAccount or a MarketNftToSolAccount, sale_phase
+// the SalePhaseAccount address of the listing's creator and mint, mint_index
+// and creator_index the listing index addresses (all three may not exist).
+// an optional [referrer, referrer_account] pair follows the last listing
+pub const SWEEP_ACCOUNTS_PER_LISTING: usize = 8;
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub taker: AccountInfo<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+}
+
+#[event]
+pub struct SweepFilled {
+    pub taker: Pubkey,
+    pub listings: u32, // listings filled
+    pub skipped: u32,  // listings gone, paused, in presale or over budget
+    pub sol_spent: u64,
+    pub quantity: u64,
+}
@@ -1,7 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{CloseAccount, Mint, SetAuthority, TokenAccount, Transfer};
 
-use super::constants::VAULT_TOKEN_2_SOL_SEED;
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        ALLOWLIST_CLAIM_SEED, BPS_DENOMINATOR, CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_TS,
+        MAX_FEE_BPS, MINT_ENTRY_SEED, MINT_INDEX_SEED, SALE_PHASE_SEED, VAULT_AUTHORITY_SEED,
+        VAULT_TOKEN_2_SOL_SEED, VAULT_VESTING_SEED, VESTING_SEED,
+    },
+    state_config::ConfigAccount,
+    state_sale_phase::AllowlistClaimAccount,
+    state_vesting::VestingAccount,
+};
 
 #[account] // sell token with sol back
 pub struct MarketTsAccount {
@@ -11,6 +22,46 @@ pub struct MarketTsAccount {
     pub token_amount: u64, // 质押的数量
     pub sol_amount: u64,   // 期待 sol 的数量
     pub create_time: i64,
+    pub bounty: u64, // lamports kept in the market account, paid to the taker or the cranker
+    pub expire_time: i64, // 0 never expires
+    pub start_time: i64, // not fillable before, 0 open right away
+    pub vesting_cliff: i64, // seconds after the exchange before anything unlocks
+    pub vesting_duration: i64, // seconds to unlock linearly, 0 delivered right away
+    pub oracle: Pubkey, // price feed the listing is pegged to, Pubkey::default() for sol_amount
+    pub spread_bps: i32, // over (or under) the oracle price
+    pub oracle_max_age: i64, // seconds
+    pub oracle_max_conf_bps: u32,
+    pub token_decimals: Option<u8>, // None for listings created before it was recorded
+    pub referral_bps: u32, // paid by the creator to a fill's referrer, on top of the fee share
+}
+
+impl MarketTsAccount {
+    pub const VERSION: u32 = 7;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 32 + 4 + 8 + 4 + 2 + 4;
+
+    pub fn is_pegged(&self) -> bool {
+        self.oracle != Pubkey::default()
+    }
+
+    // the price of a listing that is not pegged
+    pub fn fixed_sol_amount(&self) -> Result<u64> {
+        if self.is_pegged() {
+            return err!(Wen3ExError::OraclePriceRequired);
+        }
+        Ok(self.sol_amount)
+    }
+
+    pub fn is_vesting(&self) -> bool {
+        self.vesting_duration != 0
+    }
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        self.expire_time != 0 && now_ts >= self.expire_time
+    }
+
+    pub fn is_open(&self, now_ts: i64) -> bool {
+        now_ts >= self.start_time
+    }
 }
 
 #[derive(Accounts)]
@@ -49,6 +100,21 @@ pub struct MarketTsCreate<'info> {
     pub rent: Sysvar<'info, Rent>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_TS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: registry entry of the mint, may not exist
+    pub mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketTsCreate<'info> {
     pub fn transfer_from_creator_to_vault_context(
@@ -87,12 +153,14 @@ pub struct MarketTsCancel<'info> {
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub vault_authority: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketTsAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         close = creator
     )]
@@ -101,6 +169,12 @@ pub struct MarketTsCancel<'info> {
     pub mint: Account<'info, Mint>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 impl<'info> MarketTsCancel<'info> {
     pub fn transfer_from_vault_to_creator_context(
@@ -124,6 +198,73 @@ impl<'info> MarketTsCancel<'info> {
     }
 }
 
+// anyone may close an expired listing, the deposit goes back to the creator
+// and the bounty to the cranker
+#[derive(Accounts)]
+pub struct MarketTsCrank<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub cranker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = creator_token_account.mint == mint.key(),
+        constraint = creator_token_account.owner == creator.key(),
+    )]
+    pub creator_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_2_SOL_SEED, market_account.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketTsAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = market_account.token == mint.key(),
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketTsAccount>>,
+
+    pub mint: Account<'info, Mint>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+impl<'info> MarketTsCrank<'info> {
+    pub fn transfer_from_vault_to_creator_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.creator_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.creator.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
 #[derive(Accounts)]
 pub struct MarketTsExchange<'info> {
     #[account(mut, signer)]
@@ -139,6 +280,7 @@ pub struct MarketTsExchange<'info> {
     pub creator: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketTsAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         close = creator
     )]
@@ -146,6 +288,7 @@ pub struct MarketTsExchange<'info> {
     #[account(
         mut,
         constraint = vault_token_account.mint == mint.key(),
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -156,6 +299,125 @@ pub struct MarketTsExchange<'info> {
     pub token_program: AccountInfo<'info>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub system_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_TS) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, constraint = treasury.key() == config.treasury @ Wen3ExError::IncorrectTreasuryAccount)]
+    /// CHECK: only receives the protocol fee
+    pub treasury: AccountInfo<'info>,
+    #[account(seeds = [SALE_PHASE_SEED, market_account.creator.as_ref(), market_account.token.as_ref()], bump)]
+    /// CHECK: presale phase of the listing, may not exist
+    pub sale_phase: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+// taking a listing during its presale, remaining_accounts as for exchange
+#[derive(Accounts)]
+pub struct MarketTsExchangeAllowlisted<'info> {
+    pub exchange: MarketTsExchange<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [ALLOWLIST_CLAIM_SEED, exchange.sale_phase.key().as_ref(), exchange.taker.key().as_ref()],
+        bump,
+        payer = taker,
+        space = AllowlistClaimAccount::LEN,
+    )]
+    pub allowlist_claim: Box<Account<'info, AllowlistClaimAccount>>,
+    #[account(mut, signer, constraint = taker.key() == exchange.taker.key())]
+    /// CHECK: the exchange taker, pays for the claim record
+    pub taker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+}
+
+// taking a vesting listing, the tokens go to a vesting vault of the taker,
+// remaining_accounts as for exchange
+#[derive(Accounts)]
+pub struct MarketTsExchangeVested<'info> {
+    pub exchange: MarketTsExchange<'info>,
+    #[account(
+        init,
+        seeds = [VESTING_SEED, exchange.market_account.key().as_ref()],
+        bump,
+        payer = taker,
+        space = VestingAccount::LEN,
+    )]
+    pub vesting: Box<Account<'info, VestingAccount>>,
+    #[account(
+        init,
+        seeds = [VAULT_VESTING_SEED, vesting.key().as_ref()],
+        bump,
+        payer = taker,
+        token::mint = mint,
+        token::authority = vesting_authority
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, vesting.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vesting_authority: AccountInfo<'info>,
+    #[account(constraint = mint.key() == exchange.mint.key())]
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(mut, signer, constraint = taker.key() == exchange.taker.key())]
+    /// CHECK: the exchange taker, pays for the vesting accounts
+    pub taker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub system_program: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+// peg a listing to a price feed, or unpeg it with Pubkey::default(), vesting
+// listings keep their fixed price
+#[derive(Accounts)]
+#[instruction(spread_bps: i32)]
+pub struct MarketTsSetPeg<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketTsAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = !market_account.is_vesting() @ Wen3ExError::VestingNotPeggable,
+        constraint = spread_bps > -(BPS_DENOMINATOR as i32) @ Wen3ExError::InvalidAmount,
+    )]
+    pub market_account: Box<Account<'info, MarketTsAccount>>,
+    /// CHECK: the price account, parsed by the handler unless it is Pubkey::default()
+    pub oracle: AccountInfo<'info>,
+}
+
+// the bps of the price the creator pays a fill's referrer, 0 for none
+#[derive(Accounts)]
+#[instruction(referral_bps: u32)]
+pub struct MarketTsSetReferral<'info> {
+    #[account(signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketTsAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        constraint = referral_bps <= MAX_FEE_BPS @ Wen3ExError::InvalidFee,
+    )]
+    pub market_account: Box<Account<'info, MarketTsAccount>>,
+}
+
+// taking a pegged listing at the oracle price, remaining_accounts as for exchange
+#[derive(Accounts)]
+pub struct MarketTsExchangePegged<'info> {
+    pub exchange: MarketTsExchange<'info>,
+    #[account(constraint = oracle.key() == exchange.market_account.oracle @ Wen3ExError::InvalidOracle)]
+    /// CHECK: the price account of the listing, parsed by the handler
+    pub oracle: AccountInfo<'info>,
 }
 
 impl<'info> MarketTsExchange<'info> {
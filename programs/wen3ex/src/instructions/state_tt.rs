@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{CloseAccount, Mint, SetAuthority, TokenAccount, Transfer};
 
-use super::constants::VAULT_TOKEN_2_TOKEN_SEED;
+use crate::errors::Wen3ExError;
+
+use super::{
+    constants::{
+        CONFIG_SEED, CREATOR_INDEX_SEED, MARKET_TT, MINT_ENTRY_SEED, MINT_INDEX_SEED,
+        VAULT_AUTHORITY_SEED, VAULT_TOKEN_2_TOKEN_SEED,
+    },
+    state_config::ConfigAccount,
+};
 
 #[account] // token 2 token
 pub struct MarketTtAccount {
@@ -12,6 +20,24 @@ pub struct MarketTtAccount {
     pub receive_token: Pubkey, // 期待换回的物品
     pub receive_amount: u64,   // 期待换回的数量
     pub create_time: i64,
+    pub bounty: u64, // lamports kept in the market account, paid to the taker or the cranker
+    pub expire_time: i64, // 0 never expires
+    pub start_time: i64, // not fillable before, 0 open right away
+    pub deposit_decimals: Option<u8>, // None for listings created before they were recorded
+    pub receive_decimals: Option<u8>,
+}
+
+impl MarketTtAccount {
+    pub const VERSION: u32 = 4;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 2 + 2;
+
+    pub fn is_expired(&self, now_ts: i64) -> bool {
+        self.expire_time != 0 && now_ts >= self.expire_time
+    }
+
+    pub fn is_open(&self, now_ts: i64) -> bool {
+        now_ts >= self.start_time
+    }
 }
 
 #[derive(Accounts)]
@@ -52,6 +78,8 @@ pub struct MarketTtCreate<'info> {
         constraint = receive_token_account.mint == receive_token.key(),
     )]
     pub receive_token_account: Account<'info, TokenAccount>,
+    #[account(address = receive_token)]
+    pub receive_mint: Box<Account<'info, Mint>>, // for its decimals
 
     #[account(mut, signer)]
     /// CHECK: This is not dangerous because we don't read or write from this account
@@ -61,6 +89,24 @@ pub struct MarketTtCreate<'info> {
     pub rent: Sysvar<'info, Rent>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_TT) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(seeds = [MINT_ENTRY_SEED, deposit_token.as_ref()], bump)]
+    /// CHECK: registry entry of the deposit mint, may not exist
+    pub deposit_mint_entry: AccountInfo<'info>,
+    #[account(seeds = [MINT_ENTRY_SEED, receive_token.as_ref()], bump)]
+    /// CHECK: registry entry of the receive mint, may not exist
+    pub receive_mint_entry: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, creator.key().as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 
 impl<'info> MarketTtCreate<'info> {
@@ -97,12 +143,14 @@ pub struct MarketTtCancel<'info> {
     #[account(
         mut,
         constraint = deposit_token_account.mint == vault_token_account.mint,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub vault_authority: AccountInfo<'info>,
     #[account(
         mut,
+        constraint = market_account.version == MarketTtAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         constraint = market_account.deposit_token == deposit_token_account.mint,
         close = creator
@@ -111,6 +159,12 @@ pub struct MarketTtCancel<'info> {
 
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.deposit_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 
 impl<'info> MarketTtCancel<'info> {
@@ -133,6 +187,70 @@ impl<'info> MarketTtCancel<'info> {
     }
 }
 
+// anyone may close an expired listing, the deposit goes back to the creator
+// and the bounty to the cranker
+#[derive(Accounts)]
+pub struct MarketTtCrank<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub cranker: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == creator.key(),
+        constraint = deposit_token_account.mint == market_account.deposit_token,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_2_TOKEN_SEED, market_account.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, market_account.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = market_account.version == MarketTtAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = market_account.creator == *creator.key,
+        close = creator
+    )]
+    pub market_account: Box<Account<'info, MarketTtAccount>>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.deposit_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
+}
+
+impl<'info> MarketTtCrank<'info> {
+    pub fn transfer_to_creator_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.deposit_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.creator.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
+
 #[derive(Accounts)]
 pub struct MarketTtExchange<'info> {
     #[account(mut, signer)]
@@ -168,6 +286,7 @@ pub struct MarketTtExchange<'info> {
         constraint = market_account.receive_amount <= taker_deposit_token_account.amount,
         constraint = market_account.deposit_token == creator_deposit_token_account.mint,
         constraint = market_account.receive_token == taker_deposit_token_account.mint,
+        constraint = market_account.version == MarketTtAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
         constraint = market_account.creator == *creator.key,
         close = creator
     )]
@@ -175,7 +294,8 @@ pub struct MarketTtExchange<'info> {
     #[account(
         mut,
         constraint = vault_token_account.owner == vault_authority.key(),
-        constraint = vault_token_account.mint == market_account.deposit_token
+        constraint = vault_token_account.mint == market_account.deposit_token,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
     )]
     pub vault_token_account: Box<Account<'info, TokenAccount>>,
 
@@ -184,6 +304,18 @@ pub struct MarketTtExchange<'info> {
 
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub token_program: AccountInfo<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = !config.is_paused(MARKET_TT) @ Wen3ExError::MarketPaused,
+    )]
+    pub config: Box<Account<'info, ConfigAccount>>,
+    #[account(mut, seeds = [MINT_INDEX_SEED, market_account.deposit_token.as_ref()], bump)]
+    /// CHECK: listing index of the mint, may not exist
+    pub mint_index: AccountInfo<'info>,
+    #[account(mut, seeds = [CREATOR_INDEX_SEED, market_account.creator.as_ref()], bump)]
+    /// CHECK: listing index of the creator, may not exist
+    pub creator_index: AccountInfo<'info>,
 }
 
 impl<'info> MarketTtExchange<'info> {
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{CloseAccount, TokenAccount, Transfer};
+
+use crate::errors::Wen3ExError;
+
+use super::constants::{VAULT_AUTHORITY_SEED, VAULT_VESTING_SEED};
+
+// tokens bought from a vesting token 2 sol listing, nothing unlocks before
+// cliff_time, then linearly from start_time until end_time
+#[account]
+pub struct VestingAccount {
+    pub version: u32,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub total: u64,
+    pub claimed: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub bump: u8,
+}
+
+impl VestingAccount {
+    pub const VERSION: u32 = 1;
+    pub const LEN: usize = 8 + 4 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn vested(&self, now_ts: i64) -> u64 {
+        if now_ts < self.cliff_time {
+            return 0;
+        }
+        if now_ts >= self.end_time {
+            return self.total;
+        }
+        let elapsed = (now_ts - self.start_time) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut, signer)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub beneficiary: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = beneficiary_token_account.mint == vesting.mint,
+        constraint = beneficiary_token_account.owner == beneficiary.key(),
+    )]
+    pub beneficiary_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = vesting.version == VestingAccount::VERSION @ Wen3ExError::MarketVersionMismatch,
+        constraint = vesting.beneficiary == beneficiary.key() @ Wen3ExError::InvalidAuthority,
+    )]
+    pub vesting: Box<Account<'info, VestingAccount>>,
+    #[account(
+        mut,
+        seeds = [VAULT_VESTING_SEED, vesting.key().as_ref()],
+        bump,
+        constraint = !vault_token_account.is_frozen() @ Wen3ExError::VaultFrozen,
+    )]
+    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [VAULT_AUTHORITY_SEED, vesting.key().as_ref()], bump)]
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub vault_authority: AccountInfo<'info>,
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn transfer_to_beneficiary_context(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.vault_token_account.to_account_info().clone(),
+            to: self.beneficiary_token_account.to_account_info().clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+
+    pub fn close_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info().clone(),
+            destination: self.beneficiary.clone(),
+            authority: self.vault_authority.clone(),
+        };
+        CpiContext::new(self.token_program.clone(), cpi_accounts)
+    }
+}
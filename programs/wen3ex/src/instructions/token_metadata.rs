@@ -0,0 +1,240 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+    },
+};
+
+use crate::errors::Wen3ExError;
+
+// the parts of metaplex token metadata we use: the token standard and the
+// collection of a metadata account, and its `transfer` instruction, which moves programmable
+// nfts (thawing, transferring and refreezing them, checking the rule set) as
+// well as plain ones
+pub mod token_metadata_program {
+    anchor_lang::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+pub mod auth_rules_program {
+    anchor_lang::declare_id!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+}
+
+// MetadataInstruction::Transfer, TransferArgs::V1
+const TRANSFER_INSTRUCTION: u8 = 49;
+const TRANSFER_ARGS_V1: u8 = 0;
+
+const METADATA_PREFIX: &[u8] = b"metadata";
+const EDITION_SUFFIX: &[u8] = b"edition";
+
+const KEY_METADATA_V1: u8 = 4;
+const MINT_OFFSET: usize = 33;
+const NAME_OFFSET: usize = 65;
+const CREATOR_LEN: usize = 32 + 1 + 1;
+// TokenStandard::ProgrammableNonFungible / ProgrammableNonFungibleEdition
+const TOKEN_STANDARD_PROGRAMMABLE: u8 = 4;
+const TOKEN_STANDARD_PROGRAMMABLE_EDITION: u8 = 5;
+
+// the master edition of `mint`, the freeze authority token metadata sets
+pub fn edition_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            METADATA_PREFIX,
+            token_metadata_program::ID.as_ref(),
+            mint.as_ref(),
+            EDITION_SUFFIX,
+        ],
+        &token_metadata_program::ID,
+    )
+    .0
+}
+
+// whether the nft of `mint` is programmable, read from its metadata account
+pub fn is_programmable(metadata: &AccountInfo, mint: &Pubkey) -> Result<bool> {
+    let token_standard = read_metadata(metadata, mint)?.token_standard;
+    Ok(token_standard == Some(TOKEN_STANDARD_PROGRAMMABLE)
+        || token_standard == Some(TOKEN_STANDARD_PROGRAMMABLE_EDITION))
+}
+
+// the collection of the nft of `mint`, when verified
+pub fn verified_collection(metadata: &AccountInfo, mint: &Pubkey) -> Result<Option<Pubkey>> {
+    Ok(match read_metadata(metadata, mint)?.collection {
+        Some((true, collection)) => Some(collection),
+        _ => None,
+    })
+}
+
+struct MetadataFields {
+    token_standard: Option<u8>,
+    collection: Option<(bool, Pubkey)>, // verified, key
+}
+
+// metadata written before token standards and collections existed ends
+// early, those read as none
+fn read_metadata(metadata: &AccountInfo, mint: &Pubkey) -> Result<MetadataFields> {
+    if metadata.owner != &token_metadata_program::ID {
+        return err!(Wen3ExError::InvalidMetadata);
+    }
+    let data = metadata.try_borrow_data()?;
+    if data.len() < NAME_OFFSET
+        || data[0] != KEY_METADATA_V1
+        || data[MINT_OFFSET..NAME_OFFSET] != mint.to_bytes()
+    {
+        return err!(Wen3ExError::InvalidMetadata);
+    }
+
+    let mut reader = Reader {
+        data: &data,
+        offset: NAME_OFFSET,
+    };
+    // name, symbol, uri
+    for _ in 0..3 {
+        let len = reader.u32()? as usize;
+        reader.skip(len);
+    }
+    reader.skip(2); // seller_fee_basis_points
+    if reader.u8() == 1 {
+        let count = reader.u32()? as usize;
+        reader.skip(count * CREATOR_LEN);
+    }
+    reader.skip(2); // primary_sale_happened, is_mutable
+    if reader.u8() == 1 {
+        reader.skip(1); // edition_nonce
+    }
+    let token_standard = match reader.u8() {
+        1 => Some(reader.u8()),
+        _ => None,
+    };
+    let collection = match reader.u8() {
+        1 => Some((reader.u8() == 1, reader.pubkey()?)),
+        _ => None,
+    };
+    Ok(MetadataFields {
+        token_standard,
+        collection,
+    })
+}
+
+// reads past the end as the end of old metadata, see read_metadata
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn skip(&mut self, len: usize) {
+        self.offset = self.offset.saturating_add(len);
+    }
+
+    fn u8(&mut self) -> u8 {
+        let value = self.data.get(self.offset).copied().unwrap_or(0);
+        self.skip(1);
+        value
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset.saturating_add(4))
+            .ok_or(Wen3ExError::InvalidMetadata)?;
+        self.offset += 4;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset.saturating_add(32))
+            .ok_or(Wen3ExError::InvalidMetadata)?;
+        self.offset += 32;
+        Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+    }
+}
+
+// optional accounts, the token records and the rule set, are passed as the
+// token metadata program itself when absent
+pub struct PnftTransfer<'a, 'info> {
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub token: &'a AccountInfo<'info>,
+    pub token_owner: &'a AccountInfo<'info>,
+    pub destination: &'a AccountInfo<'info>,
+    pub destination_owner: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub edition: &'a AccountInfo<'info>,
+    pub owner_token_record: &'a AccountInfo<'info>,
+    pub destination_token_record: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+    pub authorization_rules_program: &'a AccountInfo<'info>,
+    pub authorization_rules: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> PnftTransfer<'a, 'info> {
+    pub fn invoke(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let accounts = vec![
+            AccountMeta::new(self.token.key(), false),
+            AccountMeta::new_readonly(self.token_owner.key(), false),
+            AccountMeta::new(self.destination.key(), false),
+            AccountMeta::new_readonly(self.destination_owner.key(), false),
+            AccountMeta::new_readonly(self.mint.key(), false),
+            AccountMeta::new(self.metadata.key(), false),
+            AccountMeta::new_readonly(self.edition.key(), false),
+            self.optional(self.owner_token_record),
+            self.optional(self.destination_token_record),
+            AccountMeta::new_readonly(self.authority.key(), true),
+            AccountMeta::new(self.payer.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+            AccountMeta::new_readonly(self.sysvar_instructions.key(), false),
+            AccountMeta::new_readonly(self.token_program.key(), false),
+            AccountMeta::new_readonly(self.associated_token_program.key(), false),
+            AccountMeta::new_readonly(self.authorization_rules_program.key(), false),
+            AccountMeta::new_readonly(self.authorization_rules.key(), false),
+        ];
+        let mut data = vec![TRANSFER_INSTRUCTION, TRANSFER_ARGS_V1];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(0); // authorization_data: None
+
+        invoke_signed(
+            &Instruction {
+                program_id: token_metadata_program::ID,
+                accounts,
+                data,
+            },
+            &[
+                self.token.clone(),
+                self.token_owner.clone(),
+                self.destination.clone(),
+                self.destination_owner.clone(),
+                self.mint.clone(),
+                self.metadata.clone(),
+                self.edition.clone(),
+                self.owner_token_record.clone(),
+                self.destination_token_record.clone(),
+                self.authority.clone(),
+                self.payer.clone(),
+                self.system_program.clone(),
+                self.sysvar_instructions.clone(),
+                self.token_program.clone(),
+                self.associated_token_program.clone(),
+                self.authorization_rules_program.clone(),
+                self.authorization_rules.clone(),
+                self.token_metadata_program.clone(),
+            ],
+            signer_seeds,
+        )
+        .map_err(Into::into)
+    }
+
+    fn optional(&self, account: &AccountInfo) -> AccountMeta {
+        if account.key() == token_metadata_program::ID {
+            AccountMeta::new_readonly(account.key(), false)
+        } else {
+            AccountMeta::new(account.key(), false)
+        }
+    }
+}
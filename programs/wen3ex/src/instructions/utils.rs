@@ -0,0 +1,40 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+};
+
+use crate::errors::Wen3ExError;
+
+// moves lamports with the system program when `system_program` is given
+// (`from` is a signing system account), by hand otherwise (`from` is owned
+// by this program)
+pub fn send_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: Option<&AccountInfo<'info>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    match system_program {
+        Some(system_program) => invoke(
+            &system_instruction::transfer(from.key, to.key, amount),
+            &[from.clone(), to.clone(), system_program.clone()],
+        )
+        .map_err(Into::into),
+        None => {
+            **from.try_borrow_mut_lamports()? -= amount;
+            **to.try_borrow_mut_lamports()? += amount;
+            Ok(())
+        }
+    }
+}
+
+// a listing either never expires (0) or expires some time after it opens
+pub fn check_expire_time(expire_time: i64, open_ts: i64) -> Result<()> {
+    if expire_time != 0 && expire_time <= open_ts {
+        return err!(Wen3ExError::InvalidExpireTime);
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::token;
+
+use crate::errors::Wen3ExError;
+
+use super::{constants::VAULT_AUTHORITY_SEED, state_vesting::ClaimVested};
+
+pub fn claim(ctx: Context<ClaimVested>) -> Result<()> {
+    let vesting = &ctx.accounts.vesting;
+    let amount = vesting.vested(Clock::get()?.unix_timestamp) - vesting.claimed;
+    if amount == 0 {
+        return err!(Wen3ExError::NothingToClaim);
+    }
+
+    let vesting_key = vesting.key();
+    let vault_authority_bump = *ctx.bumps.get("vault_authority").unwrap();
+    let authority_seeds = &[
+        VAULT_AUTHORITY_SEED,
+        vesting_key.as_ref(),
+        &[vault_authority_bump],
+    ];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_to_beneficiary_context()
+            .with_signer(&[&authority_seeds[..]]),
+        amount,
+    )?;
+
+    let vesting = &mut ctx.accounts.vesting;
+    vesting.claimed += amount;
+
+    // all out, the rent of both accounts goes back to the beneficiary
+    if vesting.claimed == vesting.total {
+        token::close_account(
+            ctx.accounts
+                .close_vault_context()
+                .with_signer(&[&authority_seeds[..]]),
+        )?;
+        ctx.accounts
+            .vesting
+            .close(ctx.accounts.beneficiary.clone())?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
 #![allow(clippy::result_large_err)]
+// the generated cpi client does not carry the per instruction allows, the
+// program itself is linted without the cpi feature
+#![cfg_attr(feature = "cpi", allow(clippy::too_many_arguments))]
 
 mod errors;
 mod instructions;
@@ -12,18 +15,75 @@ declare_id!("Wen3vAue7f8CfWkNhHzyJ8qHyNJBzP8FH2zb7kFAZD3");
 pub mod wen3ex {
     use super::*;
 
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
-        msg!("initialize");
-        Ok(())
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        instructions::config::initialize(ctx)
+    }
+
+    // pause or resume create / exchange of the given MARKET_* bits, cancel is never paused
+    pub fn set_paused(ctx: Context<ConfigUpdate>, markets: u32, paused: bool) -> Result<()> {
+        instructions::config::set_paused(ctx, markets, paused)
+    }
+
+    // protocol fee on the sol side of exchanges, referral_share_bps of it goes
+    // to the referrer when one is passed
+    pub fn set_fees(
+        ctx: Context<ConfigUpdate>,
+        fee_bps: u32,
+        referral_share_bps: u32,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::config::set_fees(ctx, fee_bps, referral_share_bps, treasury)
+    }
+
+    pub fn propose_admin(ctx: Context<ConfigUpdate>, new_admin: Pubkey) -> Result<()> {
+        instructions::config::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<ConfigAcceptAdmin>) -> Result<()> {
+        instructions::config::accept_admin(ctx)
+    }
+
+    pub fn referrer_register(ctx: Context<ReferrerRegister>) -> Result<()> {
+        instructions::referral::register(ctx)
+    }
+
+    // mint registry, status is MINT_STATUS_ALLOWED or MINT_STATUS_DENIED
+    pub fn set_mint_status(ctx: Context<MintEntrySet>, status: u8) -> Result<()> {
+        instructions::registry::set_mint_status(ctx, status)
+    }
+
+    pub fn remove_mint_entry(ctx: Context<MintEntryRemove>) -> Result<()> {
+        instructions::registry::remove_mint_entry(ctx)
+    }
+
+    // MARKET_* bits enforcing the allowlist / denylist on create
+    pub fn set_mint_policy(
+        ctx: Context<ConfigUpdate>,
+        allowlist_markets: u32,
+        denylist_markets: u32,
+    ) -> Result<()> {
+        instructions::registry::set_mint_policy(ctx, allowlist_markets, denylist_markets)
+    }
+
+    // MARKET_* bits rejecting mints with a freeze authority on create
+    pub fn set_freeze_authority_policy(
+        ctx: Context<ConfigUpdate>,
+        freeze_authority_markets: u32,
+    ) -> Result<()> {
+        instructions::registry::set_freeze_authority_policy(ctx, freeze_authority_markets)
     }
 
     // market token to token
+    #[allow(clippy::too_many_arguments)]
     pub fn market_tt_create(
         ctx: Context<MarketTtCreate>,
         deposit_amount: u64,
         receive_amount: u64,
         deposit_token: Pubkey,
         receive_token: Pubkey,
+        bounty: u64,
+        expire_time: i64,
+        start_time: i64,
     ) -> Result<()> {
         instructions::market_tt::create(
             ctx,
@@ -31,6 +91,9 @@ pub mod wen3ex {
             receive_amount,
             deposit_token,
             receive_token,
+            bounty,
+            expire_time,
+            start_time,
         )
     }
 
@@ -38,23 +101,50 @@ pub mod wen3ex {
         instructions::market_tt::cancel(ctx)
     }
 
-    pub fn market_tt_exchange(ctx: Context<MarketTtExchange>) -> Result<()> {
+    // anyone closes an expired listing and takes its bounty
+    pub fn market_tt_crank(ctx: Context<MarketTtCrank>) -> Result<()> {
+        instructions::market_tt::crank(ctx)
+    }
+
+    pub fn market_tt_exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketTtExchange<'info>>,
+    ) -> Result<()> {
         instructions::market_tt::exchange(ctx)
     }
 
     // market token sol, sell token
+    #[allow(clippy::too_many_arguments)]
     pub fn market_ts_create(
         ctx: Context<MarketTsCreate>,
         token_amount: u64,
         sol_amount: u64,
+        bounty: u64,
+        expire_time: i64,
+        start_time: i64,
+        vesting_cliff: i64,
+        vesting_duration: i64,
     ) -> Result<()> {
-        instructions::market_ts::create(ctx, token_amount, sol_amount)
+        instructions::market_ts::create(
+            ctx,
+            token_amount,
+            sol_amount,
+            bounty,
+            expire_time,
+            start_time,
+            vesting_cliff,
+            vesting_duration,
+        )
     }
 
     pub fn market_ts_cancel(ctx: Context<MarketTsCancel>) -> Result<()> {
         instructions::market_ts::cancel(ctx)
     }
 
+    // anyone closes an expired listing and takes its bounty
+    pub fn market_ts_crank(ctx: Context<MarketTsCrank>) -> Result<()> {
+        instructions::market_ts::crank(ctx)
+    }
+
     pub fn market_ts_exchange<'info>(
         ctx: Context<'_, '_, '_, 'info, MarketTsExchange<'info>>,
     ) -> Result<()> {
@@ -63,19 +153,97 @@ pub mod wen3ex {
         instructions::market_ts::exchange(ctx)
     }
 
+    // take a listing during its presale, proof of (taker, allocation) against
+    // the sale phase merkle root
+    pub fn market_ts_exchange_allowlisted<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketTsExchangeAllowlisted<'info>>,
+        allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::market_ts::exchange_allowlisted(ctx, allocation, proof)
+    }
+
+    // take a vesting listing, the tokens unlock over time via claim_vested
+    pub fn market_ts_exchange_vested<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketTsExchangeVested<'info>>,
+    ) -> Result<()> {
+        instructions::market_ts::exchange_vested(ctx)
+    }
+
+    // peg a listing to a pyth price feed quoting sol per whole token, the
+    // oracle account Pubkey::default() unpegs it
+    pub fn market_ts_set_peg(
+        ctx: Context<MarketTsSetPeg>,
+        spread_bps: i32,
+        oracle_max_age: i64,
+        oracle_max_conf_bps: u32,
+    ) -> Result<()> {
+        instructions::market_ts::set_peg(ctx, spread_bps, oracle_max_age, oracle_max_conf_bps)
+    }
+
+    // the creator pays a fill's referrer referral_bps of the price, on top of
+    // the referrer's share of the protocol fee
+    pub fn market_ts_set_referral(
+        ctx: Context<MarketTsSetReferral>,
+        referral_bps: u32,
+    ) -> Result<()> {
+        instructions::market_ts::set_referral(ctx, referral_bps)
+    }
+
+    // take a pegged listing at the oracle price, at most max_sol_amount
+    pub fn market_ts_exchange_pegged<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketTsExchangePegged<'info>>,
+        max_sol_amount: u64,
+    ) -> Result<()> {
+        instructions::market_ts::exchange_pegged(ctx, max_sol_amount)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        instructions::vesting::claim(ctx)
+    }
+
+    // presale phase over a creator's token 2 sol listings of one mint
+    pub fn sale_phase_set(
+        ctx: Context<SalePhaseSet>,
+        merkle_root: [u8; 32],
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::sale_phase::set(ctx, merkle_root, start_time, end_time)
+    }
+
+    pub fn sale_phase_close(ctx: Context<SalePhaseClose>) -> Result<()> {
+        instructions::sale_phase::close(ctx)
+    }
+
     // market sol token, buy token
     pub fn market_st_create(
         ctx: Context<MarketStCreate>,
         token_amount: u64,
         sol_amount: u64,
+        bounty: u64,
+        expire_time: i64,
+        start_time: i64,
     ) -> Result<()> {
-        instructions::market_st::create(ctx, token_amount, sol_amount)
+        instructions::market_st::create(
+            ctx,
+            token_amount,
+            sol_amount,
+            bounty,
+            expire_time,
+            start_time,
+        )
     }
 
     pub fn market_st_cancel(ctx: Context<MarketStCancel>) -> Result<()> {
         instructions::market_st::cancel(ctx)
     }
 
+    // anyone closes an expired listing and takes its bounty
+    pub fn market_st_crank(ctx: Context<MarketStCrank>) -> Result<()> {
+        instructions::market_st::crank(ctx)
+    }
+
     pub fn market_st_exchange<'info>(
         ctx: Context<'_, '_, '_, 'info, MarketStExchange<'info>>,
     ) -> Result<()> {
@@ -87,8 +255,17 @@ pub mod wen3ex {
         ctx: Context<MarketNftToSolCreate>,
         nft_amount: u64,
         sol_amount: u64,
+        start_time: i64,
+    ) -> Result<()> {
+        instructions::market_ns::create(ctx, nft_amount, sol_amount, start_time)
+    }
+
+    // as market_ts_set_referral
+    pub fn market_nft_to_sol_set_referral(
+        ctx: Context<MarketNftToSolSetReferral>,
+        referral_bps: u32,
     ) -> Result<()> {
-        instructions::market_ns::create(ctx, nft_amount, sol_amount)
+        instructions::market_ns::set_referral(ctx, referral_bps)
     }
 
     pub fn market_nft_to_sol_cancel(ctx: Context<MarketNftToSolCancel>) -> Result<()> {
@@ -102,7 +279,340 @@ pub mod wen3ex {
         msg!("market_ts_exchange");
         instructions::market_ns::exchange(ctx)
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize {}
+    // market nft sol through token metadata's transfer, for programmable nfts
+    // and plain ones alike. the listing is a MarketNftToSolAccount
+    pub fn market_pnft_to_sol_create(
+        ctx: Context<MarketPnftToSolCreate>,
+        nft_amount: u64,
+        sol_amount: u64,
+        start_time: i64,
+    ) -> Result<()> {
+        instructions::market_pnft::create(ctx, nft_amount, sol_amount, start_time)
+    }
+
+    pub fn market_pnft_to_sol_cancel(ctx: Context<MarketPnftToSolCancel>) -> Result<()> {
+        instructions::market_pnft::cancel(ctx)
+    }
+
+    pub fn market_pnft_to_sol_exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketPnftToSolExchange<'info>>,
+    ) -> Result<()> {
+        instructions::market_pnft::exchange(ctx)
+    }
+
+    // market nft nft, nfts in escrow for a given mint or any nft of a verified
+    // collection. the offered nfts' vaults and destinations are the remaining
+    // accounts of cancel and exchange
+    pub fn market_swap_create(
+        ctx: Context<MarketSwapCreate>,
+        want_mint: Pubkey,
+        want_collection: Pubkey,
+        taker_sol: u64,
+        creator_sol: u64,
+    ) -> Result<()> {
+        instructions::market_swap::create(ctx, want_mint, want_collection, taker_sol, creator_sol)
+    }
+
+    pub fn market_swap_deposit(ctx: Context<MarketSwapDeposit>) -> Result<()> {
+        instructions::market_swap::deposit(ctx)
+    }
+
+    pub fn market_swap_cancel<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketSwapCancel<'info>>,
+    ) -> Result<()> {
+        instructions::market_swap::cancel(ctx)
+    }
+
+    pub fn market_swap_exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketSwapExchange<'info>>,
+    ) -> Result<()> {
+        instructions::market_swap::exchange(ctx)
+    }
+
+    // market compressed nft sol, the merkle proof of the leaf is the remaining
+    // accounts and root the tree's current root. indexed under the merkle tree
+    // as its mint
+    #[allow(clippy::too_many_arguments)]
+    pub fn market_cnft_to_sol_create<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketCnftCreate<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        sol_amount: u64,
+        expire_time: i64,
+        start_time: i64,
+    ) -> Result<()> {
+        instructions::market_cnft::create(
+            ctx,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+            sol_amount,
+            expire_time,
+            start_time,
+        )
+    }
+
+    pub fn market_cnft_to_sol_cancel<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketCnftCancel<'info>>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        instructions::market_cnft::cancel(ctx, root)
+    }
+
+    // the referrer pair leads the proof when `referred`
+    pub fn market_cnft_to_sol_exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketCnftExchange<'info>>,
+        root: [u8; 32],
+        referred: bool,
+    ) -> Result<()> {
+        instructions::market_cnft::exchange(ctx, root, referred)
+    }
+
+    // market token sol without custody, the creator approves the delegate PDA
+    // and the tokens are pulled from their token account on exchange
+    pub fn market_delegated_create(
+        ctx: Context<MarketDelegatedCreate>,
+        token_amount: u64,
+        sol_amount: u64,
+        expire_time: i64,
+    ) -> Result<()> {
+        instructions::market_delegated::create(ctx, token_amount, sol_amount, expire_time)
+    }
+
+    pub fn market_delegated_cancel(ctx: Context<MarketDelegatedCancel>) -> Result<()> {
+        instructions::market_delegated::cancel(ctx)
+    }
+
+    // anyone closes a listing that is expired or its token account no longer covers
+    pub fn market_delegated_close_stale(ctx: Context<MarketDelegatedCloseStale>) -> Result<()> {
+        instructions::market_delegated::close_stale(ctx)
+    }
+
+    pub fn market_delegated_exchange<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarketDelegatedExchange<'info>>,
+    ) -> Result<()> {
+        instructions::market_delegated::exchange(ctx)
+    }
+
+    // take several token 2 sol / nft 2 sol listings in one go, listings gone
+    // meanwhile, in presale or over budget are skipped
+    pub fn sweep<'info>(
+        ctx: Context<'_, '_, '_, 'info, Sweep<'info>>,
+        max_sol: u64,
+        max_quantity: u64,
+    ) -> Result<()> {
+        instructions::market_sweep::sweep(ctx, max_sol, max_quantity)
+    }
+
+    // fill up to quantity from the listings of one mint in remaining_accounts,
+    // best price first. prices are lamports per whole token, checked on the
+    // average of what was filled. a [referrer, referrer_account] pair may
+    // follow the listings
+    pub fn route_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, Route<'info>>,
+        quantity: u64,
+        max_avg_price: u64,
+    ) -> Result<()> {
+        instructions::market_route::route_buy(ctx, quantity, max_avg_price)
+    }
+
+    pub fn route_sell<'info>(
+        ctx: Context<'_, '_, '_, 'info, Route<'info>>,
+        quantity: u64,
+        min_avg_price: u64,
+    ) -> Result<()> {
+        instructions::market_route::route_sell(ctx, quantity, min_avg_price)
+    }
+
+    // index of the open listings of a mint (key is the mint) or of a creator
+    // (key is the wallet), kept up to date by every create, cancel and exchange
+    pub fn listing_index_create(ctx: Context<ListingIndexCreate>, by_creator: bool) -> Result<()> {
+        instructions::listing_index::create(ctx, by_creator)
+    }
+
+    // offers on an nft, listed (listing is the MarketNftToSolAccount) or not
+    // (listing is Pubkey::default())
+    pub fn offer_create(
+        ctx: Context<OfferCreate>,
+        nft_amount: u64,
+        sol_amount: u64,
+        listing: Pubkey,
+    ) -> Result<()> {
+        instructions::market_offer::create(ctx, nft_amount, sol_amount, listing)
+    }
+
+    pub fn offer_withdraw(ctx: Context<OfferWithdraw>) -> Result<()> {
+        instructions::market_offer::withdraw(ctx)
+    }
+
+    pub fn offer_accept_listing<'info>(
+        ctx: Context<'_, '_, '_, 'info, OfferAcceptListing<'info>>,
+    ) -> Result<()> {
+        instructions::market_offer::accept_listing(ctx)
+    }
+
+    pub fn offer_accept_wallet<'info>(
+        ctx: Context<'_, '_, '_, 'info, OfferAcceptWallet<'info>>,
+    ) -> Result<()> {
+        instructions::market_offer::accept_wallet(ctx)
+    }
+
+    // order book for a base / quote pair, prices are quote atoms per lot,
+    // orders are at least min_lots
+    pub fn order_book_create(
+        ctx: Context<OrderBookCreate>,
+        base_lot_size: u64,
+        min_lots: u64,
+    ) -> Result<()> {
+        instructions::market_order_book::create(ctx, base_lot_size, min_lots)
+    }
+
+    pub fn place_order<'info>(
+        ctx: Context<'_, '_, '_, 'info, OrderBookPlace<'info>>,
+        side: u8,
+        price: u64,
+        quantity: u64,
+        order_type: u8,
+    ) -> Result<()> {
+        instructions::market_order_book::place_order(ctx, side, price, quantity, order_type)
+    }
+
+    pub fn cancel_order(ctx: Context<OrderBookCancel>, order_id: u64) -> Result<()> {
+        instructions::market_order_book::cancel_order(ctx, order_id)
+    }
+
+    // withdraw what fills and evictions credited to the owner's balance
+    pub fn order_book_settle(ctx: Context<OrderBookSettle>) -> Result<()> {
+        instructions::market_order_book::settle(ctx)
+    }
+
+    // fixed price primary sale, quantities and caps are in units of unit_size
+    // token atoms, price is lamports per unit
+    #[allow(clippy::too_many_arguments)]
+    pub fn launchpad_create(
+        ctx: Context<LaunchpadCreate>,
+        unit_size: u64,
+        price: u64,
+        supply: u64,
+        total_cap: u64,
+        wallet_cap: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::market_launchpad::create(
+            ctx, unit_size, price, supply, total_cap, wallet_cap, start_time, end_time,
+        )
+    }
+
+    pub fn launchpad_buy<'info>(
+        ctx: Context<'_, '_, '_, 'info, LaunchpadBuy<'info>>,
+        quantity: u64,
+    ) -> Result<()> {
+        instructions::market_launchpad::buy(ctx, quantity)
+    }
+
+    pub fn launchpad_withdraw(ctx: Context<LaunchpadWithdraw>) -> Result<()> {
+        instructions::market_launchpad::withdraw(ctx)
+    }
+
+    // escrow for trades with an off chain leg, id tells apart the escrows of
+    // one buyer. the buyer or the arbiter releases to the seller, the seller
+    // or the arbiter refunds, past the deadline the buyer may refund too
+    pub fn escrow_create_sol(
+        ctx: Context<EscrowCreateSol>,
+        id: u64,
+        amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::market_escrow::create_sol(ctx, id, amount, deadline)
+    }
+
+    pub fn escrow_create_token(
+        ctx: Context<EscrowCreateToken>,
+        id: u64,
+        amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::market_escrow::create_token(ctx, id, amount, deadline)
+    }
+
+    pub fn escrow_mark_delivered(ctx: Context<EscrowMarkDelivered>) -> Result<()> {
+        instructions::market_escrow::mark_delivered(ctx)
+    }
+
+    pub fn escrow_release_sol(ctx: Context<EscrowSettleSol>) -> Result<()> {
+        instructions::market_escrow::release_sol(ctx)
+    }
+
+    pub fn escrow_refund_sol(ctx: Context<EscrowSettleSol>) -> Result<()> {
+        instructions::market_escrow::refund_sol(ctx)
+    }
+
+    pub fn escrow_release_token(ctx: Context<EscrowSettleToken>) -> Result<()> {
+        instructions::market_escrow::release_token(ctx)
+    }
+
+    pub fn escrow_refund_token(ctx: Context<EscrowSettleToken>) -> Result<()> {
+        instructions::market_escrow::refund_token(ctx)
+    }
+
+    // constant product pool for a token pair, mint_a must sort before mint_b.
+    // the swap fee stays in the pool for the liquidity providers. make the
+    // first deposit in the same transaction, a pool whose vaults got tokens
+    // before it refuses deposits
+    pub fn pool_create(ctx: Context<PoolCreate>, fee_bps: u32) -> Result<()> {
+        instructions::market_pool::create(ctx, fee_bps)
+    }
+
+    // takes at most amount_a / amount_b at the pool ratio, the first deposit
+    // locks POOL_MINIMUM_LIQUIDITY of its lp
+    pub fn pool_deposit(
+        ctx: Context<PoolLiquidity>,
+        amount_a: u64,
+        amount_b: u64,
+        min_lp: u64,
+    ) -> Result<()> {
+        instructions::market_pool::deposit(ctx, amount_a, amount_b, min_lp)
+    }
+
+    pub fn pool_withdraw(
+        ctx: Context<PoolLiquidity>,
+        lp_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    ) -> Result<()> {
+        instructions::market_pool::withdraw(ctx, lp_amount, min_amount_a, min_amount_b)
+    }
+
+    pub fn pool_swap(
+        ctx: Context<PoolSwap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        a_to_b: bool,
+    ) -> Result<()> {
+        instructions::market_pool::swap(ctx, amount_in, min_amount_out, a_to_b)
+    }
+
+    // realloc an older market account layout to the current one
+    pub fn migrate_market(ctx: Context<MarketMigrate>) -> Result<()> {
+        instructions::market_migrate::migrate(ctx)
+    }
+
+    // close a tt, ts or ns listing whose vault no longer holds the deposit,
+    // by its creator or by anyone after RECLAIM_GRACE_PERIOD, for the creator
+    pub fn reclaim_market(ctx: Context<MarketReclaim>) -> Result<()> {
+        instructions::market_reclaim::reclaim(ctx)
+    }
+
+    // the unit price of a tt, ts or st listing, normalized by the decimals of
+    // both sides, as return data
+    pub fn market_unit_price(ctx: Context<MarketUnitPrice>) -> Result<UnitPrice> {
+        instructions::market_price::unit_price(ctx)
+    }
+}
@@ -0,0 +1,68 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Connection, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+
+import { Wen3ex } from "../target/types/wen3ex";
+
+// seeds shared by the test files, see instructions/constants.rs
+export const VAULT_AUTHORITY_SEED = "vault-authority-seed";
+export const VAULT_TOKEN_SOL_SEED = "vault-token-2-sol-seed";
+export const VAULT_SOL_TOKEN_SEED = "vault-sol-2-token-seed";
+export const CONFIG_SEED = "config-seed";
+export const MINT_ENTRY_SEED = "mint-entry-seed";
+export const MINT_INDEX_SEED = "mint-index-seed";
+export const CREATOR_INDEX_SEED = "creator-index-seed";
+export const SALE_PHASE_SEED = "sale-phase-seed";
+
+export const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+export function getConfigPDA(program: Program<Wen3ex>) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from(CONFIG_SEED)],
+    program.programId
+  );
+}
+
+// the provider wallet becomes the admin. `anchor test` loads the program
+// without an upgrade authority, so build it with the localnet feature:
+// `anchor test -- --features localnet`
+export async function initConfig(program: Program<Wen3ex>) {
+  const provider = program.provider as anchor.AnchorProvider;
+  const [configPDA] = getConfigPDA(program);
+  if (await provider.connection.getAccountInfo(configPDA)) {
+    return;
+  }
+  const [programDataPDA] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+  await program.methods
+    .initialize()
+    .accounts({
+      config: configPDA,
+      program: program.programId,
+      programData: programDataPDA,
+      admin: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .rpc();
+}
+
+export async function airDrop(
+  connection: Connection,
+  pubkey: PublicKey,
+  num: number
+) {
+  const airdropSignature = await connection.requestAirdrop(
+    pubkey,
+    num * LAMPORTS_PER_SOL
+  );
+  const latestBlockHash = await connection.getLatestBlockhash();
+  await connection.confirmTransaction({
+    blockhash: latestBlockHash.blockhash,
+    lastValidBlockHeight: latestBlockHash.lastValidBlockHeight,
+    signature: airdropSignature,
+  });
+}
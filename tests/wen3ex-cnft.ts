@@ -0,0 +1,343 @@
+import * as fs from "fs";
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+import { BubblegumStandIn } from "../target/types/bubblegum_stand_in";
+
+const CNFT_LISTING_SEED = "cnft-listing-seed";
+const REFERRER_SEED = "referrer-seed";
+// the stand-in is not part of the anchor workspace, `yarn build:stand-in`
+// builds it and its idl, Anchor.toml loads it at the bubblegum address
+const BUBBLEGUM_ID = new PublicKey(
+  "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRUUY"
+);
+const SPL_NOOP_ID = new PublicKey("noop8ytexvkpCuqbf6FB89BSuNemHtPRqaNC31GWivW");
+const SPL_ACCOUNT_COMPRESSION_ID = new PublicKey(
+  "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+);
+// leaves of the stand-in tree, nodes[8 + index]
+const LEAF_OFFSET = 8;
+
+describe("wen3ex cnft", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+  const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+  const bubblegum = new Program<BubblegumStandIn>(
+    JSON.parse(fs.readFileSync("target/idl/bubblegum_stand_in.json", "utf8")),
+    BUBBLEGUM_ID,
+    provider
+  );
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const referrerKP = Keypair.generate();
+  const treeKP = Keypair.generate();
+  const dataHash = Array.from(Buffer.alloc(32, 1));
+  const creatorHash = Array.from(Buffer.alloc(32, 2));
+  const solAmount = LAMPORTS_PER_SOL / 10;
+  // the first leaves of the tree, all the creator's
+  const [soldNonce, cancelledNonce, laterNonce] = [0, 1, 2];
+
+  it("cnft before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    await airDrop(connection, referrerKP.publicKey, 1);
+    await bubblegum.methods
+      .createTree()
+      .accounts({
+        merkleTree: treeKP.publicKey,
+        payer: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([treeKP, creatorKP])
+      .rpc();
+    for (const _ of [soldNonce, cancelledNonce, laterNonce]) {
+      await bubblegum.methods
+        .mint(creatorKP.publicKey, dataHash, creatorHash)
+        .accounts({ merkleTree: treeKP.publicKey })
+        .rpc();
+    }
+    // listings of a tree are indexed under it, in place of a mint
+    await program.methods
+      .listingIndexCreate(false)
+      .accounts({
+        index: getPDA(MINT_INDEX_SEED, treeKP.publicKey),
+        key: treeKP.publicKey,
+        payer: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creatorKP])
+      .rpc();
+    await program.methods
+      .referrerRegister()
+      .accounts({
+        referrerAccount: getPDA(REFERRER_SEED, referrerKP.publicKey),
+        referrer: referrerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([referrerKP])
+      .rpc();
+  });
+
+  it("Create with a wrong proof fails", async () => {
+    const [root, proof] = await rootAndProof(soldNonce);
+    proof[0] = Keypair.generate().publicKey;
+    try {
+      await createListing(soldNonce, root, proof);
+      assert.fail("create should fail with a wrong proof");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidProof");
+    }
+  });
+
+  it("A listing is not bought before its start time", async () => {
+    const later = (await now()) + 3600;
+    await createListing(laterNonce, ...(await rootAndProof(laterNonce)), later);
+    try {
+      await exchange(laterNonce, null);
+      assert.fail("exchange should fail before the start time");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NotYetOpen");
+    }
+  });
+
+  it("List, then buy a compressed nft with a referrer", async () => {
+    await createListing(soldNonce, ...(await rootAndProof(soldNonce)));
+    const listing = getListingPDA(soldNonce);
+    const market = await program.account.marketCnftToSolAccount.fetch(
+      listing
+    );
+    expect(market.nonce.toNumber()).to.eq(soldNonce);
+    expect(await indexedListings()).to.include(listing.toBase58());
+
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    const referrerSol = await connection.getBalance(referrerKP.publicKey);
+    await setFees(100, 5_000);
+    try {
+      await exchange(soldNonce, referrerKP);
+    } finally {
+      await setFees(0, 0);
+    }
+
+    expect(await connection.getAccountInfo(listing)).to.null;
+    expect(await indexedListings()).to.not.include(listing.toBase58());
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(creatorSol);
+    // half of the 1% fee
+    expect(await connection.getBalance(referrerKP.publicKey)).to.eq(
+      referrerSol + solAmount / 200
+    );
+    // the taker owns the leaf now, so it proves with the taker as owner
+    const tree = await bubblegum.account.tree.fetch(treeKP.publicKey);
+    const takerLeaf = await leafOf(takerKP.publicKey, soldNonce);
+    assert.deepEqual(tree.nodes[LEAF_OFFSET + soldNonce], takerLeaf);
+  });
+
+  it("List, then cancel a compressed nft", async () => {
+    await createListing(
+      cancelledNonce,
+      ...(await rootAndProof(cancelledNonce))
+    );
+    const listing = getListingPDA(cancelledNonce);
+    const [root, proof] = await rootAndProof(cancelledNonce);
+    await program.methods
+      .marketCnftToSolCancel(root)
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: listing,
+        vaultAuthority: getVaultAuthorityPDA(listing),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        mintIndex: getPDA(MINT_INDEX_SEED, treeKP.publicKey),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        cnft: cnftAccounts(),
+      })
+      .remainingAccounts(proofAccounts(proof))
+      .signers([creatorKP])
+      .rpc();
+
+    expect(await connection.getAccountInfo(listing)).to.null;
+    expect(await indexedListings()).to.not.include(listing.toBase58());
+    const tree = await bubblegum.account.tree.fetch(treeKP.publicKey);
+    const creatorLeaf = await leafOf(creatorKP.publicKey, cancelledNonce);
+    assert.deepEqual(tree.nodes[LEAF_OFFSET + cancelledNonce], creatorLeaf);
+  });
+
+  // the referrer pair goes ahead of the proof
+  async function exchange(nonce: number, referrer: Keypair | null) {
+    const listing = getListingPDA(nonce);
+    const [root, proof] = await rootAndProof(nonce);
+    const referrerAccounts = referrer
+      ? [
+          { pubkey: referrer.publicKey, isSigner: false, isWritable: true },
+          {
+            pubkey: getPDA(REFERRER_SEED, referrer.publicKey),
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : [];
+    await program.methods
+      .marketCnftToSolExchange(root, referrer !== null)
+      .accounts({
+        taker: takerKP.publicKey,
+        creator: creatorKP.publicKey,
+        marketAccount: listing,
+        vaultAuthority: getVaultAuthorityPDA(listing),
+        config: getConfigPDA(program)[0],
+        treasury: admin,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        mintIndex: getPDA(MINT_INDEX_SEED, treeKP.publicKey),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        cnft: cnftAccounts(),
+      })
+      .remainingAccounts([...referrerAccounts, ...proofAccounts(proof)])
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function setFees(feeBps: number, referralShareBps: number) {
+    await program.methods
+      .setFees(feeBps, referralShareBps, admin)
+      .accounts({ config: getConfigPDA(program)[0], admin })
+      .rpc();
+  }
+
+  async function indexedListings() {
+    const index = await program.account.listingIndexAccount.fetch(
+      getPDA(MINT_INDEX_SEED, treeKP.publicKey)
+    );
+    return index.listings.map((key) => key.toBase58());
+  }
+
+  // the validator's clock, which the start time is checked against
+  async function now() {
+    const blockTime = await connection.getBlockTime(await connection.getSlot());
+    return blockTime ?? Math.floor(Date.now() / 1000);
+  }
+
+  async function createListing(
+    nonce: number,
+    root: number[],
+    proof: PublicKey[],
+    startTime = 0
+  ) {
+    const listing = getListingPDA(nonce);
+    await program.methods
+      .marketCnftToSolCreate(
+        root,
+        dataHash,
+        creatorHash,
+        new anchor.BN(nonce),
+        nonce,
+        new anchor.BN(solAmount),
+        new anchor.BN(0),
+        new anchor.BN(startTime)
+      )
+      .accounts({
+        marketAccount: listing,
+        creator: creatorKP.publicKey,
+        leafDelegate: creatorKP.publicKey,
+        vaultAuthority: getVaultAuthorityPDA(listing),
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+        mintIndex: getPDA(MINT_INDEX_SEED, treeKP.publicKey),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        cnft: cnftAccounts(),
+      })
+      .remainingAccounts(proofAccounts(proof))
+      .signers([creatorKP])
+      .rpc();
+  }
+
+  // the current root and the siblings of the leaf, bottom up
+  async function rootAndProof(index: number): Promise<[number[], PublicKey[]]> {
+    const tree = await bubblegum.account.tree.fetch(treeKP.publicKey);
+    const proof: PublicKey[] = [];
+    for (let i = LEAF_OFFSET + index; i > 1; i >>= 1) {
+      proof.push(new PublicKey(tree.nodes[i ^ 1]));
+    }
+    return [tree.nodes[1], proof];
+  }
+
+  // keccak(owner, delegate, nonce, data_hash, creator_hash), read back from a
+  // simulated mint into a scratch tree
+  async function leafOf(owner: PublicKey, nonce: number) {
+    const scratchKP = Keypair.generate();
+    await bubblegum.methods
+      .createTree()
+      .accounts({
+        merkleTree: scratchKP.publicKey,
+        payer: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([scratchKP, creatorKP])
+      .rpc();
+    for (let i = 0; i <= nonce; i++) {
+      await bubblegum.methods
+        .mint(owner, dataHash, creatorHash)
+        .accounts({ merkleTree: scratchKP.publicKey })
+        .rpc();
+    }
+    const scratch = await bubblegum.account.tree.fetch(scratchKP.publicKey);
+    return scratch.nodes[LEAF_OFFSET + nonce];
+  }
+
+  function cnftAccounts() {
+    return {
+      treeAuthority: PublicKey.findProgramAddressSync(
+        [treeKP.publicKey.toBuffer()],
+        bubblegum.programId
+      )[0],
+      merkleTree: treeKP.publicKey,
+      logWrapper: SPL_NOOP_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_ID,
+      bubblegumProgram: bubblegum.programId,
+    };
+  }
+
+  function proofAccounts(proof: PublicKey[]) {
+    return proof.map((pubkey) => ({
+      pubkey,
+      isWritable: false,
+      isSigner: false,
+    }));
+  }
+
+  function getListingPDA(nonce: number) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(CNFT_LISTING_SEED),
+        treeKP.publicKey.toBuffer(),
+        new anchor.BN(nonce).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  function getVaultAuthorityPDA(listing: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), listing.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
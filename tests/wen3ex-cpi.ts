@@ -0,0 +1,259 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  VAULT_SOL_TOKEN_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+import { Wen3exCpiExample } from "../target/types/wen3ex_cpi_example";
+
+const DESK_SEED = "desk-seed";
+
+describe("wen3ex cpi", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+  const example = anchor.workspace
+    .Wen3exCpiExample as Program<Wen3exCpiExample>;
+
+  const ownerKP = Keypair.generate();
+  const creatorKP = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  const [deskPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from(DESK_SEED), ownerKP.publicKey.toBuffer()],
+    example.programId
+  );
+  let mint: PublicKey;
+
+  it("cpi before", async () => {
+    await initConfig(program);
+    await airDrop(connection, ownerKP.publicKey, 1);
+    await airDrop(connection, creatorKP.publicKey, 3);
+    // the desk is a system account of the example program
+    await airDrop(connection, deskPDA, 2);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      mintAuthority.publicKey,
+      null,
+      0
+    );
+    for (const owner of [creatorKP.publicKey, deskPDA]) {
+      const ata = await getATA(owner);
+      await mintTo(connection, creatorKP, mint, ata.address, mintAuthority, 20);
+    }
+  });
+
+  it("Desk lists tokens for sol", async () => {
+    const marketKP = Keypair.generate();
+    await example.methods
+      .list(new anchor.BN(10), new anchor.BN(LAMPORTS_PER_SOL / 10))
+      .accounts({
+        owner: ownerKP.publicKey,
+        desk: deskPDA,
+        market: {
+          marketAccount: marketKP.publicKey,
+          vaultTokenAccount: getVaultPDA(VAULT_TOKEN_SOL_SEED, marketKP)[0],
+          deskTokenAccount: (await getATA(deskPDA)).address,
+          mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          config: getConfigPDA(program)[0],
+          mintEntry: getPDA(MINT_ENTRY_SEED, mint)[0],
+          mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, deskPDA)[0],
+        },
+        wen3exProgram: program.programId,
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(marketKP),
+      ])
+      .signers([marketKP, ownerKP])
+      .rpc();
+
+    const market = await program.account.marketTsAccount.fetch(
+      marketKP.publicKey
+    );
+    assert.ok(market.creator.equals(deskPDA));
+    expect(market.tokenAmount.toNumber()).to.eq(10);
+  });
+
+  it("Desk buys a token 2 sol listing", async () => {
+    const marketKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(5),
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketKP.publicKey,
+        vaultTokenAccount: getVaultPDA(VAULT_TOKEN_SOL_SEED, marketKP)[0],
+        creatorTokenAccount: (await getATA(creatorKP.publicKey)).address,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint)[0],
+        mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(marketKP),
+      ])
+      .signers([marketKP, creatorKP])
+      .rpc();
+
+    await example.methods
+      .buy()
+      .accounts({
+        owner: ownerKP.publicKey,
+        desk: deskPDA,
+        market: await deskExchangeAccounts(marketKP),
+        salePhase: PublicKey.findProgramAddressSync(
+          [
+            Buffer.from(SALE_PHASE_SEED),
+            creatorKP.publicKey.toBuffer(),
+            mint.toBuffer(),
+          ],
+          program.programId
+        )[0],
+        wen3exProgram: program.programId,
+      })
+      .signers([ownerKP])
+      .rpc();
+
+    // 20 minted, 10 listed, 5 bought
+    const deskToken = await getAccount(
+      connection,
+      (await getATA(deskPDA)).address
+    );
+    expect(Number(deskToken.amount)).to.eq(15);
+    expect(await connection.getAccountInfo(marketKP.publicKey)).to.null;
+  });
+
+  it("Desk sells into a sol 2 token listing", async () => {
+    const marketKP = Keypair.generate();
+    const creatorAta = await getATA(creatorKP.publicKey);
+    await program.methods
+      .marketStCreate(
+        new anchor.BN(5),
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketKP.publicKey,
+        vaultTokenAccount: getVaultPDA(VAULT_SOL_TOKEN_SEED, marketKP)[0],
+        creatorTokenAccount: creatorAta.address,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint)[0],
+        mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+      })
+      .preInstructions([
+        await program.account.marketStAccount.createInstruction(marketKP),
+      ])
+      .signers([marketKP, creatorKP])
+      .rpc();
+
+    const deskSol = await connection.getBalance(deskPDA);
+    await example.methods
+      .sell()
+      .accounts({
+        owner: ownerKP.publicKey,
+        desk: deskPDA,
+        market: await deskExchangeAccounts(marketKP, VAULT_SOL_TOKEN_SEED),
+        creatorTokenAccount: creatorAta.address,
+        wen3exProgram: program.programId,
+      })
+      .signers([ownerKP])
+      .rpc();
+
+    const deskToken = await getAccount(
+      connection,
+      (await getATA(deskPDA)).address
+    );
+    expect(Number(deskToken.amount)).to.eq(10);
+    expect(await connection.getBalance(deskPDA)).to.gt(deskSol);
+  });
+
+  async function deskExchangeAccounts(
+    marketKP: Keypair,
+    vaultSeed: string = VAULT_TOKEN_SOL_SEED
+  ) {
+    return {
+      deskTokenAccount: (await getATA(deskPDA)).address,
+      creator: creatorKP.publicKey,
+      marketAccount: marketKP.publicKey,
+      vaultTokenAccount: getVaultPDA(vaultSeed, marketKP)[0],
+      mint,
+      vaultAuthority: PublicKey.findProgramAddressSync(
+        [Buffer.from(VAULT_AUTHORITY_SEED), marketKP.publicKey.toBuffer()],
+        program.programId
+      )[0],
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      config: getConfigPDA(program)[0],
+      treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+      mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+      creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+    };
+  }
+
+  function getVaultPDA(seed: string, marketKP: Keypair) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), marketKP.publicKey.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(owner: PublicKey) {
+    return await getOrCreateAssociatedTokenAccount(
+      connection,
+      creatorKP,
+      mint,
+      owner,
+      true
+    );
+  }
+});
@@ -0,0 +1,187 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  approve,
+  getAccount,
+  revoke,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const DELEGATE_SEED = "delegate-seed";
+
+describe("wen3ex delegated", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  const [delegatePDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from(DELEGATE_SEED)],
+    program.programId
+  );
+  let mint: PublicKey;
+  let creatorAta: PublicKey;
+
+  it("delegated before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      mintAuthority.publicKey,
+      null,
+      0
+    );
+    creatorAta = (await getATA(creatorKP.publicKey)).address;
+    await mintTo(connection, creatorKP, mint, creatorAta, mintAuthority, 20);
+  });
+
+  it("Create without approval fails", async () => {
+    try {
+      await createListing(Keypair.generate(), 10);
+      assert.fail("create should fail without a delegation");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("DelegationRevoked");
+    }
+  });
+
+  it("Exchange pulls the tokens from the creator's account", async () => {
+    await approve(connection, creatorKP, creatorAta, delegatePDA, creatorKP, 20);
+    const marketKP = Keypair.generate();
+    await createListing(marketKP, 10);
+    // nothing moved yet
+    let creatorToken = await getAccount(connection, creatorAta);
+    expect(Number(creatorToken.amount)).to.eq(20);
+
+    await exchange(marketKP);
+    const takerToken = await getAccount(
+      connection,
+      (await getATA(takerKP.publicKey)).address
+    );
+    expect(Number(takerToken.amount)).to.eq(10);
+    creatorToken = await getAccount(connection, creatorAta);
+    expect(Number(creatorToken.amount)).to.eq(10);
+    expect(await connection.getAccountInfo(marketKP.publicKey)).to.null;
+  });
+
+  it("Revoked listing fails cleanly and anyone closes it", async () => {
+    const marketKP = Keypair.generate();
+    await createListing(marketKP, 10);
+    try {
+      await closeStale(marketKP);
+      assert.fail("close_stale should fail while covered");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("ListingNotStale");
+    }
+
+    await revoke(connection, creatorKP, creatorAta, creatorKP);
+    const takerSol = await connection.getBalance(takerKP.publicKey);
+    try {
+      await exchange(marketKP);
+      assert.fail("exchange should fail once revoked");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("DelegationRevoked");
+    }
+    expect(await connection.getBalance(takerKP.publicKey)).to.eq(takerSol);
+
+    await closeStale(marketKP);
+    expect(await connection.getAccountInfo(marketKP.publicKey)).to.null;
+  });
+
+  async function createListing(marketKP: Keypair, tokenAmount: number) {
+    await program.methods
+      .marketDelegatedCreate(
+        new anchor.BN(tokenAmount),
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketKP.publicKey,
+        source: creatorAta,
+        mint,
+        creator: creatorKP.publicKey,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint)[0],
+        mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .preInstructions([
+        await program.account.marketDelegatedAccount.createInstruction(
+          marketKP
+        ),
+      ])
+      .signers([marketKP, creatorKP])
+      .rpc();
+  }
+
+  async function exchange(marketKP: Keypair) {
+    await program.methods
+      .marketDelegatedExchange()
+      .accounts({
+        taker: takerKP.publicKey,
+        takerTokenAccount: (await getATA(takerKP.publicKey)).address,
+        creator: creatorKP.publicKey,
+        marketAccount: marketKP.publicKey,
+        source: creatorAta,
+        delegate: delegatePDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+      })
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function closeStale(marketKP: Keypair) {
+    await program.methods
+      .marketDelegatedCloseStale()
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: marketKP.publicKey,
+        source: creatorAta,
+        mintIndex: getPDA(MINT_INDEX_SEED, mint)[0],
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey)[0],
+      })
+      // paid by the provider wallet, not the creator
+      .rpc();
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(owner: PublicKey) {
+    return await getOrCreateAssociatedTokenAccount(
+      connection,
+      creatorKP,
+      mint,
+      owner
+    );
+  }
+});
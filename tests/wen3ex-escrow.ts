@@ -0,0 +1,137 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import { getConfigPDA, initConfig, airDrop } from "./helpers";
+
+const ESCROW_SEED = "escrow-seed";
+
+describe("wen3ex escrow", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const buyerKP = Keypair.generate();
+  const sellerKP = Keypair.generate();
+  const arbiterKP = Keypair.generate();
+
+  const amount = LAMPORTS_PER_SOL / 2;
+
+  it("escrow before", async () => {
+    await initConfig(program);
+    await airDrop(connection, buyerKP.publicKey, 2);
+    await airDrop(connection, arbiterKP.publicKey, 1);
+  });
+
+  it("Arbiter releases to the seller", async () => {
+    await createSolEscrow(1);
+
+    await program.methods
+      .escrowMarkDelivered()
+      .accounts({
+        seller: sellerKP.publicKey,
+        escrow: getEscrowPDA(1)[0],
+      })
+      .signers([sellerKP])
+      .rpc();
+    const escrow = await program.account.escrowAccount.fetch(
+      getEscrowPDA(1)[0]
+    );
+    expect(escrow.delivered).to.eq(true);
+
+    await settleSol("escrowReleaseSol", 1, arbiterKP);
+    expect(await connection.getBalance(sellerKP.publicKey)).to.eq(amount);
+    expect(await connection.getAccountInfo(getEscrowPDA(1)[0])).to.null;
+  });
+
+  it("Buyer cannot refund before the deadline", async () => {
+    await createSolEscrow(2);
+    try {
+      await settleSol("escrowRefundSol", 2, buyerKP);
+      assert.fail("refund should fail before the deadline");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidAuthority");
+    }
+
+    // the arbiter may
+    const buyerSol = await connection.getBalance(buyerKP.publicKey);
+    await settleSol("escrowRefundSol", 2, arbiterKP);
+    expect(await connection.getBalance(buyerKP.publicKey)).to.gt(
+      buyerSol + amount
+    );
+  });
+
+  it("Once delivered, only the arbiter refunds", async () => {
+    await createSolEscrow(3);
+    await program.methods
+      .escrowMarkDelivered()
+      .accounts({
+        seller: sellerKP.publicKey,
+        escrow: getEscrowPDA(3)[0],
+      })
+      .signers([sellerKP])
+      .rpc();
+
+    try {
+      await settleSol("escrowRefundSol", 3, sellerKP);
+      assert.fail("refund by the seller should fail once delivered");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidAuthority");
+    }
+
+    const buyerSol = await connection.getBalance(buyerKP.publicKey);
+    await settleSol("escrowRefundSol", 3, arbiterKP);
+    expect(await connection.getBalance(buyerKP.publicKey)).to.gt(
+      buyerSol + amount
+    );
+  });
+
+  async function createSolEscrow(id: number) {
+    await program.methods
+      .escrowCreateSol(
+        new anchor.BN(id),
+        new anchor.BN(amount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600)
+      )
+      .accounts({
+        escrow: getEscrowPDA(id)[0],
+        buyer: buyerKP.publicKey,
+        seller: sellerKP.publicKey,
+        arbiter: arbiterKP.publicKey,
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([buyerKP])
+      .rpc();
+  }
+
+  async function settleSol(
+    method: "escrowReleaseSol" | "escrowRefundSol",
+    id: number,
+    authority: Keypair
+  ) {
+    await program.methods[method]()
+      .accounts({
+        authority: authority.publicKey,
+        buyer: buyerKP.publicKey,
+        seller: sellerKP.publicKey,
+        escrow: getEscrowPDA(id)[0],
+      })
+      .signers([authority])
+      .rpc();
+  }
+
+  function getEscrowPDA(id: number) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(ESCROW_SEED),
+        buyerKP.publicKey.toBuffer(),
+        new anchor.BN(id).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+  }
+});
@@ -0,0 +1,241 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+describe("wen3ex expiry", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const crankerKP = Keypair.generate();
+  const tokenAmount = 10;
+  const bounty = LAMPORTS_PER_SOL / 100;
+  let mint: PublicKey;
+  let creatorToken: PublicKey;
+
+  it("expiry before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, crankerKP.publicKey, 1);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    creatorToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        mint,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(connection, creatorKP, mint, creatorToken, creatorKP, 100);
+  });
+
+  it("A listing is not cranked before it expires", async () => {
+    const listing = await createListing((await now()) + 3600);
+    try {
+      await crank(listing);
+      assert.fail("crank should fail before the expire time");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("ListingNotExpired");
+    }
+  });
+
+  it("Crank an expired listing, the cranker takes the bounty", async () => {
+    const expireTime = (await now()) + 2;
+    const listing = await createListing(expireTime);
+    while ((await now()) <= expireTime) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    const marketSol = await connection.getBalance(listing.publicKey);
+    const vaultSol = await connection.getBalance(getVaultPDA(listing));
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    const crankerSol = await connection.getBalance(crankerKP.publicKey);
+    const tokens = Number((await getAccount(connection, creatorToken)).amount);
+    await crank(listing);
+
+    // the provider pays the tx fee
+    expect(await connection.getBalance(crankerKP.publicKey)).to.eq(
+      crankerSol + bounty
+    );
+    expect(await connection.getBalance(creatorKP.publicKey)).to.eq(
+      creatorSol + marketSol - bounty + vaultSol
+    );
+    const creatorTokens = await getAccount(connection, creatorToken);
+    expect(Number(creatorTokens.amount)).to.eq(tokens + tokenAmount);
+    expect(await connection.getAccountInfo(listing.publicKey)).to.null;
+  });
+
+  it("Cancel hands the bounty back to the creator", async () => {
+    const listing = await createListing(0);
+    const marketSol = await connection.getBalance(listing.publicKey);
+    const vaultSol = await connection.getBalance(getVaultPDA(listing));
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    await program.methods
+      .marketTsCancel()
+      .accounts({
+        creator: creatorKP.publicKey,
+        creatorTokenAccount: creatorToken,
+        vaultTokenAccount: getVaultPDA(listing),
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+        marketAccount: listing.publicKey,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .signers([creatorKP])
+      .rpc();
+
+    // the market account's lamports hold its rent and the bounty
+    expect(marketSol).to.gt(bounty);
+    expect(await connection.getBalance(creatorKP.publicKey)).to.eq(
+      creatorSol + marketSol + vaultSol
+    );
+  });
+
+  it("A listing is not filled before its start time", async () => {
+    const listing = await createListing(0, (await now()) + 3600);
+    const takerToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        crankerKP,
+        mint,
+        crankerKP.publicKey
+      )
+    ).address;
+    try {
+      await program.methods
+        .marketTsExchange()
+        .accounts({
+          taker: crankerKP.publicKey,
+          takerTokenAccount: takerToken,
+          creator: creatorKP.publicKey,
+          marketAccount: listing.publicKey,
+          vaultTokenAccount: getVaultPDA(listing),
+          mint,
+          vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          config: getConfigPDA(program)[0],
+          treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+          salePhase: PublicKey.findProgramAddressSync(
+            [
+              Buffer.from(SALE_PHASE_SEED),
+              creatorKP.publicKey.toBuffer(),
+              mint.toBuffer(),
+            ],
+            program.programId
+          )[0],
+          mintIndex: getPDA(MINT_INDEX_SEED, mint),
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        })
+        .signers([crankerKP])
+        .rpc();
+      assert.fail("exchange should fail before the start time");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NotYetOpen");
+    }
+    expect(await connection.getAccountInfo(listing.publicKey)).to.not.null;
+  });
+
+  async function crank(listing: Keypair) {
+    await program.methods
+      .marketTsCrank()
+      .accounts({
+        cranker: crankerKP.publicKey,
+        creator: creatorKP.publicKey,
+        creatorTokenAccount: creatorToken,
+        vaultTokenAccount: getVaultPDA(listing),
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+        marketAccount: listing.publicKey,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .signers([crankerKP])
+      .rpc();
+  }
+
+  async function createListing(expireTime: number, startTime = 0) {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(tokenAmount),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(bounty),
+        new anchor.BN(expireTime),
+        new anchor.BN(startTime),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getVaultPDA(marketAccountKP),
+        creatorTokenAccount: creatorToken,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  // the validator's clock, which the expire time is checked against
+  async function now() {
+    const blockTime = await connection.getBlockTime(await connection.getSlot());
+    return blockTime ?? Math.floor(Date.now() / 1000);
+  }
+
+  function getVaultPDA(listing: Keypair) {
+    return getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey);
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
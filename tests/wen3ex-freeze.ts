@@ -0,0 +1,353 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  freezeAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const MARKET_TT = 1;
+const MARKET_TS = 1 << 1;
+const MARKET_ORDER_BOOK = 1 << 4;
+const VAULT_TOKEN_TOKEN_SEED = "vault-token-2-token-seed";
+const ORDER_BOOK_SEED = "order-book-seed";
+const VAULT_ORDER_BOOK_BASE_SEED = "vault-order-book-base-seed";
+const VAULT_ORDER_BOOK_QUOTE_SEED = "vault-order-book-quote-seed";
+
+describe("wen3ex freeze", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const marketAccountKP = Keypair.generate();
+  let mint: PublicKey;
+  let creatorToken: PublicKey;
+
+  it("freeze before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    // the creator keeps the freeze authority
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      creatorKP.publicKey,
+      0
+    );
+    creatorToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        mint,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(connection, creatorKP, mint, creatorToken, creatorKP, 100);
+  });
+
+  it("A mint with a freeze authority is refused by policy", async () => {
+    await setFreezeAuthorityPolicy(MARKET_TS);
+    try {
+      await createToken2SolMarket(Keypair.generate());
+      assert.fail("create should fail for a mint with a freeze authority");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("MintHasFreezeAuthority");
+    } finally {
+      await setFreezeAuthorityPolicy(0);
+    }
+  });
+
+  it("A token2token receive mint that can freeze is refused", async () => {
+    // the deposit mint has none, only the mint paid to the creator does
+    const depositMint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    const depositToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        depositMint,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(
+      connection,
+      creatorKP,
+      depositMint,
+      depositToken,
+      creatorKP,
+      20
+    );
+    const marketKP = Keypair.generate();
+    await setFreezeAuthorityPolicy(MARKET_TT);
+    try {
+      await program.methods
+        .marketTtCreate(
+          new anchor.BN(20),
+          new anchor.BN(10),
+          depositMint,
+          mint,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          new anchor.BN(0)
+        )
+        .accounts({
+          mintIndex: getPDA(MINT_INDEX_SEED, depositMint),
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+          creator: creatorKP.publicKey,
+          marketAccount: marketKP.publicKey,
+          vaultTokenAccount: getPDA(VAULT_TOKEN_TOKEN_SEED, marketKP.publicKey),
+          mint: depositMint,
+          depositTokenAccount: depositToken,
+          receiveTokenAccount: creatorToken,
+          receiveMint: mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          config: getConfigPDA(program)[0],
+          depositMintEntry: getPDA(MINT_ENTRY_SEED, depositMint),
+          receiveMintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        })
+        .preInstructions([
+          await program.account.marketTtAccount.createInstruction(marketKP),
+        ])
+        .signers([marketKP, creatorKP])
+        .rpc();
+      assert.fail("create should fail for a receive mint that can freeze");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("MintHasFreezeAuthority");
+    } finally {
+      await setFreezeAuthorityPolicy(0);
+    }
+  });
+
+  it("An order book over a mint that can freeze is refused", async () => {
+    const quoteMint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      6
+    );
+    const orderBook = PublicKey.findProgramAddressSync(
+      [Buffer.from(ORDER_BOOK_SEED), mint.toBuffer(), quoteMint.toBuffer()],
+      program.programId
+    )[0];
+    await setFreezeAuthorityPolicy(MARKET_ORDER_BOOK);
+    try {
+      await program.methods
+        .orderBookCreate(new anchor.BN(1), new anchor.BN(1))
+        .accounts({
+          orderBook,
+          baseVault: getPDA(VAULT_ORDER_BOOK_BASE_SEED, orderBook),
+          quoteVault: getPDA(VAULT_ORDER_BOOK_QUOTE_SEED, orderBook),
+          vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, orderBook),
+          baseMint: mint,
+          quoteMint,
+          config: getConfigPDA(program)[0],
+          payer: creatorKP.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creatorKP])
+        .rpc();
+      assert.fail("create should fail for a base mint that can freeze");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("MintHasFreezeAuthority");
+    } finally {
+      await setFreezeAuthorityPolicy(0);
+    }
+  });
+
+  it("Cancel with a frozen vault fails with VaultFrozen", async () => {
+    await createToken2SolMarket(marketAccountKP);
+    await freezeAccount(connection, creatorKP, getVaultPDA(), mint, creatorKP);
+    try {
+      await program.methods
+        .marketTsCancel()
+        .accounts({
+          mintIndex: getPDA(MINT_INDEX_SEED, mint),
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+          creator: creatorKP.publicKey,
+          creatorTokenAccount: creatorToken,
+          vaultTokenAccount: getVaultPDA(),
+          vaultAuthority: getPDA(
+            VAULT_AUTHORITY_SEED,
+            marketAccountKP.publicKey
+          ),
+          marketAccount: marketAccountKP.publicKey,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creatorKP])
+        .rpc();
+      assert.fail("cancel should fail while the vault is frozen");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VaultFrozen");
+    }
+  });
+
+  it("Exchange with a frozen vault fails with VaultFrozen", async () => {
+    const takerToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        takerKP,
+        mint,
+        takerKP.publicKey
+      )
+    ).address;
+    try {
+      await program.methods
+        .marketTsExchange()
+        .accounts({
+          taker: takerKP.publicKey,
+          takerTokenAccount: takerToken,
+          creator: creatorKP.publicKey,
+          marketAccount: marketAccountKP.publicKey,
+          vaultTokenAccount: getVaultPDA(),
+          mint,
+          vaultAuthority: getPDA(
+            VAULT_AUTHORITY_SEED,
+            marketAccountKP.publicKey
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          config: getConfigPDA(program)[0],
+          treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+          salePhase: PublicKey.findProgramAddressSync(
+            [
+              Buffer.from(SALE_PHASE_SEED),
+              creatorKP.publicKey.toBuffer(),
+              mint.toBuffer(),
+            ],
+            program.programId
+          )[0],
+          mintIndex: getPDA(MINT_INDEX_SEED, mint),
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        })
+        .signers([takerKP])
+        .rpc();
+      assert.fail("exchange should fail while the vault is frozen");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VaultFrozen");
+    }
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.not
+      .null;
+  });
+
+  it("Crank an expired listing with a frozen vault fails", async () => {
+    const expiringKP = Keypair.generate();
+    const expireTime = (await now()) + 2;
+    await createToken2SolMarket(expiringKP, expireTime);
+    const vault = getPDA(VAULT_TOKEN_SOL_SEED, expiringKP.publicKey);
+    await freezeAccount(connection, creatorKP, vault, mint, creatorKP);
+    while ((await now()) <= expireTime) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    try {
+      await program.methods
+        .marketTsCrank()
+        .accounts({
+          cranker: takerKP.publicKey,
+          creator: creatorKP.publicKey,
+          creatorTokenAccount: creatorToken,
+          vaultTokenAccount: vault,
+          vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, expiringKP.publicKey),
+          marketAccount: expiringKP.publicKey,
+          mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          mintIndex: getPDA(MINT_INDEX_SEED, mint),
+          creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        })
+        .signers([takerKP])
+        .rpc();
+      assert.fail("crank should fail while the vault is frozen");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VaultFrozen");
+    }
+  });
+
+  async function setFreezeAuthorityPolicy(markets: number) {
+    await program.methods
+      .setFreezeAuthorityPolicy(markets)
+      .accounts({
+        config: getConfigPDA(program)[0],
+        admin: (provider as anchor.AnchorProvider).wallet.publicKey,
+      })
+      .rpc();
+  }
+
+  async function createToken2SolMarket(marketKP: Keypair, expireTime = 0) {
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(20),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(expireTime),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketKP.publicKey,
+        vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, marketKP.publicKey),
+        creatorTokenAccount: creatorToken,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(marketKP),
+      ])
+      .signers([marketKP, creatorKP])
+      .rpc();
+  }
+
+  async function now() {
+    const blockTime = await connection.getBlockTime(await connection.getSlot());
+    return blockTime ?? Math.floor(Date.now() / 1000);
+  }
+
+  function getVaultPDA() {
+    return getPDA(VAULT_TOKEN_SOL_SEED, marketAccountKP.publicKey);
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
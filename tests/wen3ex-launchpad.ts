@@ -0,0 +1,219 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const LAUNCHPAD_SEED = "launchpad-seed";
+const VAULT_LAUNCHPAD_SEED = "vault-launchpad-seed";
+const PURCHASE_SEED = "purchase-seed";
+
+describe("wen3ex launchpad", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const mintAuthority = Keypair.generate();
+  const mintKP = Keypair.generate();
+  const issuerKP = Keypair.generate();
+  const buyerKP = Keypair.generate();
+
+  const unitSize = 100;
+  const price = LAMPORTS_PER_SOL / 100; // per unit
+  const supply = 10;
+  const totalCap = 8;
+  const walletCap = 5;
+  const saleSeconds = 6;
+
+  let issuerToken: PublicKey;
+  let buyerToken: PublicKey;
+
+  it("launchpad before", async () => {
+    await initConfig(program);
+    await airDrop(connection, issuerKP.publicKey, 2);
+    await airDrop(connection, buyerKP.publicKey, 2);
+
+    await createMint(
+      connection,
+      issuerKP,
+      mintAuthority.publicKey,
+      null,
+      0,
+      mintKP
+    );
+    issuerToken = await getATA(issuerKP, mintKP.publicKey);
+    buyerToken = await getATA(buyerKP, mintKP.publicKey);
+    await mintTo(
+      connection,
+      issuerKP,
+      mintKP.publicKey,
+      issuerToken,
+      mintAuthority,
+      supply * unitSize
+    );
+
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .launchpadCreate(
+        new anchor.BN(unitSize),
+        new anchor.BN(price),
+        new anchor.BN(supply),
+        new anchor.BN(totalCap),
+        new anchor.BN(walletCap),
+        new anchor.BN(now - 60),
+        new anchor.BN(now + saleSeconds)
+      )
+      .accounts({
+        launchpad: getLaunchpadPDA()[0],
+        vaultTokenAccount: getVaultPDA()[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        issuerTokenAccount: issuerToken,
+        mint: mintKP.publicKey,
+        issuer: issuerKP.publicKey,
+        config: getConfigPDA(program)[0],
+        mintEntry: getMintEntryPDA(mintKP.publicKey)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([issuerKP])
+      .rpc();
+
+    const vault = await getAccount(connection, getVaultPDA()[0]);
+    expect(Number(vault.amount)).to.eq(supply * unitSize);
+  });
+
+  it("Buy records the purchase", async () => {
+    await buy(3);
+    await buy(2);
+
+    const purchase = await program.account.purchaseAccount.fetch(
+      getPurchasePDA()[0]
+    );
+    expect(purchase.quantity.toNumber()).to.eq(5);
+    expect(purchase.paid.toNumber()).to.eq(5 * price);
+
+    const buyerAccount = await getAccount(connection, buyerToken);
+    expect(Number(buyerAccount.amount)).to.eq(5 * unitSize);
+  });
+
+  it("Wallet cap rejects more", async () => {
+    try {
+      await buy(1);
+      assert.fail("buy should fail over the wallet cap");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("WalletCapExceeded");
+    }
+  });
+
+  it("Withdraw after close", async () => {
+    await new Promise((resolve) => setTimeout(resolve, saleSeconds * 1000));
+
+    const issuerSol = await connection.getBalance(issuerKP.publicKey);
+    await program.methods
+      .launchpadWithdraw()
+      .accounts({
+        issuer: issuerKP.publicKey,
+        issuerTokenAccount: issuerToken,
+        launchpad: getLaunchpadPDA()[0],
+        vaultTokenAccount: getVaultPDA()[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([issuerKP])
+      .rpc();
+
+    const issuerAccount = await getAccount(connection, issuerToken);
+    expect(Number(issuerAccount.amount)).to.eq((supply - 5) * unitSize);
+    expect(await connection.getBalance(issuerKP.publicKey)).to.gt(issuerSol);
+    expect(await connection.getAccountInfo(getLaunchpadPDA()[0])).to.null;
+  });
+
+  async function buy(quantity: number) {
+    await program.methods
+      .launchpadBuy(new anchor.BN(quantity))
+      .accounts({
+        buyer: buyerKP.publicKey,
+        buyerTokenAccount: buyerToken,
+        purchase: getPurchasePDA()[0],
+        launchpad: getLaunchpadPDA()[0],
+        vaultTokenAccount: getVaultPDA()[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([buyerKP])
+      .rpc();
+  }
+
+  function getLaunchpadPDA() {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(LAUNCHPAD_SEED),
+        mintKP.publicKey.toBuffer(),
+        issuerKP.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+  }
+
+  function getPurchasePDA() {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(PURCHASE_SEED),
+        getLaunchpadPDA()[0].toBuffer(),
+        buyerKP.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+  }
+
+  function getVaultPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_LAUNCHPAD_SEED), getLaunchpadPDA()[0].toBuffer()],
+      program.programId
+    );
+  }
+
+  function getVaultAuthorityPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), getLaunchpadPDA()[0].toBuffer()],
+      program.programId
+    );
+  }
+
+  function getMintEntryPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(payer: Keypair, mint: PublicKey) {
+    const ata = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mint,
+      payer.publicKey
+    );
+    return ata.address;
+  }
+});
@@ -0,0 +1,165 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+// listings an index makes room for at a time, LISTING_INDEX_PAGE
+const PAGE = 64;
+
+describe("wen3ex listing index", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const listings: Keypair[] = [];
+  let mint: PublicKey;
+  let creatorToken: PublicKey;
+
+  it("listing index before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 10);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    creatorToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        mint,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(connection, creatorKP, mint, creatorToken, creatorKP, 100);
+    await program.methods
+      .listingIndexCreate(false)
+      .accounts({
+        index: getMintIndexPDA(),
+        key: mint,
+        payer: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creatorKP])
+      .rpc();
+  });
+
+  it("The index grows past a page, on the creator's rent", async () => {
+    const emptySize = (await connection.getAccountInfo(getMintIndexPDA()))
+      .data.length;
+    for (let i = 0; i <= PAGE; i++) {
+      listings.push(await createListing());
+    }
+
+    const index = await program.account.listingIndexAccount.fetch(
+      getMintIndexPDA()
+    );
+    expect(index.listings.map((key) => key.toBase58())).to.deep.eq(
+      listings.map((kp) => kp.publicKey.toBase58())
+    );
+    const account = await connection.getAccountInfo(getMintIndexPDA());
+    expect(account.data.length).to.eq(emptySize + 32 * PAGE);
+    expect(account.lamports).to.gte(
+      await connection.getMinimumBalanceForRentExemption(account.data.length)
+    );
+  });
+
+  it("A cancelled listing leaves, the last one takes its place", async () => {
+    const [cancelled] = listings.splice(1, 1);
+    await program.methods
+      .marketTsCancel()
+      .accounts({
+        creator: creatorKP.publicKey,
+        creatorTokenAccount: creatorToken,
+        vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, cancelled.publicKey),
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, cancelled.publicKey),
+        marketAccount: cancelled.publicKey,
+        mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mintIndex: getMintIndexPDA(),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .signers([creatorKP])
+      .rpc();
+
+    const index = await program.account.listingIndexAccount.fetch(
+      getMintIndexPDA()
+    );
+    listings.splice(1, 0, listings.pop());
+    expect(index.listings.map((key) => key.toBase58())).to.deep.eq(
+      listings.map((kp) => kp.publicKey.toBase58())
+    );
+  });
+
+  async function createListing() {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(1),
+        new anchor.BN(LAMPORTS_PER_SOL / 100),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_TOKEN_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: creatorToken,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getMintIndexPDA(),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  function getMintIndexPDA() {
+    return getPDA(MINT_INDEX_SEED, mint);
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
@@ -0,0 +1,87 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { assert, expect } from "chai";
+import * as fs from "fs";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import { airDrop } from "./helpers";
+
+// loaded from tests/fixtures/market-tt-v1.json: a version 1 token 2 token
+// listing of 20 for 10, created by the keypair in migrate-creator.json
+const MARKET_TT_V1 = new PublicKey(
+  "MigrateTtV111111111111111111111111111111111"
+);
+const MARKET_TT_V1_LEN = 8 + 4 + 32 + 32 + 8 + 32 + 8 + 8;
+const MARKET_TT_LEN = MARKET_TT_V1_LEN + 8 + 8 + 8 + 2 + 2;
+const MARKET_TT_VERSION = 4;
+
+describe("wen3ex migrate", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.fromSecretKey(
+    Uint8Array.from(
+      JSON.parse(fs.readFileSync("tests/fixtures/migrate-creator.json", "utf8"))
+    )
+  );
+
+  it("migrate before", async () => {
+    await airDrop(connection, creatorKP.publicKey, 1);
+    const info = await connection.getAccountInfo(MARKET_TT_V1);
+    expect(info.data.length).to.eq(MARKET_TT_V1_LEN);
+    expect(info.data.readUInt32LE(8)).to.eq(1);
+  });
+
+  it("Only the creator migrates a listing", async () => {
+    const strangerKP = Keypair.generate();
+    await airDrop(connection, strangerKP.publicKey, 1);
+    try {
+      await migrate(strangerKP);
+      assert.fail("migrate should fail for anyone but the creator");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidAuthority");
+    }
+  });
+
+  it("Migrate a version 1 token 2 token listing", async () => {
+    await migrate(creatorKP);
+
+    const info = await connection.getAccountInfo(MARKET_TT_V1);
+    expect(info.data.length).to.eq(MARKET_TT_LEN);
+    const market = await program.account.marketTtAccount.fetch(MARKET_TT_V1);
+    expect(market.version).to.eq(MARKET_TT_VERSION);
+    assert.ok(market.creator.equals(creatorKP.publicKey));
+    expect(market.depositAmount.toNumber()).to.eq(20);
+    expect(market.receiveAmount.toNumber()).to.eq(10);
+    // the appended fields read as not set
+    expect(market.bounty.toNumber()).to.eq(0);
+    expect(market.expireTime.toNumber()).to.eq(0);
+    expect(market.startTime.toNumber()).to.eq(0);
+    expect(market.depositDecimals).to.null;
+    expect(market.receiveDecimals).to.null;
+  });
+
+  it("A migrated listing is not migrated again", async () => {
+    try {
+      await migrate(creatorKP);
+      assert.fail("migrate should fail on a current listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("MarketAlreadyMigrated");
+    }
+  });
+
+  async function migrate(creator: Keypair) {
+    await program.methods
+      .migrateMarket()
+      .accounts({
+        marketAccount: MARKET_TT_V1,
+        creator: creator.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+  }
+});
@@ -0,0 +1,316 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const OFFER_SEED = "offer-seed";
+const REFERRER_SEED = "referrer-seed";
+const VAULT_NFT_SOL_SEED = "vault-nft-2-sol-seed";
+
+describe("wen3ex offer", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+  const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+
+  const sellerKP = Keypair.generate();
+  const bidderKP = Keypair.generate();
+  const marketAccountKP = Keypair.generate();
+  const offerSol = LAMPORTS_PER_SOL / 2;
+  // listed, then one in the seller's wallet
+  let listedMint: PublicKey;
+  let walletMint: PublicKey;
+
+  it("offer before", async () => {
+    await initConfig(program);
+    await airDrop(connection, sellerKP.publicKey, 2);
+    await airDrop(connection, bidderKP.publicKey, 3);
+    listedMint = await createNft();
+    walletMint = await createNft();
+
+    await program.methods
+      .marketNftToSolCreate(
+        new anchor.BN(1),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_NFT_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: await getATA(sellerKP, listedMint),
+        mint: listedMint,
+        creator: sellerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, listedMint),
+        mintIndex: getPDA(MINT_INDEX_SEED, listedMint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, sellerKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketNftToSolAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, sellerKP])
+      .rpc();
+  });
+
+  it("Place an offer, the sol is escrowed in it", async () => {
+    const bidderSol = await connection.getBalance(bidderKP.publicKey);
+    await createOffer(walletMint, PublicKey.default);
+
+    const offer = await program.account.offerAccount.fetch(
+      getOfferPDA(walletMint)
+    );
+    expect(offer.bidder.toBase58()).to.eq(bidderKP.publicKey.toBase58());
+    expect(offer.nftToken.toBase58()).to.eq(walletMint.toBase58());
+    expect(offer.nftAmount.toNumber()).to.eq(1);
+    expect(offer.solAmount.toNumber()).to.eq(offerSol);
+    expect(offer.listing.toBase58()).to.eq(PublicKey.default.toBase58());
+    expect(await connection.getBalance(bidderKP.publicKey)).to.lt(
+      bidderSol - offerSol
+    );
+  });
+
+  it("Cancel an offer, the sol comes back", async () => {
+    const bidderSol = await connection.getBalance(bidderKP.publicKey);
+    const offerLamports = await connection.getBalance(
+      getOfferPDA(walletMint)
+    );
+    await program.methods
+      .offerWithdraw()
+      .accounts({
+        bidder: bidderKP.publicKey,
+        offer: getOfferPDA(walletMint),
+      })
+      .signers([bidderKP])
+      .rpc();
+
+    expect(await connection.getAccountInfo(getOfferPDA(walletMint))).to.null;
+    // the escrow and the rent, less the tx fee
+    expect(await connection.getBalance(bidderKP.publicKey)).to.gt(
+      bidderSol + offerLamports - LAMPORTS_PER_SOL / 1000
+    );
+  });
+
+  it("An offer on another listing cannot be accepted", async () => {
+    await createOffer(listedMint, Keypair.generate().publicKey);
+    try {
+      await acceptListing();
+      assert.fail("accept should fail for an offer on another listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("OfferListingMismatch");
+    }
+    await program.methods
+      .offerWithdraw()
+      .accounts({
+        bidder: bidderKP.publicKey,
+        offer: getOfferPDA(listedMint),
+      })
+      .signers([bidderKP])
+      .rpc();
+  });
+
+  it("Accept an offer from a listing", async () => {
+    await createOffer(listedMint, marketAccountKP.publicKey);
+    const sellerSol = await connection.getBalance(sellerKP.publicKey);
+    await acceptListing();
+
+    const bidderNft = await getAccount(
+      connection,
+      await getATA(bidderKP, listedMint)
+    );
+    expect(Number(bidderNft.amount)).to.eq(1);
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    expect(await connection.getAccountInfo(getOfferPDA(listedMint))).to.null;
+    // the offer, plus the listing's and the vault's rent, less the tx fee
+    expect(await connection.getBalance(sellerKP.publicKey)).to.gt(
+      sellerSol + offerSol
+    );
+  });
+
+  it("The seller can't refer its own sale", async () => {
+    await program.methods
+      .referrerRegister()
+      .accounts({
+        referrerAccount: getPDA(REFERRER_SEED, sellerKP.publicKey),
+        referrer: sellerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([sellerKP])
+      .rpc();
+    await createOffer(walletMint, PublicKey.default);
+    try {
+      await acceptWallet([
+        { pubkey: sellerKP.publicKey, isSigner: false, isWritable: true },
+        {
+          pubkey: getPDA(REFERRER_SEED, sellerKP.publicKey),
+          isSigner: false,
+          isWritable: true,
+        },
+      ]);
+      assert.fail("accept should fail when the seller is the referrer");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("SelfReferral");
+    }
+    await program.methods
+      .offerWithdraw()
+      .accounts({
+        bidder: bidderKP.publicKey,
+        offer: getOfferPDA(walletMint),
+      })
+      .signers([bidderKP])
+      .rpc();
+  });
+
+  it("Accept an offer from the wallet", async () => {
+    await createOffer(walletMint, PublicKey.default);
+    const sellerSol = await connection.getBalance(sellerKP.publicKey);
+    await acceptWallet();
+
+    const sellerNft = await getAccount(
+      connection,
+      await getATA(sellerKP, walletMint)
+    );
+    const bidderNft = await getAccount(
+      connection,
+      await getATA(bidderKP, walletMint)
+    );
+    expect(Number(sellerNft.amount)).to.eq(0);
+    expect(Number(bidderNft.amount)).to.eq(1);
+    expect(await connection.getAccountInfo(getOfferPDA(walletMint))).to.null;
+    expect(await connection.getBalance(sellerKP.publicKey)).to.gt(
+      sellerSol + offerSol - LAMPORTS_PER_SOL / 1000
+    );
+  });
+
+  async function createOffer(mint: PublicKey, listing: PublicKey) {
+    await program.methods
+      .offerCreate(new anchor.BN(1), new anchor.BN(offerSol), listing)
+      .accounts({
+        offer: getOfferPDA(mint),
+        mint,
+        bidder: bidderKP.publicKey,
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bidderKP])
+      .rpc();
+  }
+
+  async function acceptWallet(referrer: anchor.web3.AccountMeta[] = []) {
+    await program.methods
+      .offerAcceptWallet()
+      .accounts({
+        seller: sellerKP.publicKey,
+        sellerTokenAccount: await getATA(sellerKP, walletMint),
+        bidder: bidderKP.publicKey,
+        bidderTokenAccount: await getATA(bidderKP, walletMint),
+        offer: getOfferPDA(walletMint),
+        config: getConfigPDA(program)[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        treasury: admin,
+      })
+      .remainingAccounts(referrer)
+      .signers([sellerKP])
+      .rpc();
+  }
+
+  async function acceptListing() {
+    await program.methods
+      .offerAcceptListing()
+      .accounts({
+        creator: sellerKP.publicKey,
+        bidder: bidderKP.publicKey,
+        bidderTokenAccount: await getATA(bidderKP, listedMint),
+        offer: getOfferPDA(listedMint),
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_NFT_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        vaultAuthority: getPDA(
+          VAULT_AUTHORITY_SEED,
+          marketAccountKP.publicKey
+        ),
+        config: getConfigPDA(program)[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        treasury: admin,
+        mintIndex: getPDA(MINT_INDEX_SEED, listedMint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, sellerKP.publicKey),
+      })
+      .signers([sellerKP])
+      .rpc();
+  }
+
+  async function createNft() {
+    const mint = await createMint(
+      connection,
+      sellerKP,
+      sellerKP.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      sellerKP,
+      mint,
+      await getATA(sellerKP, mint),
+      sellerKP,
+      1
+    );
+    return mint;
+  }
+
+  function getOfferPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(OFFER_SEED),
+        mint.toBuffer(),
+        bidderKP.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function getATA(owner: Keypair, mint: PublicKey) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      sellerKP,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+});
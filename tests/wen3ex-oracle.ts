@@ -0,0 +1,266 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+// loaded from tests/fixtures/mock-price.json, 0.05 sol per token
+const MOCK_PRICE = new PublicKey("MockPrice1111111111111111111111111111111111");
+
+describe("wen3ex oracle", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  const marketAccountKP = Keypair.generate();
+  let mint: PublicKey;
+
+  const depositAmount = 20;
+  // 20 tokens at 0.05 sol, plus 1%
+  const peggedSol = 1.01 * LAMPORTS_PER_SOL;
+
+  it("oracle before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 3);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      mintAuthority.publicKey,
+      null,
+      0
+    );
+    const creatorAta = await getATA(creatorKP, creatorKP.publicKey);
+    await mintTo(
+      connection,
+      creatorKP,
+      mint,
+      creatorAta.address,
+      mintAuthority,
+      depositAmount
+    );
+    await createToken2SolMarket();
+  });
+
+  it("Set peg refuses a feed the pyth program doesn't own", async () => {
+    try {
+      await setPeg(new anchor.BN(60), 100, mint);
+      assert.fail("set peg should fail for an account outside pyth");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidOracle");
+    }
+  });
+
+  it("Set peg refuses loose oracle limits", async () => {
+    for (const [maxAge, maxConfBps] of [
+      [0, 100],
+      [60, 0],
+      [60, 1_001],
+    ]) {
+      try {
+        await setPeg(new anchor.BN(maxAge), maxConfBps, MOCK_PRICE);
+        assert.fail("set peg should fail for loose limits");
+      } catch (error: any) {
+        expect(error.error.errorCode.code).to.eq("InvalidOracleLimits");
+      }
+    }
+  });
+
+  it("Pegged listing needs the oracle price", async () => {
+    await program.methods
+      .marketTsSetPeg(100, new anchor.BN(10 * 365 * 24 * 3600), 100)
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: marketAccountKP.publicKey,
+        oracle: MOCK_PRICE,
+      })
+      .signers([creatorKP])
+      .rpc();
+    const market = await program.account.marketTsAccount.fetch(
+      marketAccountKP.publicKey
+    );
+    assert.ok(market.oracle.equals(MOCK_PRICE));
+
+    try {
+      await program.methods
+        .marketTsExchange()
+        .accounts(await exchangeAccounts())
+        .signers([takerKP])
+        .rpc();
+      assert.fail("plain exchange should fail on a pegged listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("OraclePriceRequired");
+    }
+  });
+
+  it("Exchange pegged rejects a price above the max", async () => {
+    try {
+      await exchangePegged(peggedSol - 1);
+      assert.fail("exchange should fail above max_sol_amount");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("PriceAboveMax");
+    }
+  });
+
+  it("Exchange pegged at oracle price plus spread", async () => {
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    await exchangePegged(peggedSol);
+
+    const takerAta = await getATA(takerKP, takerKP.publicKey);
+    const takerToken = await getAccount(connection, takerAta.address);
+    expect(Number(takerToken.amount)).to.eq(depositAmount);
+    // the creator gets the price less fees, and the listing's rent back
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(
+      creatorSol + peggedSol * 0.9
+    );
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+  });
+
+  async function setPeg(
+    maxAge: anchor.BN,
+    maxConfBps: number,
+    oracle: PublicKey
+  ) {
+    await program.methods
+      .marketTsSetPeg(100, maxAge, maxConfBps)
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: marketAccountKP.publicKey,
+        oracle,
+      })
+      .signers([creatorKP])
+      .rpc();
+  }
+
+  async function exchangePegged(maxSolAmount: number) {
+    await program.methods
+      .marketTsExchangePegged(new anchor.BN(maxSolAmount))
+      .accounts({
+        exchange: await exchangeAccounts(),
+        oracle: MOCK_PRICE,
+      })
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function exchangeAccounts() {
+    const takerAta = await getATA(takerKP, takerKP.publicKey);
+    return {
+      taker: takerKP.publicKey,
+      takerTokenAccount: takerAta.address,
+      creator: creatorKP.publicKey,
+      marketAccount: marketAccountKP.publicKey,
+      vaultTokenAccount: getVaultPDA()[0],
+      mint,
+      vaultAuthority: getVaultAuthorityPDA()[0],
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      config: getConfigPDA(program)[0],
+      treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+      salePhase: PublicKey.findProgramAddressSync(
+        [Buffer.from(SALE_PHASE_SEED), creatorKP.publicKey.toBuffer(), mint.toBuffer()],
+        program.programId
+      )[0],
+      mintIndex: getMintIndexPDA(mint)[0],
+      creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
+    };
+  }
+
+  async function createToken2SolMarket() {
+    const creatorAta = await getATA(creatorKP, creatorKP.publicKey);
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(depositAmount),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getVaultPDA()[0],
+        creatorTokenAccount: creatorAta.address,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: PublicKey.findProgramAddressSync(
+          [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+          program.programId
+        )[0],
+        mintIndex: getMintIndexPDA(mint)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+  }
+
+  function getVaultPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_TOKEN_SOL_SEED), marketAccountKP.publicKey.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getVaultAuthorityPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), marketAccountKP.publicKey.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(payer: Keypair, owner: PublicKey) {
+    return await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mint,
+      owner
+    );
+  }
+
+  function getMintIndexPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_INDEX_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getCreatorIndexPDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CREATOR_INDEX_SEED), creator.toBuffer()],
+      program.programId
+    );
+  }
+});
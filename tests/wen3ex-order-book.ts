@@ -0,0 +1,353 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const ORDER_BOOK_SEED = "order-book-seed";
+const VAULT_ORDER_BOOK_BASE_SEED = "vault-order-book-base-seed";
+const VAULT_ORDER_BOOK_QUOTE_SEED = "vault-order-book-quote-seed";
+const ORDER_BOOK_BALANCE_SEED = "order-book-balance-seed";
+const MAX_ORDERS = 32;
+
+const SIDE_BID = 0;
+const SIDE_ASK = 1;
+const ORDER_TYPE_LIMIT = 0;
+const ORDER_TYPE_IOC = 1;
+
+describe("wen3ex order book", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const mintAuthority = Keypair.generate();
+  const baseMintKP = Keypair.generate();
+  const quoteMintKP = Keypair.generate();
+  const makerKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+
+  const baseLotSize = 10;
+  const minLots = 2;
+
+  let makerBase: PublicKey;
+  let makerQuote: PublicKey;
+  let takerBase: PublicKey;
+  let takerQuote: PublicKey;
+
+  it("order book before", async () => {
+    await initConfig(program);
+    await airDrop(connection, makerKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+
+    await createMint(
+      connection,
+      makerKP,
+      mintAuthority.publicKey,
+      null,
+      0,
+      baseMintKP
+    );
+    await createMint(
+      connection,
+      makerKP,
+      mintAuthority.publicKey,
+      null,
+      0,
+      quoteMintKP
+    );
+
+    makerBase = await getATA(makerKP, baseMintKP.publicKey);
+    makerQuote = await getATA(makerKP, quoteMintKP.publicKey);
+    takerBase = await getATA(takerKP, baseMintKP.publicKey);
+    takerQuote = await getATA(takerKP, quoteMintKP.publicKey);
+
+    await mintTo(
+      connection,
+      makerKP,
+      baseMintKP.publicKey,
+      makerBase,
+      mintAuthority,
+      1000
+    );
+    await mintTo(
+      connection,
+      takerKP,
+      quoteMintKP.publicKey,
+      takerQuote,
+      mintAuthority,
+      1000
+    );
+
+    await program.methods
+      .orderBookCreate(new anchor.BN(baseLotSize), new anchor.BN(minLots))
+      .accounts({
+        orderBook: getOrderBookPDA()[0],
+        baseVault: getVaultPDA(VAULT_ORDER_BOOK_BASE_SEED)[0],
+        quoteVault: getVaultPDA(VAULT_ORDER_BOOK_QUOTE_SEED)[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        baseMint: baseMintKP.publicKey,
+        quoteMint: quoteMintKP.publicKey,
+        config: getConfigPDA(program)[0],
+        payer: makerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([makerKP])
+      .rpc();
+  });
+
+  it("Rest asks ordered by price then time", async () => {
+    // 5 lots @ 3, then 5 lots @ 2
+    await placeOrder(makerKP, SIDE_ASK, 3, 5, ORDER_TYPE_LIMIT, []);
+    await placeOrder(makerKP, SIDE_ASK, 2, 5, ORDER_TYPE_LIMIT, []);
+
+    const orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    expect(orderBook.asks.map((o) => o.price.toNumber())).to.eql([2, 3]);
+
+    const baseVault = await getAccount(
+      connection,
+      getVaultPDA(VAULT_ORDER_BOOK_BASE_SEED)[0]
+    );
+    expect(Number(baseVault.amount)).to.eq(10 * baseLotSize);
+  });
+
+  it("Orders below min lots are refused", async () => {
+    try {
+      await placeOrder(makerKP, SIDE_ASK, 3, minLots - 1, ORDER_TYPE_LIMIT, []);
+      assert.fail("place should fail below min lots");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("OrderTooSmall");
+    }
+  });
+
+  it("Bid fills at maker prices, the maker is credited", async () => {
+    // 7 lots up to 3: 5 @ 2 and 2 @ 3
+    const makerBalance = getBalancePDA(makerKP.publicKey);
+    await placeOrder(takerKP, SIDE_BID, 3, 7, ORDER_TYPE_IOC, [
+      makerBalance,
+      makerBalance,
+    ]);
+
+    const takerBaseAccount = await getAccount(connection, takerBase);
+    expect(Number(takerBaseAccount.amount)).to.eq(7 * baseLotSize);
+    const balance = await program.account.orderBookBalanceAccount.fetch(
+      makerBalance
+    );
+    expect(balance.quoteFree.toNumber()).to.eq(5 * 2 + 2 * 3);
+    expect(balance.baseFree.toNumber()).to.eq(0);
+
+    const orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    expect(orderBook.asks.length).to.eq(1);
+    expect(orderBook.asks[0].quantity.toNumber()).to.eq(3);
+  });
+
+  it("Settle withdraws the maker's balance", async () => {
+    await settle(makerKP);
+
+    const makerQuoteAccount = await getAccount(connection, makerQuote);
+    expect(Number(makerQuoteAccount.amount)).to.eq(5 * 2 + 2 * 3);
+    const balance = await program.account.orderBookBalanceAccount.fetch(
+      getBalancePDA(makerKP.publicKey)
+    );
+    expect(balance.quoteFree.toNumber()).to.eq(0);
+  });
+
+  it("Cancel returns the escrow", async () => {
+    let orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    await program.methods
+      .cancelOrder(orderBook.asks[0].orderId)
+      .accounts({
+        owner: makerKP.publicKey,
+        ownerBaseAccount: makerBase,
+        ownerQuoteAccount: makerQuote,
+        orderBook: getOrderBookPDA()[0],
+        baseVault: getVaultPDA(VAULT_ORDER_BOOK_BASE_SEED)[0],
+        quoteVault: getVaultPDA(VAULT_ORDER_BOOK_QUOTE_SEED)[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([makerKP])
+      .rpc();
+
+    orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    expect(orderBook.asks.length).to.eq(0);
+    const makerBaseAccount = await getAccount(connection, makerBase);
+    expect(Number(makerBaseAccount.amount)).to.eq(1000 - 7 * baseLotSize);
+  });
+
+  it("A full side evicts its worst order for a better one", async () => {
+    // the maker fills the bids at 1
+    await mintTo(
+      connection,
+      makerKP,
+      quoteMintKP.publicKey,
+      makerQuote,
+      mintAuthority,
+      MAX_ORDERS * minLots
+    );
+    for (let i = 0; i < MAX_ORDERS; i++) {
+      await placeOrder(makerKP, SIDE_BID, 1, minLots, ORDER_TYPE_LIMIT, []);
+    }
+    let orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    expect(orderBook.bids.length).to.eq(MAX_ORDERS);
+    const worst = orderBook.bids[MAX_ORDERS - 1];
+
+    // no better, no room
+    try {
+      await placeOrder(takerKP, SIDE_BID, 1, minLots, ORDER_TYPE_LIMIT, [
+        getBalancePDA(makerKP.publicKey),
+      ]);
+      assert.fail("place should fail on a full side");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("OrderBookFull");
+    }
+
+    await placeOrder(takerKP, SIDE_BID, 2, minLots, ORDER_TYPE_LIMIT, [
+      getBalancePDA(makerKP.publicKey),
+    ]);
+    orderBook = await program.account.orderBookAccount.fetch(
+      getOrderBookPDA()[0]
+    );
+    expect(orderBook.bids.length).to.eq(MAX_ORDERS);
+    expect(orderBook.bids[0].price.toNumber()).to.eq(2);
+    const orderIds = orderBook.bids.map((o) => o.orderId.toNumber());
+    expect(orderIds).to.not.include(worst.orderId.toNumber());
+    // the evicted escrow waits in the maker's balance
+    const balance = await program.account.orderBookBalanceAccount.fetch(
+      getBalancePDA(makerKP.publicKey)
+    );
+    // minLots @ 1
+    expect(balance.quoteFree.toNumber()).to.eq(minLots);
+  });
+
+  async function placeOrder(
+    owner: Keypair,
+    side: number,
+    price: number,
+    quantity: number,
+    orderType: number,
+    makerBalances: PublicKey[]
+  ) {
+    const ownerBase = owner === makerKP ? makerBase : takerBase;
+    const ownerQuote = owner === makerKP ? makerQuote : takerQuote;
+    await program.methods
+      .placeOrder(
+        side,
+        new anchor.BN(price),
+        new anchor.BN(quantity),
+        orderType
+      )
+      .accounts({
+        owner: owner.publicKey,
+        ownerBaseAccount: ownerBase,
+        ownerQuoteAccount: ownerQuote,
+        orderBook: getOrderBookPDA()[0],
+        baseVault: getVaultPDA(VAULT_ORDER_BOOK_BASE_SEED)[0],
+        quoteVault: getVaultPDA(VAULT_ORDER_BOOK_QUOTE_SEED)[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        ownerBalance: getBalancePDA(owner.publicKey),
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        makerBalances.map((pubkey) => ({
+          pubkey,
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .signers([owner])
+      .rpc();
+  }
+
+  async function settle(owner: Keypair) {
+    await program.methods
+      .orderBookSettle()
+      .accounts({
+        owner: owner.publicKey,
+        ownerBaseAccount: owner === makerKP ? makerBase : takerBase,
+        ownerQuoteAccount: owner === makerKP ? makerQuote : takerQuote,
+        orderBook: getOrderBookPDA()[0],
+        ownerBalance: getBalancePDA(owner.publicKey),
+        baseVault: getVaultPDA(VAULT_ORDER_BOOK_BASE_SEED)[0],
+        quoteVault: getVaultPDA(VAULT_ORDER_BOOK_QUOTE_SEED)[0],
+        vaultAuthority: getVaultAuthorityPDA()[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  function getBalancePDA(owner: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(ORDER_BOOK_BALANCE_SEED),
+        getOrderBookPDA()[0].toBuffer(),
+        owner.toBuffer(),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function getOrderBookPDA() {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(ORDER_BOOK_SEED),
+        baseMintKP.publicKey.toBuffer(),
+        quoteMintKP.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+  }
+
+  function getVaultPDA(seed: string) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), getOrderBookPDA()[0].toBuffer()],
+      program.programId
+    );
+  }
+
+  function getVaultAuthorityPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), getOrderBookPDA()[0].toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(payer: Keypair, mint: PublicKey) {
+    const ata = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mint,
+      payer.publicKey
+    );
+    return ata.address;
+  }
+});
@@ -0,0 +1,340 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import {
+  PROGRAM_ID as TOKEN_METADATA_ID,
+  TokenStandard,
+  createCreateInstruction,
+  createMintInstruction,
+} from "@metaplex-foundation/mpl-token-metadata";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const OFFER_SEED = "offer-seed";
+const VAULT_NFT_SOL_SEED = "vault-nft-2-sol-seed";
+
+describe("wen3ex pnft", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const soldMintKP = Keypair.generate();
+  const cancelledMintKP = Keypair.generate();
+  const solAmount = new anchor.BN(LAMPORTS_PER_SOL / 10);
+
+  it("pnft before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    await createPnft(soldMintKP);
+    await createPnft(cancelledMintKP);
+  });
+
+  it("List, then buy a programmable nft", async () => {
+    const mint = soldMintKP.publicKey;
+    const marketAccountKP = await createListing(mint);
+    const vaultAuthority = getVaultAuthorityPDA(marketAccountKP.publicKey);
+    const vault = getAssociatedTokenAddressSync(mint, vaultAuthority, true);
+    // programmable nfts stay frozen in every token account
+    expect((await getAccount(connection, vault)).isFrozen).to.true;
+    await expectRefusedByOffers(marketAccountKP.publicKey, mint);
+
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    const takerToken = getAssociatedTokenAddressSync(mint, takerKP.publicKey);
+    await program.methods
+      .marketPnftToSolExchange()
+      .accounts({
+        taker: takerKP.publicKey,
+        takerTokenAccount: takerToken,
+        creator: creatorKP.publicKey,
+        marketAccount: marketAccountKP.publicKey,
+        vaultAuthority,
+        vaultTokenAccount: vault,
+        mint,
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        mintIndex: getIndexPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getIndexPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        pnft: pnftAccounts(mint, vault, takerToken),
+      })
+      .signers([takerKP])
+      .rpc();
+
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(creatorSol);
+    const taken = await getAccount(connection, takerToken);
+    expect(Number(taken.amount)).to.eq(1);
+    expect(taken.isFrozen).to.true;
+  });
+
+  it("List, then cancel a programmable nft", async () => {
+    const mint = cancelledMintKP.publicKey;
+    const marketAccountKP = await createListing(mint);
+    const vaultAuthority = getVaultAuthorityPDA(marketAccountKP.publicKey);
+    const vault = getAssociatedTokenAddressSync(mint, vaultAuthority, true);
+    const creatorToken = getAssociatedTokenAddressSync(
+      mint,
+      creatorKP.publicKey
+    );
+    await program.methods
+      .marketPnftToSolCancel()
+      .accounts({
+        creator: creatorKP.publicKey,
+        creatorTokenAccount: creatorToken,
+        marketAccount: marketAccountKP.publicKey,
+        vaultAuthority,
+        vaultTokenAccount: vault,
+        mint,
+        mintIndex: getIndexPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getIndexPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        pnft: pnftAccounts(mint, vault, creatorToken),
+      })
+      .signers([creatorKP])
+      .rpc();
+
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    const back = await getAccount(connection, creatorToken);
+    expect(Number(back.amount)).to.eq(1);
+  });
+
+  // offers go through the plain nft vault, they can't take a pnft listing
+  async function expectRefusedByOffers(listing: PublicKey, mint: PublicKey) {
+    const offer = PublicKey.findProgramAddressSync(
+      [Buffer.from(OFFER_SEED), mint.toBuffer(), takerKP.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods
+      .offerCreate(new anchor.BN(1), solAmount, listing)
+      .accounts({
+        offer,
+        mint,
+        bidder: takerKP.publicKey,
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([takerKP])
+      .rpc();
+    const bidderToken = await getOrCreateAssociatedTokenAccount(
+      connection,
+      takerKP,
+      mint,
+      takerKP.publicKey
+    );
+    try {
+      await program.methods
+        .offerAcceptListing()
+        .accounts({
+          creator: creatorKP.publicKey,
+          bidder: takerKP.publicKey,
+          bidderTokenAccount: bidderToken.address,
+          offer,
+          marketAccount: listing,
+          vaultTokenAccount: getIndexPDA(VAULT_NFT_SOL_SEED, listing),
+          vaultAuthority: getVaultAuthorityPDA(listing),
+          config: getConfigPDA(program)[0],
+          tokenProgram: TOKEN_PROGRAM_ID,
+          treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+          mintIndex: getIndexPDA(MINT_INDEX_SEED, mint),
+          creatorIndex: getIndexPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        })
+        .signers([creatorKP])
+        .rpc();
+      assert.fail("accept should fail for a pnft listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NftVaultMismatch");
+    }
+    await program.methods
+      .offerWithdraw()
+      .accounts({ bidder: takerKP.publicKey, offer })
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function createListing(mint: PublicKey) {
+    const marketAccountKP = Keypair.generate();
+    const vaultAuthority = getVaultAuthorityPDA(marketAccountKP.publicKey);
+    const vault = getAssociatedTokenAddressSync(mint, vaultAuthority, true);
+    const creatorToken = getAssociatedTokenAddressSync(
+      mint,
+      creatorKP.publicKey
+    );
+    await program.methods
+      .marketPnftToSolCreate(new anchor.BN(1), solAmount, new anchor.BN(0))
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        creator: creatorKP.publicKey,
+        creatorTokenAccount: creatorToken,
+        vaultAuthority,
+        vaultTokenAccount: vault,
+        mint,
+        config: getConfigPDA(program)[0],
+        mintEntry: getIndexPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getIndexPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getIndexPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        pnft: pnftAccounts(mint, creatorToken, vault),
+      })
+      .preInstructions([
+        await program.account.marketNftToSolAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  // a programmable nft without a rule set, minted to the creator
+  async function createPnft(mintKP: Keypair) {
+    const mint = mintKP.publicKey;
+    const creator = creatorKP.publicKey;
+    const token = getAssociatedTokenAddressSync(mint, creator);
+    const create = createCreateInstruction(
+      {
+        metadata: getMetadataPDA(mint),
+        masterEdition: getEditionPDA(mint),
+        mint,
+        authority: creator,
+        payer: creator,
+        updateAuthority: creator,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        splTokenProgram: TOKEN_PROGRAM_ID,
+      },
+      {
+        createArgs: {
+          __kind: "V1",
+          assetData: {
+            name: "pnft",
+            symbol: "PNFT",
+            uri: "",
+            sellerFeeBasisPoints: 500,
+            creators: null,
+            primarySaleHappened: false,
+            isMutable: true,
+            tokenStandard: TokenStandard.ProgrammableNonFungible,
+            collection: null,
+            uses: null,
+            collectionDetails: null,
+            ruleSet: null,
+          },
+          decimals: 0,
+          printSupply: { __kind: "Zero" },
+        },
+      }
+    );
+    // a new mint signs its creation
+    create.keys.find((key) => key.pubkey.equals(mint)).isSigner = true;
+    const mintTo = createMintInstruction(
+      {
+        token,
+        tokenOwner: creator,
+        metadata: getMetadataPDA(mint),
+        masterEdition: getEditionPDA(mint),
+        tokenRecord: getTokenRecordPDA(mint, token),
+        mint,
+        authority: creator,
+        payer: creator,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        splTokenProgram: TOKEN_PROGRAM_ID,
+        splAtaProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      },
+      { mintArgs: { __kind: "V1", amount: 1, authorizationData: null } }
+    );
+    await provider.sendAndConfirm(new Transaction().add(create, mintTo), [
+      creatorKP,
+      mintKP,
+    ]);
+  }
+
+  function pnftAccounts(
+    mint: PublicKey,
+    source: PublicKey,
+    destination: PublicKey
+  ) {
+    return {
+      metadata: getMetadataPDA(mint),
+      edition: getEditionPDA(mint),
+      sourceTokenRecord: getTokenRecordPDA(mint, source),
+      destinationTokenRecord: getTokenRecordPDA(mint, destination),
+      // no rule set
+      authorizationRules: TOKEN_METADATA_ID,
+      authorizationRulesProgram: TOKEN_METADATA_ID,
+      tokenMetadataProgram: TOKEN_METADATA_ID,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+  }
+
+  function getMetadataPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_ID
+    )[0];
+  }
+
+  function getEditionPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_ID
+    )[0];
+  }
+
+  function getTokenRecordPDA(mint: PublicKey, token: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("token_record"),
+        token.toBuffer(),
+      ],
+      TOKEN_METADATA_ID
+    )[0];
+  }
+
+  function getIndexPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  function getVaultAuthorityPDA(marketAccount: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), marketAccount.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
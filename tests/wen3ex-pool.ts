@@ -0,0 +1,308 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  getMint,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  transfer,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const POOL_SEED = "pool-seed";
+const POOL_LP_MINT_SEED = "pool-lp-mint-seed";
+const POOL_LP_LOCK_SEED = "pool-lp-lock-seed";
+// lp locked by the first deposit, POOL_MINIMUM_LIQUIDITY
+const MINIMUM_LIQUIDITY = 1_000;
+const VAULT_POOL_SEED = "vault-pool-seed";
+
+describe("wen3ex pool", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const ownerKP = Keypair.generate();
+  const traderKP = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  // mint_a sorts before mint_b
+  const [mintAKP, mintBKP] = [Keypair.generate(), Keypair.generate()].sort(
+    (x, y) => Buffer.compare(x.publicKey.toBuffer(), y.publicKey.toBuffer())
+  );
+  const mintA = mintAKP.publicKey;
+  const mintB = mintBKP.publicKey;
+  const [poolPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from(POOL_SEED), mintA.toBuffer(), mintB.toBuffer()],
+    program.programId
+  );
+  const [lpMintPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from(POOL_LP_MINT_SEED), poolPDA.toBuffer()],
+    program.programId
+  );
+  const [lpLockPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from(POOL_LP_LOCK_SEED), poolPDA.toBuffer()],
+    program.programId
+  );
+
+  it("pool before", async () => {
+    await initConfig(program);
+    await airDrop(connection, ownerKP.publicKey, 2);
+    await airDrop(connection, traderKP.publicKey, 2);
+    for (const kp of [mintAKP, mintBKP]) {
+      await createMint(connection, ownerKP, mintAuthority.publicKey, null, 0, kp);
+      for (const owner of [ownerKP, traderKP]) {
+        const ata = await getATA(owner, kp.publicKey);
+        await mintTo(connection, owner, kp.publicKey, ata.address, mintAuthority, 100_000);
+      }
+    }
+  });
+
+  it("Create pool with mints out of order fails", async () => {
+    const [reversedPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(POOL_SEED), mintB.toBuffer(), mintA.toBuffer()],
+      program.programId
+    );
+    try {
+      await createPool(reversedPDA, mintB, mintA);
+      assert.fail("create should fail with mint_a after mint_b");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidPoolMints");
+    }
+  });
+
+  it("Create pool and deposit", async () => {
+    await createPool(poolPDA, mintA, mintB);
+    const pool = await program.account.poolAccount.fetch(poolPDA);
+    assert.ok(pool.lpMint.equals(lpMintPDA));
+
+    await getATA(ownerKP, lpMintPDA);
+    // sqrt(30 * 30) doesn't cover the locked lp
+    try {
+      await deposit(30, 30);
+      assert.fail("a first deposit under the minimum liquidity should fail");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InsufficientLiquidity");
+    }
+    await deposit(10_000, 40_000);
+
+    // sqrt(10_000 * 40_000), less the locked lp
+    const lpToken = await getAccount(
+      connection,
+      (await getATA(ownerKP, lpMintPDA)).address
+    );
+    expect(Number(lpToken.amount)).to.eq(20_000 - MINIMUM_LIQUIDITY);
+    const lpLock = await getAccount(connection, lpLockPDA);
+    expect(Number(lpLock.amount)).to.eq(MINIMUM_LIQUIDITY);
+  });
+
+  it("Swap respects min amount out", async () => {
+    // 1000 a in at 0.3%: 40_000 * 997 / (10_000 + 997)
+    const expectedOut = 3626;
+    try {
+      await swap(1000, expectedOut + 1, true);
+      assert.fail("swap should fail below min_amount_out");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("SlippageExceeded");
+    }
+
+    await swap(1000, expectedOut, true);
+    const traderB = await getAccount(
+      connection,
+      (await getATA(traderKP, mintB)).address
+    );
+    expect(Number(traderB.amount)).to.eq(100_000 + expectedOut);
+  });
+
+  it("Withdraw all liquidity, the locked lp's share stays", async () => {
+    // 19_000 of 20_000 lp: 11_000 * 0.95 a and 36_374 * 0.95 b, rounded down
+    await program.methods
+      .poolWithdraw(new anchor.BN(19_000), new anchor.BN(10_450), new anchor.BN(34_555))
+      .accounts(await liquidityAccounts())
+      .signers([ownerKP])
+      .rpc();
+    const lpMint = await getMint(connection, lpMintPDA);
+    expect(Number(lpMint.supply)).to.eq(MINIMUM_LIQUIDITY);
+    const vaultA = await getAccount(connection, getVaultPDA(mintA)[0]);
+    expect(Number(vaultA.amount)).to.eq(550);
+  });
+
+  it("A pool whose vaults got tokens before any lp is refused", async () => {
+    const mintC = await createMint(
+      connection,
+      ownerKP,
+      mintAuthority.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      ownerKP,
+      mintC,
+      (await getATA(ownerKP, mintC)).address,
+      mintAuthority,
+      100_000
+    );
+    const [a, b] = [mintA, mintC].sort((x, y) =>
+      Buffer.compare(x.toBuffer(), y.toBuffer())
+    );
+    const [pool] = PublicKey.findProgramAddressSync(
+      [Buffer.from(POOL_SEED), a.toBuffer(), b.toBuffer()],
+      program.programId
+    );
+    await createPool(pool, a, b);
+    // a donation ahead of the first deposit
+    await transfer(
+      connection,
+      ownerKP,
+      (await getATA(ownerKP, a)).address,
+      getVaultPDA(a, pool)[0],
+      ownerKP,
+      1
+    );
+    const [lpMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from(POOL_LP_MINT_SEED), pool.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .poolDeposit(new anchor.BN(10_000), new anchor.BN(10_000), new anchor.BN(1))
+        .accounts({
+          owner: ownerKP.publicKey,
+          ownerTokenA: (await getATA(ownerKP, a)).address,
+          ownerTokenB: (await getATA(ownerKP, b)).address,
+          ownerLpToken: (await getATA(ownerKP, lpMint)).address,
+          pool,
+          lpMint,
+          lpLock: PublicKey.findProgramAddressSync(
+            [Buffer.from(POOL_LP_LOCK_SEED), pool.toBuffer()],
+            program.programId
+          )[0],
+          vaultA: getVaultPDA(a, pool)[0],
+          vaultB: getVaultPDA(b, pool)[0],
+          vaultAuthority: getVaultAuthorityPDA(pool)[0],
+          config: getConfigPDA(program)[0],
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([ownerKP])
+        .rpc();
+      assert.fail("deposit should fail into a pool holding tokens");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("PoolNotEmpty");
+    }
+  });
+
+  async function deposit(amountA: number, amountB: number) {
+    await program.methods
+      .poolDeposit(new anchor.BN(amountA), new anchor.BN(amountB), new anchor.BN(1))
+      .accounts(await liquidityAccounts())
+      .signers([ownerKP])
+      .rpc();
+  }
+
+  async function createPool(pool: PublicKey, a: PublicKey, b: PublicKey) {
+    await program.methods
+      .poolCreate(30)
+      .accounts({
+        pool,
+        lpMint: PublicKey.findProgramAddressSync(
+          [Buffer.from(POOL_LP_MINT_SEED), pool.toBuffer()],
+          program.programId
+        )[0],
+        lpLock: PublicKey.findProgramAddressSync(
+          [Buffer.from(POOL_LP_LOCK_SEED), pool.toBuffer()],
+          program.programId
+        )[0],
+        vaultA: getVaultPDA(a, pool)[0],
+        vaultB: getVaultPDA(b, pool)[0],
+        vaultAuthority: getVaultAuthorityPDA(pool)[0],
+        mintA: a,
+        mintB: b,
+        mintAEntry: getMintEntryPDA(a)[0],
+        mintBEntry: getMintEntryPDA(b)[0],
+        creator: ownerKP.publicKey,
+        config: getConfigPDA(program)[0],
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([ownerKP])
+      .rpc();
+  }
+
+  async function liquidityAccounts() {
+    return {
+      owner: ownerKP.publicKey,
+      ownerTokenA: (await getATA(ownerKP, mintA)).address,
+      ownerTokenB: (await getATA(ownerKP, mintB)).address,
+      ownerLpToken: (await getATA(ownerKP, lpMintPDA)).address,
+      pool: poolPDA,
+      lpMint: lpMintPDA,
+      lpLock: lpLockPDA,
+      vaultA: getVaultPDA(mintA)[0],
+      vaultB: getVaultPDA(mintB)[0],
+      vaultAuthority: getVaultAuthorityPDA(poolPDA)[0],
+      config: getConfigPDA(program)[0],
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+  }
+
+  async function swap(amountIn: number, minAmountOut: number, aToB: boolean) {
+    await program.methods
+      .poolSwap(new anchor.BN(amountIn), new anchor.BN(minAmountOut), aToB)
+      .accounts({
+        trader: traderKP.publicKey,
+        traderTokenA: (await getATA(traderKP, mintA)).address,
+        traderTokenB: (await getATA(traderKP, mintB)).address,
+        pool: poolPDA,
+        vaultA: getVaultPDA(mintA)[0],
+        vaultB: getVaultPDA(mintB)[0],
+        vaultAuthority: getVaultAuthorityPDA(poolPDA)[0],
+        config: getConfigPDA(program)[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([traderKP])
+      .rpc();
+  }
+
+  function getVaultPDA(mint: PublicKey, pool: PublicKey = poolPDA) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_POOL_SEED), pool.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getVaultAuthorityPDA(pool: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), pool.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getMintEntryPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(payer: Keypair, mint: PublicKey) {
+    return await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mint,
+      payer.publicKey
+    );
+  }
+});
@@ -0,0 +1,238 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  freezeAccount,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const VAULT_NFT_SOL_SEED = "vault-nft-2-sol-seed";
+
+describe("wen3ex reclaim", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const strangerKP = Keypair.generate();
+  const marketAccountKP = Keypair.generate();
+  const depositAmount = 20;
+  let mint: PublicKey;
+  let creatorToken: PublicKey;
+
+  it("reclaim before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, strangerKP.publicKey, 1);
+    // the creator keeps the freeze authority, to break the listing
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      creatorKP.publicKey,
+      0
+    );
+    creatorToken = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        mint,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(connection, creatorKP, mint, creatorToken, creatorKP, 100);
+    await createToken2SolMarket();
+  });
+
+  it("A listing holding its deposit is not reclaimed", async () => {
+    try {
+      await reclaim(creatorKP);
+      assert.fail("reclaim should fail while the vault holds the deposit");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VaultConsistent");
+    }
+  });
+
+  it("A vault holding more than its deposit is not reclaimed", async () => {
+    await mintTo(connection, creatorKP, mint, getVaultPDA(), creatorKP, 5);
+    try {
+      await reclaim(creatorKP);
+      assert.fail("reclaim should fail while the vault covers the deposit");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VaultConsistent");
+    }
+  });
+
+  it("An nft listing is only reclaimed from its own vault", async () => {
+    const nftMarketKP = Keypair.generate();
+    const nft = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    const creatorNft = (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        nft,
+        creatorKP.publicKey
+      )
+    ).address;
+    await mintTo(connection, creatorKP, nft, creatorNft, creatorKP, 1);
+    await program.methods
+      .marketNftToSolCreate(
+        new anchor.BN(1),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: nftMarketKP.publicKey,
+        vaultTokenAccount: getPDA(VAULT_NFT_SOL_SEED, nftMarketKP.publicKey),
+        creatorTokenAccount: creatorNft,
+        mint: nft,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, nft),
+        mintIndex: getPDA(MINT_INDEX_SEED, nft),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketNftToSolAccount.createInstruction(
+          nftMarketKP
+        ),
+      ])
+      .signers([nftMarketKP, creatorKP])
+      .rpc();
+
+    // listed into the token vault, so the programmable one is refused
+    const programmableVault = getAssociatedTokenAddressSync(
+      nft,
+      getPDA(VAULT_AUTHORITY_SEED, nftMarketKP.publicKey),
+      true
+    );
+    try {
+      await reclaim(
+        creatorKP,
+        nftMarketKP.publicKey,
+        programmableVault,
+        nft,
+        creatorNft
+      );
+      assert.fail("reclaim should only take the recorded vault");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("IncorrectVaultTokenAccount");
+    }
+  });
+
+  it("Reclaim a listing whose vault is frozen", async () => {
+    await freezeAccount(connection, creatorKP, getVaultPDA(), mint, creatorKP);
+    // before the grace period, only the creator
+    try {
+      await reclaim(strangerKP);
+      assert.fail("reclaim by a stranger should wait for the grace period");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("ReclaimTooEarly");
+    }
+
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    await reclaim(creatorKP);
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(creatorSol);
+    // the frozen vault stays behind
+    const vault = await getAccount(connection, getVaultPDA());
+    expect(vault.isFrozen).to.true;
+  });
+
+  async function reclaim(
+    caller: Keypair,
+    market = marketAccountKP.publicKey,
+    vault = getVaultPDA(),
+    listedMint = mint,
+    creatorTokenAccount = creatorToken
+  ) {
+    await program.methods
+      .reclaimMarket()
+      .accounts({
+        marketAccount: market,
+        vaultTokenAccount: vault,
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, market),
+        caller: caller.publicKey,
+        creator: creatorKP.publicKey,
+        creatorTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mintIndex: getPDA(MINT_INDEX_SEED, listedMint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .signers([caller])
+      .rpc();
+  }
+
+  async function createToken2SolMarket() {
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(depositAmount),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getVaultPDA(),
+        creatorTokenAccount: creatorToken,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+  }
+
+  function getVaultPDA() {
+    return getPDA(VAULT_TOKEN_SOL_SEED, marketAccountKP.publicKey);
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
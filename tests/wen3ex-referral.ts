@@ -0,0 +1,284 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const REFERRER_SEED = "referrer-seed";
+const BPS = 10_000;
+const FEE_BPS = 100;
+const REFERRAL_SHARE_BPS = 5_000;
+
+describe("wen3ex referral", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+  const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const referrerKP = Keypair.generate();
+  const treasuryKP = Keypair.generate();
+  const solAmount = LAMPORTS_PER_SOL / 10;
+  const fee = (solAmount * FEE_BPS) / BPS;
+  const referrerShare = (fee * REFERRAL_SHARE_BPS) / BPS;
+  let mint: PublicKey;
+
+  it("referral before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 3);
+    await airDrop(connection, referrerKP.publicKey, 1);
+    // funded, so the fee it receives needn't cover its rent
+    await airDrop(connection, treasuryKP.publicKey, 1);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      creatorKP,
+      mint,
+      await getATA(creatorKP),
+      creatorKP,
+      100
+    );
+    for (const kp of [referrerKP, takerKP]) {
+      await program.methods
+        .referrerRegister()
+        .accounts({
+          referrerAccount: getPDA(REFERRER_SEED, kp.publicKey),
+          referrer: kp.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([kp])
+        .rpc();
+    }
+  });
+
+  it("The fee is split between the treasury and the referrer", async () => {
+    const listing = await createListing();
+    const balances = await getBalances();
+    await setFees(FEE_BPS, REFERRAL_SHARE_BPS, treasuryKP.publicKey);
+    try {
+      await exchange(listing, referrerKP);
+    } finally {
+      await setFees(0, 0, admin);
+    }
+
+    const after = await getBalances();
+    expect(after.treasury).to.eq(balances.treasury + fee - referrerShare);
+    expect(after.referrer).to.eq(balances.referrer + referrerShare);
+    // the listing's and the vault's rent come back on top
+    expect(after.creator).to.gt(balances.creator + solAmount - fee);
+    const stats = await program.account.referrerAccount.fetch(
+      getPDA(REFERRER_SEED, referrerKP.publicKey)
+    );
+    expect(stats.trades.toNumber()).to.eq(1);
+    expect(stats.volume.toNumber()).to.eq(solAmount);
+    expect(stats.earnings.toNumber()).to.eq(referrerShare);
+  });
+
+  it("The creator pays its referral bps on top", async () => {
+    const referralBps = 200;
+    const listing = await createListing();
+    try {
+      await setReferral(listing, 1_001);
+      assert.fail("set referral should fail over 10%");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidFee");
+    }
+    await setReferral(listing, referralBps);
+
+    const balances = await getBalances();
+    await setFees(FEE_BPS, REFERRAL_SHARE_BPS, treasuryKP.publicKey);
+    try {
+      await exchange(listing, referrerKP);
+    } finally {
+      await setFees(0, 0, admin);
+    }
+
+    const makerShare = (solAmount * referralBps) / BPS;
+    const after = await getBalances();
+    expect(after.treasury).to.eq(balances.treasury + fee - referrerShare);
+    expect(after.referrer).to.eq(
+      balances.referrer + referrerShare + makerShare
+    );
+    expect(after.creator).to.gt(
+      balances.creator + solAmount - fee - makerShare
+    );
+    expect(after.creator).to.lt(balances.creator + solAmount - fee);
+  });
+
+  it("Without a fee to share, the referrer's stats don't move", async () => {
+    const referrerAccount = getPDA(REFERRER_SEED, referrerKP.publicKey);
+    const before = await program.account.referrerAccount.fetch(
+      referrerAccount
+    );
+    await exchange(await createListing(), referrerKP);
+
+    const after = await program.account.referrerAccount.fetch(
+      referrerAccount
+    );
+    expect(after.trades.toNumber()).to.eq(before.trades.toNumber());
+    expect(after.volume.toNumber()).to.eq(before.volume.toNumber());
+  });
+
+  it("A taker can't refer their own fill", async () => {
+    const listing = await createListing();
+    try {
+      await exchange(listing, takerKP);
+      assert.fail("exchange should fail with the taker as referrer");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("SelfReferral");
+    }
+  });
+
+  async function getBalances() {
+    return {
+      creator: await connection.getBalance(creatorKP.publicKey),
+      referrer: await connection.getBalance(referrerKP.publicKey),
+      treasury: await connection.getBalance(treasuryKP.publicKey),
+    };
+  }
+
+  async function exchange(listing: Keypair, referrer: Keypair) {
+    const [configPDA] = getConfigPDA(program);
+    const config = await program.account.configAccount.fetch(configPDA);
+    await program.methods
+      .marketTsExchange()
+      .accounts({
+        taker: takerKP.publicKey,
+        takerTokenAccount: await getATA(takerKP),
+        creator: creatorKP.publicKey,
+        marketAccount: listing.publicKey,
+        vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey),
+        mint,
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        config: configPDA,
+        treasury: config.treasury,
+        salePhase: PublicKey.findProgramAddressSync(
+          [
+            Buffer.from(SALE_PHASE_SEED),
+            creatorKP.publicKey.toBuffer(),
+            mint.toBuffer(),
+          ],
+          program.programId
+        )[0],
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .remainingAccounts([
+        { pubkey: referrer.publicKey, isSigner: false, isWritable: true },
+        {
+          pubkey: getPDA(REFERRER_SEED, referrer.publicKey),
+          isSigner: false,
+          isWritable: true,
+        },
+      ])
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function setReferral(listing: Keypair, referralBps: number) {
+    await program.methods
+      .marketTsSetReferral(referralBps)
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: listing.publicKey,
+      })
+      .signers([creatorKP])
+      .rpc();
+  }
+
+  async function setFees(
+    feeBps: number,
+    referralShareBps: number,
+    treasury: PublicKey
+  ) {
+    await program.methods
+      .setFees(feeBps, referralShareBps, treasury)
+      .accounts({ config: getConfigPDA(program)[0], admin })
+      .rpc();
+  }
+
+  async function createListing() {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(10),
+        new anchor.BN(solAmount),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_TOKEN_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: await getATA(creatorKP),
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function getATA(owner: Keypair) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+});
@@ -0,0 +1,277 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  getAccount,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const REFERRER_SEED = "referrer-seed";
+
+describe("wen3ex route", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const mintAuthority = Keypair.generate();
+  const referrerKP = Keypair.generate();
+  // 10 tokens each, at 0.02 and 0.01 sol per token
+  const dearKP = Keypair.generate();
+  const cheapKP = Keypair.generate();
+  let mint: PublicKey;
+
+  it("route before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    await airDrop(connection, referrerKP.publicKey, 1);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      mintAuthority.publicKey,
+      null,
+      0
+    );
+    const creatorAta = await getATA(creatorKP.publicKey);
+    await mintTo(
+      connection,
+      creatorKP,
+      mint,
+      creatorAta.address,
+      mintAuthority,
+      20
+    );
+    await createToken2SolMarket(dearKP, 10, LAMPORTS_PER_SOL / 5);
+    await createToken2SolMarket(cheapKP, 10, LAMPORTS_PER_SOL / 10);
+  });
+
+  it("Route buy rejects an average price above the max", async () => {
+    try {
+      await routeBuy(15, 13_000_000);
+      assert.fail("route should fail above max_avg_price");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("PriceAboveMax");
+    }
+  });
+
+  it("Route buy fills the cheapest listing first, the next in part", async () => {
+    // 10 at 0.01 and 5 at 0.02, 0.2 sol for 15
+    await routeBuy(15, 14_000_000);
+
+    const takerToken = await getAccount(
+      connection,
+      (await getATA(takerKP.publicKey)).address
+    );
+    expect(Number(takerToken.amount)).to.eq(15);
+    expect(await connection.getAccountInfo(cheapKP.publicKey)).to.null;
+
+    const dear = await program.account.marketTsAccount.fetch(
+      dearKP.publicKey
+    );
+    expect(dear.tokenAmount.toNumber()).to.eq(5);
+    expect(dear.solAmount.toNumber()).to.eq(LAMPORTS_PER_SOL / 10);
+    const vault = await getAccount(connection, getVaultPDA(dearKP)[0]);
+    expect(Number(vault.amount)).to.eq(5);
+  });
+
+  it("A referrer passed after the listings takes its share", async () => {
+    await program.methods
+      .referrerRegister()
+      .accounts({
+        referrerAccount: getReferrerPDA(),
+        referrer: referrerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([referrerKP])
+      .rpc();
+    const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+    const referrerSol = await connection.getBalance(referrerKP.publicKey);
+    // 1% fee, half of it to the referrer
+    await setFees(100, 5_000, admin);
+    try {
+      // the 5 left at 0.02, 0.1 sol
+      await routeBuy(5, 20_000_000, referrerKP);
+    } finally {
+      await setFees(0, 0, admin);
+    }
+
+    const fee = LAMPORTS_PER_SOL / 10 / 100;
+    expect(await connection.getBalance(referrerKP.publicKey)).to.eq(
+      referrerSol + fee / 2
+    );
+    const referrer = await program.account.referrerAccount.fetch(
+      getReferrerPDA()
+    );
+    expect(referrer.trades.toNumber()).to.eq(1);
+    expect(referrer.earnings.toNumber()).to.eq(fee / 2);
+  });
+
+  async function setFees(
+    feeBps: number,
+    referralShareBps: number,
+    treasury: PublicKey
+  ) {
+    await program.methods
+      .setFees(feeBps, referralShareBps, treasury)
+      .accounts({
+        config: getConfigPDA(program)[0],
+        admin: (provider as anchor.AnchorProvider).wallet.publicKey,
+      })
+      .rpc();
+  }
+
+  function getReferrerPDA() {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(REFERRER_SEED), referrerKP.publicKey.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function routeBuy(
+    quantity: number,
+    maxAvgPrice: number,
+    referrer?: Keypair
+  ) {
+    const listingAccounts = [dearKP, cheapKP].flatMap((market) =>
+      [
+        market.publicKey,
+        getVaultPDA(market)[0],
+        getVaultAuthorityPDA(market)[0],
+        creatorKP.publicKey,
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from(SALE_PHASE_SEED),
+            creatorKP.publicKey.toBuffer(),
+            mint.toBuffer(),
+          ],
+          program.programId
+        )[0],
+        getMintIndexPDA(mint)[0],
+        getCreatorIndexPDA(creatorKP.publicKey)[0],
+      ].map((pubkey, i) => ({
+        pubkey,
+        isWritable: i != 4,
+        isSigner: false,
+      }))
+    );
+    const remainingAccounts = referrer
+      ? listingAccounts.concat(
+          [referrer.publicKey, getReferrerPDA()].map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+      : listingAccounts;
+    await program.methods
+      .routeBuy(new anchor.BN(quantity), new anchor.BN(maxAvgPrice))
+      .accounts({
+        taker: takerKP.publicKey,
+        takerTokenAccount: (await getATA(takerKP.publicKey)).address,
+        mint,
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function createToken2SolMarket(
+    marketKP: Keypair,
+    tokenAmount: number,
+    solAmount: number
+  ) {
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(tokenAmount),
+        new anchor.BN(solAmount),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        mintIndex: getMintIndexPDA(mint)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
+        marketAccount: marketKP.publicKey,
+        vaultTokenAccount: getVaultPDA(marketKP)[0],
+        creatorTokenAccount: (await getATA(creatorKP.publicKey)).address,
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: PublicKey.findProgramAddressSync(
+          [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+          program.programId
+        )[0],
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(marketKP),
+      ])
+      .signers([marketKP, creatorKP])
+      .rpc();
+  }
+
+  function getVaultPDA(marketKP: Keypair) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_TOKEN_SOL_SEED), marketKP.publicKey.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getVaultAuthorityPDA(marketKP: Keypair) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), marketKP.publicKey.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function getATA(owner: PublicKey) {
+    return await getOrCreateAssociatedTokenAccount(
+      connection,
+      creatorKP,
+      mint,
+      owner
+    );
+  }
+
+  function getMintIndexPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_INDEX_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getCreatorIndexPDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CREATOR_INDEX_SEED), creator.toBuffer()],
+      program.programId
+    );
+  }
+});
@@ -0,0 +1,329 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { keccak_256 } from "@noble/hashes/sha3";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const ALLOWLIST_CLAIM_SEED = "allowlist-claim-seed";
+
+// leaves are keccak(wallet, allocation as u64 le), pairs are hashed sorted,
+// see SalePhaseAccount::verify
+function leaf(wallet: PublicKey, allocation: number) {
+  const amount = Buffer.alloc(8);
+  amount.writeBigUInt64LE(BigInt(allocation));
+  return Buffer.from(keccak_256(Buffer.concat([wallet.toBuffer(), amount])));
+}
+
+function hashPair(a: Buffer, b: Buffer) {
+  const [left, right] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return Buffer.from(keccak_256(Buffer.concat([left, right])));
+}
+
+// the root and each leaf's proof, bottom up, an odd node moves up as is
+function merkleTree(leaves: Buffer[]) {
+  const proofs: Buffer[][] = leaves.map(() => []);
+  let positions = leaves.map((_, i) => i);
+  let level = leaves;
+  while (level.length > 1) {
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(
+        i + 1 < level.length ? hashPair(level[i], level[i + 1]) : level[i]
+      );
+    }
+    positions.forEach((position, i) => {
+      const sibling = position ^ 1;
+      if (sibling < level.length) {
+        proofs[i].push(level[sibling]);
+      }
+    });
+    positions = positions.map((position) => position >> 1);
+    level = next;
+  }
+  return { root: level[0], proofs };
+}
+
+describe("wen3ex sale phase", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const bigKP = Keypair.generate();
+  const smallKP = Keypair.generate();
+  const outsiderKP = Keypair.generate();
+  const tokenAmount = 10;
+  const bigAllocation = 2 * tokenAmount;
+  const smallAllocation = tokenAmount;
+  let mint: PublicKey;
+  let root: Buffer;
+  let proofs: Buffer[][];
+
+  it("sale phase before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    for (const kp of [bigKP, smallKP, outsiderKP]) {
+      await airDrop(connection, kp.publicKey, 2);
+    }
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      creatorKP,
+      mint,
+      await getATA(creatorKP),
+      creatorKP,
+      100
+    );
+
+    ({ root, proofs } = merkleTree([
+      leaf(bigKP.publicKey, bigAllocation),
+      leaf(smallKP.publicKey, smallAllocation),
+      leaf(creatorKP.publicKey, 1),
+    ]));
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .salePhaseSet(
+        Array.from(root),
+        new anchor.BN(now - 60),
+        new anchor.BN(now + 3600)
+      )
+      .accounts({
+        salePhase: getSalePhasePDA(),
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creatorKP])
+      .rpc();
+  });
+
+  it("A presale listing needs the allowlist", async () => {
+    const listing = await createListing();
+    try {
+      await program.methods
+        .marketTsExchange()
+        .accounts(await exchangeAccounts(listing, bigKP))
+        .signers([bigKP])
+        .rpc();
+      assert.fail("exchange should fail during the presale");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("AllowlistRequired");
+    }
+  });
+
+  it("An allowlisted wallet takes a listing with its proof", async () => {
+    const listing = await createListing();
+    await exchangeAllowlisted(listing, bigKP, bigAllocation, proofs[0]);
+
+    expect(await connection.getAccountInfo(listing.publicKey)).to.null;
+    const claim = await program.account.allowlistClaimAccount.fetch(
+      getClaimPDA(bigKP)
+    );
+    expect(claim.taken.toNumber()).to.eq(tokenAmount);
+  });
+
+  it("A bad proof is refused", async () => {
+    const listing = await createListing();
+    // someone else's proof
+    await expectNotAllowlisted(
+      exchangeAllowlisted(listing, outsiderKP, bigAllocation, proofs[0])
+    );
+    // a bigger allocation than the leaf's
+    await expectNotAllowlisted(
+      exchangeAllowlisted(listing, smallKP, bigAllocation, proofs[1])
+    );
+    // a proof missing a level
+    await expectNotAllowlisted(
+      exchangeAllowlisted(
+        listing,
+        smallKP,
+        smallAllocation,
+        proofs[1].slice(1)
+      )
+    );
+    expect(await connection.getAccountInfo(listing.publicKey)).to.not.null;
+  });
+
+  it("A wallet takes no more than its allocation", async () => {
+    const first = await createListing();
+    const second = await createListing();
+    await exchangeAllowlisted(first, smallKP, smallAllocation, proofs[1]);
+    try {
+      await exchangeAllowlisted(second, smallKP, smallAllocation, proofs[1]);
+      assert.fail("exchange should fail over the allocation");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("AllocationExceeded");
+    }
+
+    // the big allocation has room for one more listing, exactly
+    await exchangeAllowlisted(second, bigKP, bigAllocation, proofs[0]);
+    const claim = await program.account.allowlistClaimAccount.fetch(
+      getClaimPDA(bigKP)
+    );
+    expect(claim.taken.toNumber()).to.eq(bigAllocation);
+    try {
+      await exchangeAllowlisted(
+        await createListing(),
+        bigKP,
+        bigAllocation,
+        proofs[0]
+      );
+      assert.fail("exchange should fail over the allocation");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("AllocationExceeded");
+    }
+  });
+
+  async function expectNotAllowlisted(exchange: Promise<void>) {
+    try {
+      await exchange;
+      assert.fail("exchange should fail without a valid proof");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NotAllowlisted");
+    }
+  }
+
+  async function exchangeAllowlisted(
+    listing: Keypair,
+    taker: Keypair,
+    allocation: number,
+    proof: Buffer[]
+  ) {
+    await program.methods
+      .marketTsExchangeAllowlisted(
+        new anchor.BN(allocation),
+        proof.map((node) => Array.from(node))
+      )
+      .accounts({
+        exchange: await exchangeAccounts(listing, taker),
+        allowlistClaim: getClaimPDA(taker),
+        taker: taker.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([taker])
+      .rpc();
+  }
+
+  async function exchangeAccounts(listing: Keypair, taker: Keypair) {
+    return {
+      taker: taker.publicKey,
+      takerTokenAccount: await getATA(taker),
+      creator: creatorKP.publicKey,
+      marketAccount: listing.publicKey,
+      vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey),
+      mint,
+      vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      config: getConfigPDA(program)[0],
+      treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+      salePhase: getSalePhasePDA(),
+      mintIndex: getPDA(MINT_INDEX_SEED, mint),
+      creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+    };
+  }
+
+  async function createListing() {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(tokenAmount),
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_TOKEN_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: await getATA(creatorKP),
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  function getSalePhasePDA() {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(SALE_PHASE_SEED),
+        creatorKP.publicKey.toBuffer(),
+        mint.toBuffer(),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function getClaimPDA(taker: Keypair) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from(ALLOWLIST_CLAIM_SEED),
+        getSalePhasePDA().toBuffer(),
+        taker.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function getATA(owner: Keypair) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+});
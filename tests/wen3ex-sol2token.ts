@@ -15,8 +15,6 @@ import { assert, expect } from "chai";
 
 import { Wen3ex, IDL } from "../target/types/wen3ex";
 
-const VAULT_AUTHORITY_SEED = "vault-authority-seed";
-const VAULT_SOL_2_TOKEN_SEED = "vault-sol-2-token-seed";
 
 describe("wen3ex token2sol", async () => {
   // Configure the client to use the local cluster.
@@ -53,8 +51,9 @@ describe("wen3ex token2sol", async () => {
   const marketAccountKP = anchor.web3.Keypair.generate(); //
 
   it("wen3ex sol2token before", async () => {
-    await airDrop(creatorKP.publicKey, 2);
-    await airDrop(takerKP.publicKey, 2);
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
     let creatorSol = await printSolBalance(creatorKP.publicKey);
     console.log("before all", creatorSol);
     // 1. create mint
@@ -131,7 +130,7 @@ describe("wen3ex token2sol", async () => {
     );
     const [vaultPDA, _vaultAccountBump] = PublicKey.findProgramAddressSync(
       [
-        Buffer.from(VAULT_SOL_2_TOKEN_SEED),
+        Buffer.from(VAULT_SOL_TOKEN_SEED),
         marketAccountKP.publicKey.toBuffer(),
       ],
       program.programId
@@ -155,6 +154,8 @@ describe("wen3ex token2sol", async () => {
     await program.methods
       .marketStCancel()
       .accounts({
+        mintIndex: getMintIndexPDA(rubyKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         creator: creatorKP.publicKey,
         creatorTokenAccount: creatorRubyAta.address,
         vaultTokenAccount: vaultPDA,
@@ -213,24 +214,20 @@ describe("wen3ex token2sol", async () => {
       await program.methods
         .marketStExchange()
         .accounts({
+          mintIndex: getMintIndexPDA(rubyKP.publicKey)[0],
+          creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
           taker: takerKP.publicKey,
           takerTokenAccount: takerRubyAta.address,
           creator: creatorKP.publicKey,
-
+          creatorTokenAccount: creatorRubyAta.address,
           vaultTokenAccount: vaultPDA,
           vaultAuthority: vaultAuthorityPDA,
           marketAccount: marketAccountKP.publicKey,
           mint: rubyKP.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
+          config: getConfigPDA(program)[0],
+          treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
         })
-
-        .remainingAccounts([
-          {
-            pubkey: creatorRubyAta.address,
-            isSigner: false,
-            isWritable: true,
-          },
-        ])
         .signers([takerKP])
         .rpc();
     } catch (error) {
@@ -253,10 +250,31 @@ describe("wen3ex token2sol", async () => {
     return solBalance;
   }
 
+  function getMintIndexPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_INDEX_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getCreatorIndexPDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CREATOR_INDEX_SEED), creator.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getMintEntryPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
   function getVaultPDA() {
     return PublicKey.findProgramAddressSync(
       [
-        Buffer.from(VAULT_SOL_2_TOKEN_SEED),
+        Buffer.from(VAULT_SOL_TOKEN_SEED),
         marketAccountKP.publicKey.toBuffer(),
       ],
       program.programId
@@ -285,9 +303,14 @@ describe("wen3ex token2sol", async () => {
     await program.methods
       .marketStCreate(
         new anchor.BN(creatorRubyAmount),
-        new anchor.BN(LAMPORTS_PER_SOL)
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
       )
       .accounts({
+        mintIndex: getMintIndexPDA(rubyKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         marketAccount: marketAccountKP.publicKey,
         vaultTokenAccount: vaultPDA,
         creatorTokenAccount: creatorRubyAta.address,
@@ -296,6 +319,8 @@ describe("wen3ex token2sol", async () => {
         systemProgram: anchor.web3.SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getMintEntryPDA(rubyKP.publicKey)[0],
       })
       .preInstructions([
         await program.account.marketStAccount.createInstruction(
@@ -329,14 +354,6 @@ describe("wen3ex token2sol", async () => {
     await createMint(connection, payer, mintAuthority.publicKey, null, 0, kp);
   }
 
-  async function airDrop(pubkey: PublicKey, num: number) {
-    const airdropSignature = await connection.requestAirdrop(
-      pubkey,
-      num * LAMPORTS_PER_SOL
-    );
-    await confirmSignature(airdropSignature);
-  }
-
   async function confirmSignature(signature: string) {
     const latestBlockHash = await connection.getLatestBlockhash();
     await connection.confirmTransaction({
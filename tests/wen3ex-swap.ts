@@ -0,0 +1,523 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import {
+  CollectionDetails,
+  PROGRAM_ID as TOKEN_METADATA_ID,
+  TokenStandard,
+  createCreateInstruction,
+  createMintInstruction,
+  createVerifySizedCollectionItemInstruction,
+} from "@metaplex-foundation/mpl-token-metadata";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const VAULT_SWAP_SEED = "vault-swap-seed";
+const REFERRER_SEED = "referrer-seed";
+
+describe("wen3ex swap", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const referrerKP = Keypair.generate();
+  // the creator's offered nfts, and the nft the taker gives
+  let offeredMints: PublicKey[];
+  let wantedMint: PublicKey;
+
+  it("swap before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    await airDrop(connection, referrerKP.publicKey, 1);
+    offeredMints = [
+      await mintNft(creatorKP),
+      await mintNft(creatorKP),
+      await mintNft(creatorKP),
+    ];
+    wantedMint = await mintNft(takerKP);
+  });
+
+  it("Swap two nfts for the wanted one, the taker adds sol", async () => {
+    const marketAccountKP = await createSwap(
+      offeredMints[0],
+      wantedMint,
+      new anchor.BN(LAMPORTS_PER_SOL / 10),
+      new anchor.BN(0)
+    );
+    await depositNft(marketAccountKP.publicKey, offeredMints[1]);
+    const market = await program.account.marketSwapAccount.fetch(
+      marketAccountKP.publicKey
+    );
+    expect(market.offered.length).to.eq(2);
+
+    // a wrong nft is refused
+    const otherMint = await mintNft(takerKP);
+    try {
+      await exchange(
+        marketAccountKP.publicKey,
+        otherMint,
+        offeredMints.slice(0, 2)
+      );
+      assert.fail("exchange should fail with an nft not asked for");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NftNotWanted");
+    }
+
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    await exchange(
+      marketAccountKP.publicKey,
+      wantedMint,
+      offeredMints.slice(0, 2)
+    );
+
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(creatorSol);
+    for (const mint of offeredMints.slice(0, 2)) {
+      const taken = await getAccount(connection, await getATA(takerKP, mint));
+      expect(Number(taken.amount)).to.eq(1);
+    }
+    const received = await getAccount(
+      connection,
+      await getATA(creatorKP, wantedMint)
+    );
+    expect(Number(received.amount)).to.eq(1);
+  });
+
+  it("A mint that is not an nft is refused", async () => {
+    const fungible = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      creatorKP,
+      fungible,
+      await getATA(creatorKP, fungible),
+      creatorKP,
+      2
+    );
+    try {
+      await createSwap(
+        fungible,
+        wantedMint,
+        new anchor.BN(0),
+        new anchor.BN(0)
+      );
+      assert.fail("create should fail for a supply of 2");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NotAnNft");
+    }
+  });
+
+  it("Swap for any nft of a verified collection", async () => {
+    const collection = await createMetaplexNft(takerKP, null);
+    const member = await createMetaplexNft(takerKP, collection);
+    const unverified = await createMetaplexNft(takerKP, collection);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createVerifySizedCollectionItemInstruction({
+          metadata: getMetadataPDA(member),
+          collectionAuthority: takerKP.publicKey,
+          payer: takerKP.publicKey,
+          collectionMint: collection,
+          collection: getMetadataPDA(collection),
+          collectionMasterEditionAccount: getEditionPDA(collection),
+        })
+      ),
+      [takerKP]
+    );
+
+    const offered = await mintNft(creatorKP);
+    const marketAccountKP = await createSwap(
+      offered,
+      PublicKey.default,
+      new anchor.BN(0),
+      new anchor.BN(0),
+      collection
+    );
+    try {
+      await exchange(marketAccountKP.publicKey, unverified, [offered]);
+      assert.fail("exchange should fail with an unverified member");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NftNotWanted");
+    }
+    await exchange(marketAccountKP.publicKey, member, [offered]);
+
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    const taken = await getAccount(connection, await getATA(takerKP, offered));
+    expect(Number(taken.amount)).to.eq(1);
+    const received = await getAccount(
+      connection,
+      await getATA(creatorKP, member)
+    );
+    expect(Number(received.amount)).to.eq(1);
+  });
+
+  it("A referrer after the vaults shares the taker_sol fee", async () => {
+    await program.methods
+      .referrerRegister()
+      .accounts({
+        referrerAccount: getPDA(REFERRER_SEED, referrerKP.publicKey),
+        referrer: referrerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([referrerKP])
+      .rpc();
+    const offered = await mintNft(creatorKP);
+    const wanted = await mintNft(takerKP);
+    const marketAccountKP = await createSwap(
+      offered,
+      wanted,
+      new anchor.BN(LAMPORTS_PER_SOL / 10),
+      new anchor.BN(0)
+    );
+    const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+    const referrerSol = await connection.getBalance(referrerKP.publicKey);
+    // 1% fee, half of it to the referrer
+    await setFees(100, 5_000, admin);
+    try {
+      await exchange(marketAccountKP.publicKey, wanted, [offered], referrerKP);
+    } finally {
+      await setFees(0, 0, admin);
+    }
+
+    const fee = LAMPORTS_PER_SOL / 10 / 100;
+    expect(await connection.getBalance(referrerKP.publicKey)).to.eq(
+      referrerSol + fee / 2
+    );
+    const referrer = await program.account.referrerAccount.fetch(
+      getPDA(REFERRER_SEED, referrerKP.publicKey)
+    );
+    expect(referrer.trades.toNumber()).to.eq(1);
+    expect(referrer.earnings.toNumber()).to.eq(fee / 2);
+  });
+
+  it("Cancel a swap, the creator's sol comes back", async () => {
+    const marketAccountKP = await createSwap(
+      offeredMints[2],
+      wantedMint,
+      new anchor.BN(0),
+      new anchor.BN(LAMPORTS_PER_SOL / 10)
+    );
+    const creatorSol = await connection.getBalance(creatorKP.publicKey);
+    await program.methods
+      .marketSwapCancel()
+      .accounts({
+        creator: creatorKP.publicKey,
+        marketAccount: marketAccountKP.publicKey,
+        vaultAuthority: getVaultAuthorityPDA(marketAccountKP.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mintIndex: getPDA(MINT_INDEX_SEED, offeredMints[2]),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .remainingAccounts(
+        await vaultAccounts(
+          marketAccountKP.publicKey,
+          [offeredMints[2]],
+          creatorKP
+        )
+      )
+      .signers([creatorKP])
+      .rpc();
+
+    expect(await connection.getAccountInfo(marketAccountKP.publicKey)).to.null;
+    expect(await connection.getBalance(creatorKP.publicKey)).to.gt(
+      creatorSol + LAMPORTS_PER_SOL / 10
+    );
+    const back = await getAccount(
+      connection,
+      await getATA(creatorKP, offeredMints[2])
+    );
+    expect(Number(back.amount)).to.eq(1);
+  });
+
+  async function setFees(
+    feeBps: number,
+    referralShareBps: number,
+    treasury: PublicKey
+  ) {
+    await program.methods
+      .setFees(feeBps, referralShareBps, treasury)
+      .accounts({
+        config: getConfigPDA(program)[0],
+        admin: (provider as anchor.AnchorProvider).wallet.publicKey,
+      })
+      .rpc();
+  }
+
+  async function createSwap(
+    mint: PublicKey,
+    wantMint: PublicKey,
+    takerSol: anchor.BN,
+    creatorSol: anchor.BN,
+    wantCollection = PublicKey.default
+  ) {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketSwapCreate(wantMint, wantCollection, takerSol, creatorSol)
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getVaultPDA(marketAccountKP.publicKey, mint),
+        creatorTokenAccount: await getATA(creatorKP, mint),
+        mint,
+        creator: creatorKP.publicKey,
+        vaultAuthority: getVaultAuthorityPDA(marketAccountKP.publicKey),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketSwapAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  async function depositNft(marketAccount: PublicKey, mint: PublicKey) {
+    await program.methods
+      .marketSwapDeposit()
+      .accounts({
+        marketAccount,
+        vaultTokenAccount: getVaultPDA(marketAccount, mint),
+        creatorTokenAccount: await getATA(creatorKP, mint),
+        mint,
+        creator: creatorKP.publicKey,
+        vaultAuthority: getVaultAuthorityPDA(marketAccount),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+      })
+      .signers([creatorKP])
+      .rpc();
+  }
+
+  async function exchange(
+    marketAccount: PublicKey,
+    takerMint: PublicKey,
+    offered: PublicKey[],
+    referrer?: Keypair
+  ) {
+    const remainingAccounts = await vaultAccounts(
+      marketAccount,
+      offered,
+      takerKP
+    );
+    if (referrer) {
+      for (const pubkey of [
+        referrer.publicKey,
+        getPDA(REFERRER_SEED, referrer.publicKey),
+      ]) {
+        remainingAccounts.push({ pubkey, isWritable: true, isSigner: false });
+      }
+    }
+    await program.methods
+      .marketSwapExchange()
+      .accounts({
+        taker: takerKP.publicKey,
+        takerNftAccount: await getATA(takerKP, takerMint),
+        takerMint,
+        // only read when asked by collection
+        takerMetadata: getMetadataPDA(takerMint),
+        creatorNftAccount: await getATA(creatorKP, takerMint),
+        creator: creatorKP.publicKey,
+        marketAccount,
+        vaultAuthority: getVaultAuthorityPDA(marketAccount),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        mintIndex: getPDA(MINT_INDEX_SEED, offered[0]),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([takerKP])
+      .rpc();
+  }
+
+  // [vault, destination] per offered nft, see SWAP_ACCOUNTS_PER_NFT
+  async function vaultAccounts(
+    marketAccount: PublicKey,
+    offered: PublicKey[],
+    destinationOwner: Keypair
+  ) {
+    const accounts = [];
+    for (const mint of offered) {
+      accounts.push(
+        {
+          pubkey: getVaultPDA(marketAccount, mint),
+          isWritable: true,
+          isSigner: false,
+        },
+        {
+          pubkey: await getATA(destinationOwner, mint),
+          isWritable: true,
+          isSigner: false,
+        }
+      );
+    }
+    return accounts;
+  }
+
+  async function mintNft(owner: Keypair) {
+    const mint = await createMint(connection, owner, owner.publicKey, null, 0);
+    await mintTo(connection, owner, mint, await getATA(owner, mint), owner, 1);
+    return mint;
+  }
+
+  // a token metadata nft minted to `owner`, a sized collection without
+  // `collection`, an unverified member of it otherwise
+  async function createMetaplexNft(owner: Keypair, collection: PublicKey) {
+    const mintKP = Keypair.generate();
+    const mint = mintKP.publicKey;
+    const collectionDetails: CollectionDetails = collection
+      ? null
+      : { __kind: "V1", size: 0 };
+    const memberOf = collection ? { verified: false, key: collection } : null;
+    const create = createCreateInstruction(
+      {
+        metadata: getMetadataPDA(mint),
+        masterEdition: getEditionPDA(mint),
+        mint,
+        authority: owner.publicKey,
+        payer: owner.publicKey,
+        updateAuthority: owner.publicKey,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        splTokenProgram: TOKEN_PROGRAM_ID,
+      },
+      {
+        createArgs: {
+          __kind: "V1",
+          assetData: {
+            name: "nft",
+            symbol: "NFT",
+            uri: "",
+            sellerFeeBasisPoints: 0,
+            creators: null,
+            primarySaleHappened: false,
+            isMutable: true,
+            tokenStandard: TokenStandard.NonFungible,
+            collection: memberOf,
+            uses: null,
+            collectionDetails,
+            ruleSet: null,
+          },
+          decimals: 0,
+          printSupply: { __kind: "Zero" },
+        },
+      }
+    );
+    // a new mint signs its creation
+    create.keys.find((key) => key.pubkey.equals(mint)).isSigner = true;
+    const mintTo = createMintInstruction(
+      {
+        token: getAssociatedTokenAddressSync(mint, owner.publicKey),
+        tokenOwner: owner.publicKey,
+        metadata: getMetadataPDA(mint),
+        masterEdition: getEditionPDA(mint),
+        mint,
+        authority: owner.publicKey,
+        payer: owner.publicKey,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        splTokenProgram: TOKEN_PROGRAM_ID,
+        splAtaProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      },
+      { mintArgs: { __kind: "V1", amount: 1, authorizationData: null } }
+    );
+    await provider.sendAndConfirm(new Transaction().add(create, mintTo), [
+      owner,
+      mintKP,
+    ]);
+    return mint;
+  }
+
+  function getMetadataPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_ID.toBuffer(), mint.toBuffer()],
+      TOKEN_METADATA_ID
+    )[0];
+  }
+
+  function getEditionPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_ID
+    )[0];
+  }
+
+  async function getATA(owner: Keypair, mint: PublicKey) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+
+  function getVaultPDA(marketAccount: PublicKey, mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_SWAP_SEED), marketAccount.toBuffer(), mint.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  function getVaultAuthorityPDA(marketAccount: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(VAULT_AUTHORITY_SEED), marketAccount.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
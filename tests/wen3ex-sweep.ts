@@ -0,0 +1,315 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const REFERRER_SEED = "referrer-seed";
+const MARKET_TS = 1 << 1;
+const SWEEP_ACCOUNTS_PER_LISTING = 8;
+
+describe("wen3ex sweep", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+  const admin = (provider as anchor.AnchorProvider).wallet.publicKey;
+
+  const creatorKP = Keypair.generate();
+  const presaleCreatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const referrerKP = Keypair.generate();
+  const tokenAmount = 10;
+  const solAmount = LAMPORTS_PER_SOL / 10;
+  let mint: PublicKey;
+  let takerToken: PublicKey;
+
+  it("sweep before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, presaleCreatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    await airDrop(connection, referrerKP.publicKey, 1);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    for (const kp of [creatorKP, presaleCreatorKP]) {
+      await mintTo(
+        connection,
+        creatorKP,
+        mint,
+        await getATA(kp),
+        creatorKP,
+        100
+      );
+    }
+    takerToken = await getATA(takerKP);
+
+    // the presale creator's listings of the mint are in presale for an hour
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .salePhaseSet(
+        Array(32).fill(0),
+        new anchor.BN(now - 60),
+        new anchor.BN(now + 3600)
+      )
+      .accounts({
+        salePhase: getSalePhasePDA(presaleCreatorKP.publicKey),
+        mint,
+        creator: presaleCreatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([presaleCreatorKP])
+      .rpc();
+  });
+
+  it("Sweep fills open listings and skips the rest", async () => {
+    const open = await createListing(creatorKP, 0);
+    const notOpen = await createListing(
+      creatorKP,
+      Math.floor(Date.now() / 1000) + 3600
+    );
+    const presale = await createListing(presaleCreatorKP, 0);
+    const another = await createListing(creatorKP, 0);
+
+    await sweep(
+      [
+        [open, creatorKP],
+        [notOpen, creatorKP],
+        [presale, presaleCreatorKP],
+        [another, creatorKP],
+      ],
+      []
+    );
+
+    expect(await connection.getAccountInfo(open.publicKey)).to.null;
+    expect(await connection.getAccountInfo(another.publicKey)).to.null;
+    expect(await connection.getAccountInfo(notOpen.publicKey)).to.not.null;
+    expect(await connection.getAccountInfo(presale.publicKey)).to.not.null;
+    const taken = await getAccount(connection, takerToken);
+    expect(Number(taken.amount)).to.eq(2 * tokenAmount);
+  });
+
+  it("Taken and paused listings are skipped", async () => {
+    const taken = await createListing(creatorKP, 0);
+    await sweep([[taken, creatorKP]], []);
+
+    // taken meanwhile
+    const before = await getAccount(connection, takerToken);
+    await sweep([[taken, creatorKP]], []);
+    let after = await getAccount(connection, takerToken);
+    expect(Number(after.amount)).to.eq(Number(before.amount));
+
+    const paused = await createListing(creatorKP, 0);
+    await setPaused(MARKET_TS, true);
+    try {
+      await sweep([[paused, creatorKP]], []);
+    } finally {
+      await setPaused(MARKET_TS, false);
+    }
+    after = await getAccount(connection, takerToken);
+    expect(Number(after.amount)).to.eq(Number(before.amount));
+    expect(await connection.getAccountInfo(paused.publicKey)).to.not.null;
+  });
+
+  it("Listings come in groups of 8 accounts", async () => {
+    const listing = await createListing(creatorKP, 0);
+    const accounts = await listingAccounts(listing, creatorKP);
+    expect(accounts.length).to.eq(SWEEP_ACCOUNTS_PER_LISTING);
+    try {
+      await sweepAccounts(accounts.slice(0, SWEEP_ACCOUNTS_PER_LISTING - 1));
+      assert.fail("sweep should fail on a broken group");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidSweepAccounts");
+    }
+  });
+
+  it("Sweep shares the fee with a trailing referrer", async () => {
+    const referrerAccount = getPDA(REFERRER_SEED, referrerKP.publicKey);
+    await program.methods
+      .referrerRegister()
+      .accounts({
+        referrerAccount,
+        referrer: referrerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([referrerKP])
+      .rpc();
+
+    const listing = await createListing(creatorKP, 0);
+    const referrerSol = await connection.getBalance(referrerKP.publicKey);
+    // 1% fee, half of it to the referrer
+    await setFees(100, 5_000);
+    try {
+      await sweep(
+        [[listing, creatorKP]],
+        [referrerKP.publicKey, referrerAccount]
+      );
+    } finally {
+      await setFees(0, 0);
+    }
+
+    const fee = (solAmount * 100) / 10_000;
+    expect(await connection.getBalance(referrerKP.publicKey)).to.eq(
+      referrerSol + fee / 2
+    );
+    const stats = await program.account.referrerAccount.fetch(
+      referrerAccount
+    );
+    expect(stats.trades.toNumber()).to.eq(1);
+    expect(stats.volume.toNumber()).to.eq(solAmount);
+    expect(stats.earnings.toNumber()).to.eq(fee / 2);
+  });
+
+  async function sweep(
+    listings: [Keypair, Keypair][],
+    referrer: PublicKey[]
+  ) {
+    const accounts = [];
+    for (const [listing, creator] of listings) {
+      accounts.push(...(await listingAccounts(listing, creator)));
+    }
+    for (const pubkey of referrer) {
+      accounts.push({ pubkey, isSigner: false, isWritable: true });
+    }
+    await sweepAccounts(accounts);
+  }
+
+  async function sweepAccounts(accounts: anchor.web3.AccountMeta[]) {
+    await program.methods
+      .sweep(new anchor.BN(10 * LAMPORTS_PER_SOL), new anchor.BN(1_000))
+      .accounts({
+        taker: takerKP.publicKey,
+        config: getConfigPDA(program)[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        treasury: admin,
+      })
+      .remainingAccounts(accounts)
+      .signers([takerKP])
+      .rpc();
+  }
+
+  // see SWEEP_ACCOUNTS_PER_LISTING
+  async function listingAccounts(listing: Keypair, creator: Keypair) {
+    const writable = (pubkey: PublicKey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    });
+    return [
+      writable(listing.publicKey),
+      writable(getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey)),
+      writable(getPDA(VAULT_AUTHORITY_SEED, listing.publicKey)),
+      writable(creator.publicKey),
+      writable(takerToken),
+      {
+        pubkey: getSalePhasePDA(creator.publicKey),
+        isSigner: false,
+        isWritable: false,
+      },
+      writable(getPDA(MINT_INDEX_SEED, mint)),
+      writable(getPDA(CREATOR_INDEX_SEED, creator.publicKey)),
+    ];
+  }
+
+  async function createListing(creator: Keypair, startTime: number) {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(tokenAmount),
+        new anchor.BN(solAmount),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(startTime),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_TOKEN_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: await getATA(creator),
+        mint,
+        creator: creator.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creator.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creator])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  async function setPaused(markets: number, paused: boolean) {
+    await program.methods
+      .setPaused(markets, paused)
+      .accounts({ config: getConfigPDA(program)[0], admin })
+      .rpc();
+  }
+
+  async function setFees(feeBps: number, referralShareBps: number) {
+    await program.methods
+      .setFees(feeBps, referralShareBps, admin)
+      .accounts({ config: getConfigPDA(program)[0], admin })
+      .rpc();
+  }
+
+  function getSalePhasePDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(SALE_PHASE_SEED), creator.toBuffer(), mint.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function getATA(owner: Keypair) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+});
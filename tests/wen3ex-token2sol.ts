@@ -15,8 +15,7 @@ import { assert, expect } from "chai";
 
 import { Wen3ex, IDL } from "../target/types/wen3ex";
 
-const VAULT_AUTHORITY_SEED = "vault-authority-seed";
-const VAULT_TOKEN_SOL_SEED = "vault-token-2-sol-seed";
+const MARKET_TS = 1 << 1;
 
 describe("wen3ex token2sol", async () => {
   // Configure the client to use the local cluster.
@@ -53,8 +52,8 @@ describe("wen3ex token2sol", async () => {
   const marketAccountKP = anchor.web3.Keypair.generate(); //
 
   it("wen3ex token2sol before", async () => {
-    await airDrop(creatorKP.publicKey, 2);
-    await airDrop(takerKP.publicKey, 2);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
     let creatorSol = await printSolBalance(creatorKP.publicKey);
     console.log("before all", creatorSol);
     // 1. create mint
@@ -95,9 +94,36 @@ describe("wen3ex token2sol", async () => {
   });
 
   it("wen3ex token2sol is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    await initConfig(program);
+    const config = await program.account.configAccount.fetch(
+      getConfigPDA(program)[0]
+    );
+    expect(config.pausedMarkets).to.eq(0);
+  });
+
+  it("Create the listing index of the mint", async () => {
+    await program.methods
+      .listingIndexCreate(false)
+      .accounts({
+        index: getMintIndexPDA(goldKP.publicKey)[0],
+        key: goldKP.publicKey,
+        payer: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creatorKP])
+      .rpc();
+  });
+
+  it("Paused market rejects create", async () => {
+    await setPaused(MARKET_TS, true);
+    try {
+      await createToken2SolMarket();
+      assert.fail("create should fail while paused");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("MarketPaused");
+    } finally {
+      await setPaused(MARKET_TS, false);
+    }
   });
 
   it("Create marketAccount token 2 sol", async () => {
@@ -165,6 +191,13 @@ describe("wen3ex token2sol", async () => {
     const userTsMarkets = await program.account.marketTsAccount.all(filter);
     expect(userTsMarkets.length).to.eq(1);
 
+    const mintIndex = await program.account.listingIndexAccount.fetch(
+      getMintIndexPDA(goldKP.publicKey)[0]
+    );
+    expect(mintIndex.listings.map((key) => key.toBase58())).to.deep.eq([
+      marketAccountKP.publicKey.toBase58(),
+    ]);
+
     creatorSol = await printSolBalance(creatorKP.publicKey);
     console.log(4, { creatorSol }); // 1994459840
   });
@@ -197,6 +230,8 @@ describe("wen3ex token2sol", async () => {
     await program.methods
       .marketTsCancel()
       .accounts({
+        mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         creator: creatorKP.publicKey,
         creatorTokenAccount: creatorGoldAta.address,
         vaultTokenAccount: vaultPDA,
@@ -229,6 +264,10 @@ describe("wen3ex token2sol", async () => {
     );
     expect(vaultPDAClosed).to.null;
     expect(marketAccountClosed).to.null;
+    const mintIndex = await program.account.listingIndexAccount.fetch(
+      getMintIndexPDA(goldKP.publicKey)[0]
+    );
+    expect(mintIndex.listings.length).to.eq(0);
   });
 
   // before all 2000000000
@@ -279,6 +318,8 @@ describe("wen3ex token2sol", async () => {
     await program.methods
       .marketTsExchange()
       .accounts({
+        mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         taker: takerKP.publicKey,
         takerTokenAccount: takerGoldAta.address,
 
@@ -289,6 +330,9 @@ describe("wen3ex token2sol", async () => {
         vaultAuthority: vaultAuthorityPDA,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        config: getConfigPDA(program)[0],
+        treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+        salePhase: getSalePhasePDA(creatorKP.publicKey, goldKP.publicKey)[0],
       })
       .signers([takerKP])
       .rpc();
@@ -325,6 +369,44 @@ describe("wen3ex token2sol", async () => {
     return solBalance;
   }
 
+  function getMintIndexPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_INDEX_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getCreatorIndexPDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CREATOR_INDEX_SEED), creator.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getSalePhasePDA(creator: PublicKey, mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(SALE_PHASE_SEED), creator.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getMintEntryPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  async function setPaused(markets: number, paused: boolean) {
+    await program.methods
+      .setPaused(markets, paused)
+      .accounts({
+        config: getConfigPDA(program)[0],
+        admin: (provider as anchor.AnchorProvider).wallet.publicKey,
+      })
+      .rpc();
+  }
+
   function getVaultPDA() {
     return PublicKey.findProgramAddressSync(
       [Buffer.from(VAULT_TOKEN_SOL_SEED), marketAccountKP.publicKey.toBuffer()],
@@ -351,9 +433,16 @@ describe("wen3ex token2sol", async () => {
     await program.methods
       .marketTsCreate(
         new anchor.BN(creatorDepositAmount),
-        new anchor.BN(LAMPORTS_PER_SOL)
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
       )
       .accounts({
+        mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         marketAccount: marketAccountKP.publicKey,
         vaultTokenAccount: vaultPDA,
         creatorTokenAccount: creatorGoldAta.address,
@@ -362,6 +451,8 @@ describe("wen3ex token2sol", async () => {
         systemProgram: anchor.web3.SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getMintEntryPDA(goldKP.publicKey)[0],
       })
       .preInstructions([
         await program.account.marketTsAccount.createInstruction(
@@ -395,14 +486,6 @@ describe("wen3ex token2sol", async () => {
     await createMint(connection, payer, mintAuthority.publicKey, null, 0, kp);
   }
 
-  async function airDrop(pubkey: PublicKey, num: number) {
-    const airdropSignature = await connection.requestAirdrop(
-      pubkey,
-      num * LAMPORTS_PER_SOL
-    );
-    await confirmSignature(airdropSignature);
-  }
-
   async function confirmSignature(signature: string) {
     const latestBlockHash = await connection.getLatestBlockhash();
     await connection.confirmTransaction({
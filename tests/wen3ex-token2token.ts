@@ -3,7 +3,7 @@ import path from "node:path";
 
 import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
-import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { Keypair, PublicKey } from "@solana/web3.js";
 import {
   getAccount,
   TOKEN_PROGRAM_ID,
@@ -14,8 +14,16 @@ import {
 import { assert, expect } from "chai";
 
 import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
 
-const VAULT_AUTHORITY_SEED = "vault-authority-seed";
 const VAULT_TOKEN_2_TOKEN_SEED = "vault-token-2-token-seed";
 
 describe("wen3ex token2token", async () => {
@@ -63,14 +71,16 @@ describe("wen3ex token2token", async () => {
   const marketAccountKP = anchor.web3.Keypair.generate(); //
 
   it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+    await initConfig(program);
+    const config = await program.account.configAccount.fetch(
+      getConfigPDA(program)[0]
+    );
+    expect(config.pausedMarkets).to.eq(0);
   });
 
   it("wen3ex token2token before", async () => {
-    await airDrop(creatorKP.publicKey, 2);
-    await airDrop(takerKP.publicKey, 2);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
 
     // 1. create mint
     await initMint(goldKP, creatorKP);
@@ -190,6 +200,26 @@ describe("wen3ex token2token", async () => {
     expect(userTTMarkets.length).to.eq(1);
   });
 
+  it("Quote the unit price of marketAccount token 2 token", async () => {
+    const marketAccount = await program.account.marketTtAccount.fetch(
+      marketAccountKP.publicKey
+    );
+    expect(marketAccount.depositDecimals).to.eq(0);
+    expect(marketAccount.receiveDecimals).to.eq(0);
+
+    const unitPrice = await program.methods
+      .marketUnitPrice()
+      .accounts({ marketAccount: marketAccountKP.publicKey })
+      .view();
+    assert.ok(unitPrice.baseMint.equals(goldKP.publicKey));
+    assert.ok(unitPrice.quoteMint.equals(rubyKP.publicKey));
+    // takerDepositAmount ruby for creatorDepositAmount gold, both 0 decimals
+    const price = new anchor.BN(takerDepositAmount)
+      .mul(new anchor.BN(10).pow(new anchor.BN(unitPrice.decimals)))
+      .div(new anchor.BN(creatorDepositAmount));
+    expect(unitPrice.price.toString()).to.eq(price.toString());
+  });
+
   it("Close marketAccount token 2 token without exchange", async () => {
     let creatorGoldAta = await getATA(
       creatorKP,
@@ -214,6 +244,8 @@ describe("wen3ex token2token", async () => {
     await program.methods
       .marketTtCancel()
       .accounts({
+        mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         creator: creatorKP.publicKey,
         depositTokenAccount: creatorGoldAta.address,
         vaultTokenAccount: vaultPDA,
@@ -265,6 +297,8 @@ describe("wen3ex token2token", async () => {
     await program.methods
       .marketTtExchange()
       .accounts({
+        mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+        creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
         taker: takerKP.publicKey,
         takerDepositTokenAccount: takerRubyAta.address,
         takerReceiveTokenAccount: takerGoldAta.address,
@@ -276,6 +310,7 @@ describe("wen3ex token2token", async () => {
         vaultTokenAccount: vaultPDA,
         vaultAuthority: vaultAuthorityPDA,
         tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
       })
       .signers([takerKP])
       .rpc();
@@ -310,6 +345,27 @@ describe("wen3ex token2token", async () => {
     expect(marketAccountClosed).to.null;
   });
 
+  function getMintIndexPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_INDEX_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getCreatorIndexPDA(creator: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CREATOR_INDEX_SEED), creator.toBuffer()],
+      program.programId
+    );
+  }
+
+  function getMintEntryPDA(mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(MINT_ENTRY_SEED), mint.toBuffer()],
+      program.programId
+    );
+  }
+
   function getVaultPDA() {
     return PublicKey.findProgramAddressSync(
       [
@@ -346,18 +402,27 @@ describe("wen3ex token2token", async () => {
           new anchor.BN(20),
           new anchor.BN(10),
           goldKP.publicKey,
-          rubyKP.publicKey
+          rubyKP.publicKey,
+          new anchor.BN(0),
+          new anchor.BN(0),
+          new anchor.BN(0)
         )
         .accounts({
+          mintIndex: getMintIndexPDA(goldKP.publicKey)[0],
+          creatorIndex: getCreatorIndexPDA(creatorKP.publicKey)[0],
           creator: creatorKP.publicKey,
           marketAccount: marketAccountKP.publicKey,
           vaultTokenAccount: vaultPDA,
           mint: goldKP.publicKey,
           depositTokenAccount: creatorGoldAta.address,
           receiveTokenAccount: creatorRubyAta.address,
+          receiveMint: rubyKP.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
           tokenProgram: TOKEN_PROGRAM_ID,
+          config: getConfigPDA(program)[0],
+          depositMintEntry: getMintEntryPDA(goldKP.publicKey)[0],
+          receiveMintEntry: getMintEntryPDA(rubyKP.publicKey)[0],
         })
         .preInstructions([
           await program.account.marketTtAccount.createInstruction(
@@ -395,14 +460,6 @@ describe("wen3ex token2token", async () => {
     await createMint(connection, payer, mintAuthority.publicKey, null, 0, kp);
   }
 
-  async function airDrop(pubkey: PublicKey, num: number) {
-    const airdropSignature = await connection.requestAirdrop(
-      pubkey,
-      num * LAMPORTS_PER_SOL
-    );
-    await confirmSignature(airdropSignature);
-  }
-
   async function confirmSignature(signature: string) {
     const latestBlockHash = await connection.getLatestBlockhash();
     await connection.confirmTransaction({
@@ -0,0 +1,255 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_TOKEN_SOL_SEED,
+  VAULT_SOL_TOKEN_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const VAULT_TOKEN_2_TOKEN_SEED = "vault-token-2-token-seed";
+// UnitPrice.price is scaled by 10^PRICE_DECIMALS
+const PRICE_DECIMALS = 12;
+
+describe("wen3ex unit price", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  // a 6 decimals mint, like usdc, and a 9 decimals one, like sol
+  let mint6: PublicKey;
+  let mint9: PublicKey;
+  let creatorToken6: PublicKey;
+  let creatorToken9: PublicKey;
+
+  it("unit price before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 5);
+    mint6 = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      6
+    );
+    mint9 = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      9
+    );
+    creatorToken6 = await getATA(mint6);
+    creatorToken9 = await getATA(mint9);
+    await mintTo(
+      connection,
+      creatorKP,
+      mint6,
+      creatorToken6,
+      creatorKP,
+      100 * 10 ** 6
+    );
+    await mintTo(
+      connection,
+      creatorKP,
+      mint9,
+      creatorToken9,
+      creatorKP,
+      100 * 10 ** 9
+    );
+  });
+
+  it("Quote token 2 token across 6 and 9 decimals", async () => {
+    // 2 whole mint6 for 3 whole mint9, 1.5 mint9 each
+    const listing = await createToken2TokenMarket(
+      mint6,
+      2 * 10 ** 6,
+      mint9,
+      3 * 10 ** 9
+    );
+    const unitPrice = await quote(listing);
+    assert.ok(unitPrice.baseMint.equals(mint6));
+    assert.ok(unitPrice.quoteMint.equals(mint9));
+    expect(unitPrice.price.toString()).to.eq(scaled(15, 1));
+  });
+
+  it("Quote token 2 token across 9 and 6 decimals", async () => {
+    // 3 whole mint9 for 2 whole mint6, 0.666... mint6 each, rounded down
+    const listing = await createToken2TokenMarket(
+      mint9,
+      3 * 10 ** 9,
+      mint6,
+      2 * 10 ** 6
+    );
+    const unitPrice = await quote(listing);
+    assert.ok(unitPrice.baseMint.equals(mint9));
+    assert.ok(unitPrice.quoteMint.equals(mint6));
+    expect(unitPrice.price.toString()).to.eq("666666666666");
+  });
+
+  it("Quote token 2 sol of a 6 decimals mint", async () => {
+    // 4 whole mint6 for 1 sol, 0.25 sol each
+    const listing = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(4 * 10 ** 6),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: listing.publicKey,
+        vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey),
+        creatorTokenAccount: creatorToken6,
+        mint: mint6,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint6),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint6),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(listing),
+      ])
+      .signers([listing, creatorKP])
+      .rpc();
+
+    const unitPrice = await quote(listing);
+    assert.ok(unitPrice.baseMint.equals(mint6));
+    assert.ok(unitPrice.quoteMint.equals(PublicKey.default));
+    expect(unitPrice.price.toString()).to.eq(scaled(25, 2));
+  });
+
+  it("Quote sol 2 token of a 6 decimals mint", async () => {
+    // 1 sol for half a mint6, 2 sol each
+    const listing = Keypair.generate();
+    await program.methods
+      .marketStCreate(
+        new anchor.BN(10 ** 6 / 2),
+        new anchor.BN(LAMPORTS_PER_SOL),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        marketAccount: listing.publicKey,
+        vaultTokenAccount: getPDA(VAULT_SOL_TOKEN_SEED, listing.publicKey),
+        creatorTokenAccount: creatorToken6,
+        mint: mint6,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint6),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint6),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketStAccount.createInstruction(listing),
+      ])
+      .signers([listing, creatorKP])
+      .rpc();
+
+    const unitPrice = await quote(listing);
+    assert.ok(unitPrice.baseMint.equals(mint6));
+    assert.ok(unitPrice.quoteMint.equals(PublicKey.default));
+    expect(unitPrice.price.toString()).to.eq(scaled(2, 0));
+  });
+
+  async function createToken2TokenMarket(
+    depositMint: PublicKey,
+    depositAmount: number,
+    receiveMint: PublicKey,
+    receiveAmount: number
+  ) {
+    const listing = Keypair.generate();
+    await program.methods
+      .marketTtCreate(
+        new anchor.BN(depositAmount),
+        new anchor.BN(receiveAmount),
+        depositMint,
+        receiveMint,
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accounts({
+        mintIndex: getPDA(MINT_INDEX_SEED, depositMint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+        creator: creatorKP.publicKey,
+        marketAccount: listing.publicKey,
+        vaultTokenAccount: getPDA(VAULT_TOKEN_2_TOKEN_SEED, listing.publicKey),
+        mint: depositMint,
+        depositTokenAccount: await getATA(depositMint),
+        receiveTokenAccount: await getATA(receiveMint),
+        receiveMint,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        depositMintEntry: getPDA(MINT_ENTRY_SEED, depositMint),
+        receiveMintEntry: getPDA(MINT_ENTRY_SEED, receiveMint),
+      })
+      .preInstructions([
+        await program.account.marketTtAccount.createInstruction(listing),
+      ])
+      .signers([listing, creatorKP])
+      .rpc();
+    return listing;
+  }
+
+  async function quote(listing: Keypair) {
+    return await program.methods
+      .marketUnitPrice()
+      .accounts({ marketAccount: listing.publicKey })
+      .view();
+  }
+
+  // digits * 10^-exponent, scaled by 10^PRICE_DECIMALS
+  function scaled(digits: number, exponent: number) {
+    return new anchor.BN(digits)
+      .mul(new anchor.BN(10).pow(new anchor.BN(PRICE_DECIMALS - exponent)))
+      .toString();
+  }
+
+  async function getATA(mint: PublicKey) {
+    return (
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        creatorKP,
+        mint,
+        creatorKP.publicKey
+      )
+    ).address;
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+});
@@ -0,0 +1,298 @@
+import * as anchor from "@project-serum/anchor";
+import { Program } from "@project-serum/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert, expect } from "chai";
+
+import { Wen3ex } from "../target/types/wen3ex";
+import {
+  VAULT_AUTHORITY_SEED,
+  VAULT_TOKEN_SOL_SEED,
+  MINT_ENTRY_SEED,
+  MINT_INDEX_SEED,
+  CREATOR_INDEX_SEED,
+  SALE_PHASE_SEED,
+  getConfigPDA,
+  initConfig,
+  airDrop,
+} from "./helpers";
+
+const VESTING_SEED = "vesting-seed";
+const VAULT_VESTING_SEED = "vault-vesting-seed";
+
+describe("wen3ex vesting", async () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
+  const { connection } = provider;
+  const program = anchor.workspace.Wen3Ex as Program<Wen3ex>;
+
+  const creatorKP = Keypair.generate();
+  const takerKP = Keypair.generate();
+  const tokenAmount = 60;
+  const vestingCliff = 2;
+  const vestingDuration = 6;
+  let mint: PublicKey;
+  let listing: Keypair;
+
+  it("vesting before", async () => {
+    await initConfig(program);
+    await airDrop(connection, creatorKP.publicKey, 2);
+    await airDrop(connection, takerKP.publicKey, 2);
+    mint = await createMint(
+      connection,
+      creatorKP,
+      creatorKP.publicKey,
+      null,
+      0
+    );
+    await mintTo(
+      connection,
+      creatorKP,
+      mint,
+      await getATA(creatorKP),
+      creatorKP,
+      1_000
+    );
+  });
+
+  it("A vesting listing of no tokens is refused", async () => {
+    try {
+      await createListing(0);
+      assert.fail("create should fail for a vesting listing of 0 tokens");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("InvalidAmount");
+    }
+  });
+
+  it("A vesting listing can't be pegged", async () => {
+    listing = await createListing(tokenAmount);
+    try {
+      await program.methods
+        .marketTsSetPeg(0, new anchor.BN(60), 100)
+        .accounts({
+          creator: creatorKP.publicKey,
+          marketAccount: listing.publicKey,
+          oracle: PublicKey.default,
+        })
+        .signers([creatorKP])
+        .rpc();
+      assert.fail("set peg should fail on a vesting listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VestingNotPeggable");
+    }
+  });
+
+  it("A vesting listing is only taken with exchange_vested", async () => {
+    try {
+      await program.methods
+        .marketTsExchange()
+        .accounts(await exchangeAccounts())
+        .signers([takerKP])
+        .rpc();
+      assert.fail("exchange should fail on a vesting listing");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("VestingRequired");
+    }
+  });
+
+  it("Take a vesting listing, the tokens wait in its vault", async () => {
+    await program.methods
+      .marketTsExchangeVested()
+      .accounts({
+        exchange: await exchangeAccounts(),
+        vesting: getVestingPDA(),
+        vestingVault: getPDA(VAULT_VESTING_SEED, getVestingPDA()),
+        vestingAuthority: getPDA(VAULT_AUTHORITY_SEED, getVestingPDA()),
+        mint,
+        taker: takerKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([takerKP])
+      .rpc();
+
+    const vesting = await program.account.vestingAccount.fetch(
+      getVestingPDA()
+    );
+    expect(vesting.beneficiary.toBase58()).to.eq(
+      takerKP.publicKey.toBase58()
+    );
+    expect(vesting.total.toNumber()).to.eq(tokenAmount);
+    expect(vesting.claimed.toNumber()).to.eq(0);
+    expect(vesting.cliffTime.toNumber()).to.eq(
+      vesting.startTime.toNumber() + vestingCliff
+    );
+    expect(vesting.endTime.toNumber()).to.eq(
+      vesting.startTime.toNumber() + vestingDuration
+    );
+    const vault = await getAccount(
+      connection,
+      getPDA(VAULT_VESTING_SEED, getVestingPDA())
+    );
+    expect(Number(vault.amount)).to.eq(tokenAmount);
+    expect(await connection.getAccountInfo(listing.publicKey)).to.null;
+  });
+
+  it("Nothing is claimed before the cliff", async () => {
+    const vesting = await program.account.vestingAccount.fetch(
+      getVestingPDA()
+    );
+    if ((await now()) >= vesting.cliffTime.toNumber()) {
+      // too slow a validator to see the cliff, nothing to test
+      return;
+    }
+    try {
+      await claim();
+      assert.fail("claim should fail before the cliff");
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.eq("NothingToClaim");
+    }
+  });
+
+  it("Claim after the cliff, then the rest after the end", async () => {
+    const vesting = await program.account.vestingAccount.fetch(
+      getVestingPDA()
+    );
+    await waitUntil(vesting.cliffTime.toNumber());
+    await claim();
+    const claimed = (
+      await program.account.vestingAccount.fetchNullable(getVestingPDA())
+    )?.claimed.toNumber();
+    if (claimed !== undefined) {
+      expect(claimed).to.gt(0);
+      expect(claimed).to.lt(tokenAmount);
+      await waitUntil(vesting.endTime.toNumber());
+      await claim();
+    }
+
+    const takerTokens = await getAccount(connection, await getATA(takerKP));
+    expect(Number(takerTokens.amount)).to.eq(tokenAmount);
+    // all out, both accounts are closed
+    expect(await connection.getAccountInfo(getVestingPDA())).to.null;
+    expect(
+      await connection.getAccountInfo(
+        getPDA(VAULT_VESTING_SEED, getVestingPDA())
+      )
+    ).to.null;
+  });
+
+  async function claim() {
+    await program.methods
+      .claimVested()
+      .accounts({
+        beneficiary: takerKP.publicKey,
+        beneficiaryTokenAccount: await getATA(takerKP),
+        vesting: getVestingPDA(),
+        vaultTokenAccount: getPDA(VAULT_VESTING_SEED, getVestingPDA()),
+        vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, getVestingPDA()),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([takerKP])
+      .rpc();
+  }
+
+  async function exchangeAccounts() {
+    return {
+      taker: takerKP.publicKey,
+      takerTokenAccount: await getATA(takerKP),
+      creator: creatorKP.publicKey,
+      marketAccount: listing.publicKey,
+      vaultTokenAccount: getPDA(VAULT_TOKEN_SOL_SEED, listing.publicKey),
+      mint,
+      vaultAuthority: getPDA(VAULT_AUTHORITY_SEED, listing.publicKey),
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      config: getConfigPDA(program)[0],
+      treasury: (provider as anchor.AnchorProvider).wallet.publicKey,
+      salePhase: PublicKey.findProgramAddressSync(
+        [
+          Buffer.from(SALE_PHASE_SEED),
+          creatorKP.publicKey.toBuffer(),
+          mint.toBuffer(),
+        ],
+        program.programId
+      )[0],
+      mintIndex: getPDA(MINT_INDEX_SEED, mint),
+      creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+    };
+  }
+
+  async function createListing(amount: number) {
+    const marketAccountKP = Keypair.generate();
+    await program.methods
+      .marketTsCreate(
+        new anchor.BN(amount),
+        new anchor.BN(LAMPORTS_PER_SOL / 10),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(0),
+        new anchor.BN(vestingCliff),
+        new anchor.BN(vestingDuration)
+      )
+      .accounts({
+        marketAccount: marketAccountKP.publicKey,
+        vaultTokenAccount: getPDA(
+          VAULT_TOKEN_SOL_SEED,
+          marketAccountKP.publicKey
+        ),
+        creatorTokenAccount: await getATA(creatorKP),
+        mint,
+        creator: creatorKP.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        config: getConfigPDA(program)[0],
+        mintEntry: getPDA(MINT_ENTRY_SEED, mint),
+        mintIndex: getPDA(MINT_INDEX_SEED, mint),
+        creatorIndex: getPDA(CREATOR_INDEX_SEED, creatorKP.publicKey),
+      })
+      .preInstructions([
+        await program.account.marketTsAccount.createInstruction(
+          marketAccountKP
+        ),
+      ])
+      .signers([marketAccountKP, creatorKP])
+      .rpc();
+    return marketAccountKP;
+  }
+
+  // the validator's clock, which vesting is checked against
+  async function now() {
+    const blockTime = await connection.getBlockTime(await connection.getSlot());
+    return blockTime ?? Math.floor(Date.now() / 1000);
+  }
+
+  async function waitUntil(time: number) {
+    while ((await now()) < time) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+  }
+
+  function getVestingPDA() {
+    return getPDA(VESTING_SEED, listing.publicKey);
+  }
+
+  function getPDA(seed: string, key: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), key.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  async function getATA(owner: Keypair) {
+    const account = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      mint,
+      owner.publicKey
+    );
+    return account.address;
+  }
+});